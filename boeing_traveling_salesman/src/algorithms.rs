@@ -1,73 +1,634 @@
 
-use std::f64::INFINITY;
 use std::collections::HashMap;
 use bitvec::prelude::*;
-use std::process;
+use std::time::{Duration, Instant};
+use crate::rng::SeededRng;
+use rayon::prelude::*;
+use std::fmt;
+use crate::feasibility::{self, FeasibilityReport};
 
-pub fn held_karp_algorithm(distance_matrix: &Vec<Vec<f64>>) -> (f64, Vec<usize>) { 
-    // charles files could possibly have no valid path with start node 0 thus this will try start node 1 and so on till valid path found
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HeldKarpMode {
+    FixedStart(usize),            // open path leaving the depot, may end anywhere
+    FixedStartEnd(usize, usize),  // open path from the first node to the second
+    ClosedTour(usize),            // leaves the depot and comes back to it, path lists the depot at both ends
+    BestOpenPath,                 // cheapest open path over every start and end
+}
+
+pub struct HeldKarpConfig {
+    pub threads: usize,            // worker threads for the DP layers, 0 uses every core
+    pub memory_limit_bytes: usize, // refuse instead of allocating more than this for the tables
+    pub precedences: Vec<(usize, usize)>, // (before, after) pairs, after can't be added to a mask that doesn't hold before
+}
+
+impl Default for HeldKarpConfig {
+    fn default() -> Self {
+        HeldKarpConfig {
+            threads: 0,
+            memory_limit_bytes: 8 << 30, // 8 GiB is enough for a 25 node fixed depot solve
+            precedences: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HeldKarpError {
+    DepotOutOfRange { depot: usize, nodes: usize },
+    PrecedenceOutOfRange { node: usize, nodes: usize },
+    TooManyNodes { nodes: usize, max: usize },
+    OutOfMemory { required_bytes: usize, limit_bytes: usize },
+    ThreadPool(String),
+    Infeasible(Box<FeasibilityReport>), // the pre-check proved there is no path, nothing was allocated
+}
+
+impl fmt::Display for HeldKarpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeldKarpError::DepotOutOfRange { depot, nodes } => write!(f, "depot {} is not one of the {} locations", depot, nodes),
+            HeldKarpError::PrecedenceOutOfRange { node, nodes } => write!(f, "precedence names location {} but there are only {} locations", node, nodes),
+            HeldKarpError::TooManyNodes { nodes, max } => write!(f, "{} locations is more than the {} Held-Karp supports", nodes, max),
+            HeldKarpError::OutOfMemory { required_bytes, limit_bytes } => write!(f, "Held-Karp needs {} MiB of tables but the limit is {} MiB", required_bytes >> 20, limit_bytes >> 20),
+            HeldKarpError::ThreadPool(message) => write!(f, "could not start the Held-Karp thread pool: {}", message),
+            HeldKarpError::Infeasible(report) => write!(f, "{}", report),
+        }
+    }
+}
+
+impl std::error::Error for HeldKarpError {}
+
+const HELD_KARP_MAX_NODES: usize = 30; // masks are u32 and parents u8, memory runs out well before this anyway
+const HELD_KARP_CHUNK: usize = 4096; // masks per parallel work item
+
+pub fn held_karp_memory_estimate(nodes: usize, mode: HeldKarpMode) -> usize {
+    // bytes for the cost (f32) and parent (u8) tables. Every mask only stores a slot for the nodes it contains,
+    // so m free nodes need m * 2^(m - 1) slots. A fixed depot is never in the masks, BestOpenPath leaves every node free
+    let free_nodes = match mode {
+        HeldKarpMode::BestOpenPath => nodes,
+        _ => nodes.saturating_sub(1),
+    };
+    if free_nodes == 0 {
+        return 0;
+    }
+    let slots = (free_nodes as u128) << (free_nodes - 1);
+    usize::try_from(slots * (std::mem::size_of::<f32>() + std::mem::size_of::<u8>()) as u128).unwrap_or(usize::MAX)
+}
+
+fn colex_rank(mask: u32, binomial: &[Vec<usize>]) -> usize { // position of mask among the masks with the same popcount
+    let mut rank = 0;
+    let mut remaining = mask;
+    let mut index = 1;
+    while remaining != 0 {
+        let bit = remaining.trailing_zeros() as usize;
+        rank += binomial[bit][index];
+        remaining &= remaining - 1;
+        index += 1;
+    }
+    rank
+}
+
+fn colex_unrank(mut rank: usize, popcount: usize, binomial: &[Vec<usize>]) -> u32 {
+    let mut mask = 0;
+    let mut bit = binomial.len() - 1;
+    for index in (1..=popcount).rev() {
+        while binomial[bit][index] > rank {
+            bit -= 1;
+        }
+        mask |= 1 << bit;
+        rank -= binomial[bit][index];
+        bit = bit.saturating_sub(1);
+    }
+    mask
+}
+
+pub fn held_karp_algorithm(distance_matrix: &[Vec<f64>], mode: HeldKarpMode, config: &HeldKarpConfig) -> Result<(f64, Vec<usize>), HeldKarpError> {
+    // one DP over (visited set, last node), computed once for the mode. A fixed depot is taken out of the masks and
+    // only prices the first arc, for BestOpenPath a virtual source with free arcs to every node plays that role.
+    // The tables are stored per popcount layer with masks in colex order and one f32 cost / u8 parent per node in
+    // the mask, so layer k only reads layer k - 1 and its chunks can be filled in parallel
     let n = distance_matrix.len();
-    let full_mask = (1 << n) - 1;
-    
-    for start_node in 0..n { // Try different start nodes until a valid path is found
-        let mut dp = vec![vec![INFINITY; n]; 1 << n];
-        let mut parent = vec![vec![-1; n]; 1 << n];
-
-        // Base case: Starting at start_node, cost is 0
-        dp[1 << start_node][start_node] = 0.0;
-
-        // Iterate over all subsets of nodes
-        for mask in 1..(1 << n) {
-            for last_visited in 0..n {
-                if mask & (1 << last_visited) != 0 {
-                    for prev in 0..n {
-                        if prev != last_visited && (mask & (1 << prev)) != 0 {
-                            let prev_mask = mask ^ (1 << last_visited);
-                            let new_cost = dp[prev_mask][prev] + distance_matrix[prev][last_visited];
-                            if new_cost < dp[mask][last_visited] {
-                                dp[mask][last_visited] = new_cost;
-                                parent[mask][last_visited] = prev as isize;
+    if n == 0 {
+        return Ok((f64::INFINITY, Vec::new()));
+    }
+    let (depot, end_node) = match mode {
+        HeldKarpMode::FixedStart(start) | HeldKarpMode::ClosedTour(start) => (Some(start), None),
+        HeldKarpMode::FixedStartEnd(start, end) => (Some(start), Some(end)),
+        HeldKarpMode::BestOpenPath => (None, None),
+    };
+    if let Some(&node) = depot.iter().chain(end_node.iter()).find(|&&node| node >= n) {
+        return Err(HeldKarpError::DepotOutOfRange { depot: node, nodes: n });
+    }
+    if let Some(&(before, after)) = config.precedences.iter().find(|&&(before, after)| before >= n || after >= n) {
+        return Err(HeldKarpError::PrecedenceOutOfRange { node: before.max(after), nodes: n });
+    }
+    let report = feasibility::check_matrix(distance_matrix); // O(n^2), fails fast before the 2^n tables
+    if !report.is_feasible() {
+        return Err(HeldKarpError::Infeasible(Box::new(report)));
+    }
+    if n > HELD_KARP_MAX_NODES {
+        return Err(HeldKarpError::TooManyNodes { nodes: n, max: HELD_KARP_MAX_NODES });
+    }
+    let required_bytes = held_karp_memory_estimate(n, mode);
+    if required_bytes > config.memory_limit_bytes {
+        return Err(HeldKarpError::OutOfMemory { required_bytes, limit_bytes: config.memory_limit_bytes });
+    }
+
+    let free: Vec<usize> = (0..n).filter(|&node| Some(node) != depot).collect(); // nodes that live in the masks
+    let m = free.len();
+    if m == 0 { // a single location with a depot, the path is just the depot
+        return Ok((0.0, vec![depot.unwrap()]));
+    }
+    // required[i] holds the free nodes that have to be in the mask before free[i] joins it. The depot is always first,
+    // so it satisfies every pair it comes first in and breaks every pair it comes second in
+    let mut required = vec![0u32; m];
+    for &(before, after) in &config.precedences {
+        if Some(after) == depot {
+            return Ok((f64::INFINITY, Vec::new()));
+        }
+        if Some(before) != depot {
+            let slot_of = |node: usize| free.iter().position(|&free_node| free_node == node).unwrap();
+            required[slot_of(after)] |= 1 << slot_of(before);
+        }
+    }
+    let distances: Vec<f32> = free.iter().flat_map(|&from| free.iter().map(move |&to| distance_matrix[from][to] as f32)).collect();
+    let source_cost: Vec<f32> = free.iter().map(|&node| depot.map_or(0.0, |start| distance_matrix[start][node] as f32)).collect();
+    let mut binomial = vec![vec![0usize; m + 1]; m + 1]; // binomial[a][b] = a choose b
+    for a in 0..=m {
+        binomial[a][0] = 1;
+        for b in 1..=a {
+            binomial[a][b] = binomial[a - 1][b - 1] + binomial[a - 1][b];
+        }
+    }
+
+    // allocate every layer up front so a failed allocation is an error instead of an abort halfway through
+    let mut cost_layers: Vec<Vec<f32>> = Vec::with_capacity(m + 1);
+    let mut parent_layers: Vec<Vec<u8>> = Vec::with_capacity(m + 1);
+    for (k, &masks) in binomial[m].iter().enumerate() {
+        let slots = masks * k;
+        let mut costs = Vec::new();
+        let mut parents = Vec::new();
+        if costs.try_reserve_exact(slots).is_err() || parents.try_reserve_exact(slots).is_err() {
+            return Err(HeldKarpError::OutOfMemory { required_bytes, limit_bytes: config.memory_limit_bytes });
+        }
+        costs.resize(slots, f32::INFINITY);
+        parents.resize(slots, u8::MAX);
+        cost_layers.push(costs);
+        parent_layers.push(parents);
+    }
+    cost_layers[1].copy_from_slice(&source_cost); // layer 1 in colex order is just node 0, 1, 2, ...
+    for (cost, &needs) in cost_layers[1].iter_mut().zip(&required) {
+        if needs != 0 {
+            *cost = f32::INFINITY;
+        }
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(config.threads).build().map_err(|e| HeldKarpError::ThreadPool(e.to_string()))?;
+    pool.install(|| {
+        for k in 2..=m {
+            let (lower_costs, upper_costs) = cost_layers.split_at_mut(k);
+            let previous = &lower_costs[k - 1];
+            upper_costs[0].par_chunks_mut(HELD_KARP_CHUNK * k).zip(parent_layers[k].par_chunks_mut(HELD_KARP_CHUNK * k)).enumerate().for_each(|(chunk, (costs, parents))| {
+                let mut mask = colex_unrank(chunk * HELD_KARP_CHUNK, k, &binomial);
+                let mut bits = vec![0usize; k];
+                let mut without = vec![0usize; k]; // rank of the mask with bits[i] removed
+                for (slot_costs, slot_parents) in costs.chunks_mut(k).zip(parents.chunks_mut(k)) {
+                    let mut remaining = mask;
+                    for bit in bits.iter_mut() {
+                        *bit = remaining.trailing_zeros() as usize;
+                        remaining &= remaining - 1;
+                    }
+                    // removing bits[i] keeps the colex terms below it and shifts the ones above it down an index
+                    let mut below = 0;
+                    for i in 0..k {
+                        let above: usize = (i + 1..k).map(|j| binomial[bits[j]][j]).sum();
+                        without[i] = below + above;
+                        below += binomial[bits[i]][i + 1];
+                    }
+                    for i in 0..k {
+                        let last = bits[i];
+                        if required[last] & !mask != 0 { // a predecessor of last isn't visited yet, the slot stays infinite
+                            continue;
+                        }
+                        let base = without[i] * (k - 1);
+                        let mut best = f32::INFINITY;
+                        let mut best_parent = u8::MAX;
+                        for j in 0..k {
+                            if j == i {
+                                continue;
+                            }
+                            let slot = if j < i { j } else { j - 1 };
+                            let cost = previous[base + slot] + distances[bits[j] * m + last];
+                            if cost < best {
+                                best = cost;
+                                best_parent = bits[j] as u8;
                             }
                         }
+                        slot_costs[i] = best;
+                        slot_parents[i] = best_parent;
+                    }
+                    if mask != u32::MAX { // Gosper's hack, next mask with the same popcount in colex order
+                        let lowest = mask & mask.wrapping_neg();
+                        let ripple = mask.wrapping_add(lowest);
+                        mask = ripple | (((mask ^ ripple) >> 2) / lowest);
                     }
                 }
-            }
+            });
+        }
+    });
+
+    // Find the minimum cost end, the closed tour also pays the arc back to the depot
+    let mut min_cost = f32::INFINITY;
+    let mut last_slot = None;
+    for (slot, &cost) in cost_layers[m].iter().enumerate() {
+        let cost = match mode {
+            HeldKarpMode::ClosedTour(start) => cost + distance_matrix[free[slot]][start] as f32,
+            _ => cost,
+        };
+        if end_node.is_some_and(|end| end != free[slot]) {
+            continue;
+        }
+        if cost < min_cost {
+            min_cost = cost;
+            last_slot = Some(slot);
         }
+    }
+    let Some(mut slot) = last_slot else {
+        return Ok((f64::INFINITY, Vec::new()));
+    };
+
+    // Reconstruct the path from the parents, walking down the layers
+    let mut mask: u32 = (1u32 << m) - 1; // m < 32 by HELD_KARP_MAX_NODES
+    let mut path = Vec::with_capacity(n + 1);
+    for k in (1..=m).rev() {
+        let index = colex_rank(mask, &binomial) * k + slot;
+        let last = (0..m).filter(|&node| mask & (1 << node) != 0).nth(slot).unwrap();
+        path.push(free[last]);
+        mask ^= 1 << last;
+        let parent = parent_layers[k][index] as usize;
+        if k > 1 {
+            slot = (mask & ((1 << parent) - 1)).count_ones() as usize;
+        }
+    }
+    if let Some(start) = depot {
+        path.push(start);
+    }
+    path.reverse();
+    if let HeldKarpMode::ClosedTour(start) = mode {
+        path.push(start);
+    }
+    // the tables are f32, report the exact cost of the chosen path
+    let cost = path.windows(2).map(|arc| distance_matrix[arc[0]][arc[1]]).sum();
+    Ok((cost, path))
+}
+
+pub struct AnnealingSchedule { // geometric cooling, temperature is multiplied by cooling_rate every iterations_per_temperature moves
+    pub initial_temperature: f64,
+    pub cooling_rate: f64,
+    pub minimum_temperature: f64,
+    pub iterations_per_temperature: usize,
+}
+
+impl Default for AnnealingSchedule {
+    fn default() -> Self {
+        AnnealingSchedule {
+            initial_temperature: 1.0, // arcs in the generated worlds cost 1 to 10
+            cooling_rate: 0.99,
+            minimum_temperature: 0.001,
+            iterations_per_temperature: 5000,
+        }
+    }
+}
+
+pub struct AnnealingConfig {
+    pub seed: u64,
+    pub schedule: AnnealingSchedule,
+    pub time_budget: Duration, // wall clock limit, stops early even if the schedule is not finished
+    pub max_iterations: Option<usize>, // iteration cap, runs that stop on this instead of the clock are reproducible per seed
+    pub missing_arc_penalty: Option<f64>, // cost charged for a missing arc, None uses n * largest arc so any feasible path beats any infeasible one
+    pub initial_path: Option<Vec<usize>>, // starting path (for example nearest neighbor), None starts from a random order
+    pub precedences: Vec<(usize, usize)>, // (before, after) pairs, each broken pair is charged like a missing arc and the path is never kept as the best
+}
+
+impl Default for AnnealingConfig {
+    fn default() -> Self {
+        AnnealingConfig {
+            seed: 0,
+            schedule: AnnealingSchedule::default(),
+            time_budget: Duration::from_secs(10),
+            max_iterations: None,
+            missing_arc_penalty: None,
+            initial_path: None,
+            precedences: Vec::new(),
+        }
+    }
+}
+
+pub struct AnnealingTracePoint { // snapshot taken every time the temperature drops
+    pub iteration: usize,
+    pub temperature: f64,
+    pub current_cost: f64, // includes missing arc and broken precedence penalties
+    pub best_cost: f64, // best feasible cost so far, infinity until a feasible path is seen
+}
 
-        // Find the minimum cost path
-        let mut min_cost = INFINITY;
-        let mut last_node = -1;
-        for i in 0..n {
-            let cost = dp[full_mask][i];
-            if cost < min_cost {
-                min_cost = cost;
-                last_node = i as isize;
+pub struct AnnealingResult {
+    pub cost: f64, // infinity and an empty path if no feasible path was ever found
+    pub path: Vec<usize>,
+    pub iterations: usize,
+    pub trace: Vec<AnnealingTracePoint>,
+}
+
+#[derive(Clone, Copy)]
+enum PathMove { // moves on an open path, positions are indexes into the path
+    Reverse(usize, usize), // reverse path[i..=j], i < j
+    Swap(usize, usize), // swap path[i] and path[j], i < j
+    Relocate(usize, usize), // take the node at path[i] out and put it back so it sits at position j
+    SegmentSwap(usize, usize, usize), // path[i..j] and path[j..k] trade places without reversing, i < j < k
+}
+
+impl PathMove {
+    fn changed_arcs(self, path: &[usize], old_arcs: &mut Vec<(usize, usize)>, new_arcs: &mut Vec<(usize, usize)>) {
+        // arcs the move removes and arcs it adds, everything else in the path is untouched
+        let n = path.len();
+        old_arcs.clear();
+        new_arcs.clear();
+        match self {
+            PathMove::Reverse(i, j) => {
+                if i > 0 {
+                    old_arcs.push((path[i - 1], path[i]));
+                    new_arcs.push((path[i - 1], path[j]));
+                }
+                if j < n - 1 {
+                    old_arcs.push((path[j], path[j + 1]));
+                    new_arcs.push((path[i], path[j + 1]));
+                }
+                for k in i..j {
+                    old_arcs.push((path[k], path[k + 1]));
+                    new_arcs.push((path[k + 1], path[k]));
+                }
+            }
+            PathMove::Swap(i, j) => {
+                if i > 0 {
+                    old_arcs.push((path[i - 1], path[i]));
+                    new_arcs.push((path[i - 1], path[j]));
+                }
+                if j < n - 1 {
+                    old_arcs.push((path[j], path[j + 1]));
+                    new_arcs.push((path[i], path[j + 1]));
+                }
+                if j == i + 1 {
+                    old_arcs.push((path[i], path[j]));
+                    new_arcs.push((path[j], path[i]));
+                } else {
+                    old_arcs.push((path[i], path[i + 1]));
+                    old_arcs.push((path[j - 1], path[j]));
+                    new_arcs.push((path[j], path[i + 1]));
+                    new_arcs.push((path[j - 1], path[i]));
+                }
+            }
+            PathMove::Relocate(i, j) if i < j => { // path[i..=j] rotates left by one
+                if i > 0 {
+                    old_arcs.push((path[i - 1], path[i]));
+                    new_arcs.push((path[i - 1], path[i + 1]));
+                }
+                old_arcs.push((path[i], path[i + 1]));
+                new_arcs.push((path[j], path[i]));
+                if j < n - 1 {
+                    old_arcs.push((path[j], path[j + 1]));
+                    new_arcs.push((path[i], path[j + 1]));
+                }
+            }
+            PathMove::Relocate(i, j) => { // path[j..=i] rotates right by one
+                if j > 0 {
+                    old_arcs.push((path[j - 1], path[j]));
+                    new_arcs.push((path[j - 1], path[i]));
+                }
+                old_arcs.push((path[i - 1], path[i]));
+                new_arcs.push((path[i], path[j]));
+                if i < n - 1 {
+                    old_arcs.push((path[i], path[i + 1]));
+                    new_arcs.push((path[i - 1], path[i + 1]));
+                }
+            }
+            PathMove::SegmentSwap(i, j, k) => {
+                if i > 0 {
+                    old_arcs.push((path[i - 1], path[i]));
+                    new_arcs.push((path[i - 1], path[j]));
+                }
+                old_arcs.push((path[j - 1], path[j]));
+                new_arcs.push((path[k - 1], path[i]));
+                if k < n {
+                    old_arcs.push((path[k - 1], path[k]));
+                    new_arcs.push((path[j - 1], path[k]));
+                }
             }
         }
+    }
+
+    fn apply(self, path: &mut [usize]) {
+        match self {
+            PathMove::Reverse(i, j) => path[i..=j].reverse(),
+            PathMove::Swap(i, j) => path.swap(i, j),
+            PathMove::Relocate(i, j) if i < j => path[i..=j].rotate_left(1),
+            PathMove::Relocate(i, j) => path[j..=i].rotate_right(1),
+            PathMove::SegmentSwap(i, j, k) => path[i..k].rotate_left(j - i),
+        }
+    }
 
-        // If a valid path is found, reconstruct and return it
-        if min_cost < INFINITY {
-            let mut path = Vec::new();
-            let mut mask = full_mask;
-            let mut current_node = last_node;
+    fn span(self) -> (usize, usize) { // first and last position the move can change
+        match self {
+            PathMove::Reverse(i, j) | PathMove::Swap(i, j) => (i, j),
+            PathMove::Relocate(i, j) => (i.min(j), i.max(j)),
+            PathMove::SegmentSwap(i, _, k) => (i, k - 1),
+        }
+    }
+}
 
-            while current_node != -1 {
-                path.push(current_node as usize);
-                let next_node = parent[mask][current_node as usize];
-                mask ^= 1 << (current_node as usize);
-                current_node = next_node;
+fn random_path_move(rng: &mut SeededRng, n: usize, guide: Option<(usize, usize)>) -> Option<PathMove> {
+    // random move on a path of n >= 2 nodes, guide = (from, to) positions of an arc the move should create
+    let move_type = rng.gen_range(4);
+    let (from, to) = match guide {
+        Some((from, to)) if to != from + 1 => (from, to),
+        Some(_) => return None, // arc is already in the path
+        None => {
+            let from = rng.gen_range(n);
+            let mut to = rng.gen_range(n - 1);
+            if to >= from { // two different positions
+                to += 1;
             }
+            (from, to)
+        }
+    };
+    match move_type {
+        0 | 1 => { // reverse or swap so that to lands right after from (exact when to is after from)
+            let (i, j) = if to > from { (from + 1, to) } else { (to, from) };
+            if i >= j {
+                return None;
+            }
+            Some(if move_type == 0 { PathMove::Reverse(i, j) } else { PathMove::Swap(i, j) })
+        }
+        2 => { // move the node at to right behind from
+            let j = if to > from { from + 1 } else { from };
+            if j == to { None } else { Some(PathMove::Relocate(to, j)) }
+        }
+        _ => { // segment swap whose middle join is from -> to
+            if to > from + 1 { // A [..=from] B [from+1..to] C [to..k] D
+                let k = to + 1 + rng.gen_range(n - to);
+                Some(PathMove::SegmentSwap(from + 1, to, k))
+            } else if to < from { // B starts at to and C ends at from, C then B puts from in front of to
+                let j = to + 1 + rng.gen_range(from - to);
+                Some(PathMove::SegmentSwap(to, j, from + 1))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+pub fn simulated_annealing(distance_matrix: &[Vec<f64>], config: &AnnealingConfig) -> AnnealingResult {
+    // simulated annealing over open paths with reverse, swap, relocate and segment swap moves
+    // missing arcs and broken precedence pairs are allowed while searching but are charged missing_arc_penalty, only
+    // feasible paths are kept as the best
+    const CANDIDATES_PER_NODE: usize = 10; // cheapest outgoing arcs per node that guided moves aim for
+    const GUIDED_MOVE_RATE: f64 = 0.9; // share of moves built around a candidate arc instead of random positions
+    let n = distance_matrix.len();
+    let mut rng = SeededRng::new(config.seed);
+    let mut path: Vec<usize> = match &config.initial_path {
+        Some(initial_path) if initial_path.len() == n => initial_path.clone(),
+        _ => { // no starting path, or a failed construction that came back empty
+            let mut random_path: Vec<usize> = (0..n).collect();
+            rng.shuffle(&mut random_path);
+            random_path
+        }
+    };
+    if n < 2 {
+        let cost = if n == 1 { 0.0 } else { f64::INFINITY };
+        return AnnealingResult { cost, path, iterations: 0, trace: Vec::new() };
+    }
+    let penalty = config.missing_arc_penalty.unwrap_or_else(|| {
+        let largest_arc = distance_matrix.iter().flatten().filter(|cost| cost.is_finite()).fold(0.0, |a: f64, &b| a.max(b));
+        largest_arc.max(1.0) * n as f64
+    });
+    let arcs_cost = |arcs: &[(usize, usize)]| -> (f64, i64) { // (penalized cost, missing arcs)
+        arcs.iter().fold((0.0, 0), |(cost, missing), &(from, to)| {
+            let arc_cost = distance_matrix[from][to];
+            if arc_cost.is_finite() { (cost + arc_cost, missing) } else { (cost + penalty, missing + 1) }
+        })
+    };
+    let candidate_lists: Vec<Vec<usize>> = distance_matrix.iter().enumerate().map(|(from, row)| {
+        let mut out_arcs: Vec<usize> = (0..n).filter(|&to| to != from && row[to].is_finite()).collect();
+        out_arcs.sort_by(|&a, &b| row[a].total_cmp(&row[b]));
+        out_arcs.truncate(CANDIDATES_PER_NODE);
+        out_arcs
+    }).collect();
+    let mut position = vec![0; n];
+    for (k, &node) in path.iter().enumerate() {
+        position[node] = k;
+    }
+    // precedence pairs by node, a move only changes the pairs with an end inside its span
+    let precedences: Vec<(usize, usize)> = config.precedences.iter().copied().filter(|&(before, after)| before < n && after < n).collect();
+    let mut node_pairs = vec![Vec::new(); n];
+    for (index, &(before, after)) in precedences.iter().enumerate() {
+        node_pairs[before].push(index);
+        if after != before {
+            node_pairs[after].push(index);
+        }
+    }
+    let pairs = &precedences;
+    let broken_in_span = |path: &[usize], position: &[usize], first: usize, last: usize| -> i64 {
+        // broken pairs with an end in path[first..=last], a pair with both ends inside is counted at its before node
+        path[first..=last].iter()
+            .flat_map(|&node| node_pairs[node].iter().map(move |&index| (node, pairs[index])))
+            .filter(|&(node, (before, _))| node == before || !(first..=last).contains(&position[before]))
+            .filter(|&(_, (before, after))| position[before] >= position[after])
+            .count() as i64
+    };
+
+    let (mut current_cost, mut current_missing) = arcs_cost(&path.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>());
+    let mut current_broken = broken_in_span(&path, &position, 0, n - 1);
+    current_cost += penalty * current_broken as f64;
+    let mut best_cost = f64::INFINITY;
+    let mut best_path = Vec::new();
+    if current_missing == 0 && current_broken == 0 {
+        best_cost = current_cost;
+        best_path = path.clone();
+    }
 
-            path.reverse();
-            return (min_cost, path);
+    let start_time = Instant::now();
+    let reached_max_iterations = |iteration: usize| config.max_iterations.is_some_and(|max_iterations| iteration >= max_iterations);
+    let mut temperature = config.schedule.initial_temperature;
+    let mut trace = Vec::new();
+    let mut iteration = 0;
+    let mut old_arcs = Vec::new();
+    let mut new_arcs = Vec::new();
+    let mut backup = Vec::new();
+    while temperature > config.schedule.minimum_temperature && start_time.elapsed() < config.time_budget && !reached_max_iterations(iteration) {
+        for _ in 0..config.schedule.iterations_per_temperature {
+            if reached_max_iterations(iteration) {
+                break;
+            }
+            iteration += 1;
+            let from = rng.gen_range(n);
+            let candidates = &candidate_lists[path[from]];
+            let guide = if !candidates.is_empty() && rng.next_f64() < GUIDED_MOVE_RATE {
+                Some((from, position[candidates[rng.gen_range(candidates.len())]]))
+            } else {
+                None
+            };
+            let Some(path_move) = random_path_move(&mut rng, n, guide) else {
+                continue;
+            };
+            path_move.changed_arcs(&path, &mut old_arcs, &mut new_arcs);
+            let (old_cost, old_missing) = arcs_cost(&old_arcs);
+            let (new_cost, new_missing) = arcs_cost(&new_arcs);
+            let (first, last) = path_move.span();
+            let mut broken_delta = 0;
+            if !precedences.is_empty() { // make the move to count the pairs it breaks and fixes, put back below if rejected
+                broken_delta -= broken_in_span(&path, &position, first, last);
+                backup.clear();
+                backup.extend_from_slice(&path[first..=last]);
+                path_move.apply(&mut path);
+                for (k, &node) in path.iter().enumerate().take(last + 1).skip(first) {
+                    position[node] = k;
+                }
+                broken_delta += broken_in_span(&path, &position, first, last);
+            }
+            let delta = new_cost - old_cost + penalty * broken_delta as f64;
+            if delta <= 0.0 || rng.next_f64() < (-delta / temperature).exp() { // metropolis acceptance
+                if precedences.is_empty() {
+                    path_move.apply(&mut path);
+                    for (k, &node) in path.iter().enumerate().take(last + 1).skip(first) { // only this range moved
+                        position[node] = k;
+                    }
+                }
+                current_cost += delta;
+                current_missing += new_missing - old_missing;
+                current_broken += broken_delta;
+                if current_missing == 0 && current_broken == 0 && current_cost < best_cost - 1e-9 {
+                    best_cost = current_cost;
+                    best_path.clone_from(&path);
+                }
+            } else if !precedences.is_empty() {
+                path[first..=last].copy_from_slice(&backup);
+                for (k, &node) in path.iter().enumerate().take(last + 1).skip(first) {
+                    position[node] = k;
+                }
+            }
         }
+        trace.push(AnnealingTracePoint { iteration, temperature, current_cost, best_cost });
+        temperature *= config.schedule.cooling_rate;
     }
 
-    // If no valid path is found for any start node, return failure
-    (INFINITY, Vec::new())
+    if best_path.is_empty() {
+        return AnnealingResult { cost: f64::INFINITY, path: best_path, iterations: iteration, trace };
+    }
+    let cost = best_path.windows(2).map(|w| distance_matrix[w[0]][w[1]]).sum(); // recompute so drift from the running deltas never reaches the caller
+    AnnealingResult { cost, path: best_path, iterations: iteration, trace }
 }
 
 pub fn nearest_neighbor_full_graph(distances: &HashMap<usize,HashMap<usize,f64>>) -> (f64, Vec<usize>) { // nearest neighbor for full tree
+    // infinity like the sparse heuristics if the graph is not full after all and the walk runs out of nodes
+    if distances.is_empty() {
+        return (f64::INFINITY, Vec::new());
+    }
     let mut total_min_cost:f64 = 0.0;
     let mut path = Vec::new();
     path.push(0);
@@ -81,9 +642,9 @@ pub fn nearest_neighbor_full_graph(distances: &HashMap<usize,HashMap<usize,f64>>
     while visited_counter != amount_of_nodes{
         let mut min_cost: f64 = f64::INFINITY;
         let mut node_to_visit = usize::MAX;
-        for (second, cost) in &distances[&current_location] { //finds nearest neighbor
+        for (second, cost) in distances.get(&current_location).into_iter().flatten() { //finds nearest neighbor
             // Use `second` (usize) and `cost` (f64) here
-            if  visited[*second] == false && *cost < min_cost
+            if  !visited[*second] && *cost < min_cost
             {
                 min_cost = *cost;
                 node_to_visit = *second;
@@ -92,17 +653,15 @@ pub fn nearest_neighbor_full_graph(distances: &HashMap<usize,HashMap<usize,f64>>
         }
         if min_cost != f64::INFINITY { // found a node to visit
             path.push(node_to_visit);
-            total_min_cost = total_min_cost + min_cost;
+            total_min_cost += min_cost;
             //println!("The cost being added {}", min_cost);
-            visited_counter = visited_counter + 1;
+            visited_counter += 1;
             current_location = node_to_visit;
             visited.set(node_to_visit,true);
             
         }
-        else {
-            println!("Could not find a node but graph should be a full graph ERROR");
-            process::exit(1);
-        
+        else { // dead end, the graph was not a full graph
+            return (f64::INFINITY, Vec::new());
         }
         
     }
@@ -118,126 +677,1241 @@ pub fn nearest_neighbor_full_graph(distances: &HashMap<usize,HashMap<usize,f64>>
 
 
 
+#[derive(Default)]
+pub struct MultiStartConfig {
+    pub seed: u64,
+    pub starts: Option<usize>, // None tries every start node, Some(k) a seeded sample of k of them
+    pub threads: usize,        // worker threads, 0 uses every core
+}
+
+fn randomized_nearest_neighbor(neighbors: &[Vec<(usize, f64)>], start: usize, rng: &mut SeededRng) -> (f64, Vec<usize>) {
+    // nearest neighbor from one start, ties between equally cheap arcs are broken at random instead of by map order
+    const TIE_EPSILON: f64 = 1e-9;
+    let n = neighbors.len();
+    let mut visited = bitvec![0; n];
+    visited.set(start, true);
+    let mut path = vec![start];
+    let mut total_cost = 0.0;
+    let mut current = start;
+    while path.len() < n {
+        let mut best: Option<(usize, f64)> = None;
+        let mut ties = 0;
+        for &(next, cost) in &neighbors[current] {
+            if visited[next] {
+                continue;
+            }
+            match best {
+                Some((_, best_cost)) if cost > best_cost + TIE_EPSILON => {}
+                Some((_, best_cost)) if cost >= best_cost - TIE_EPSILON => { // reservoir sample among the tied arcs
+                    ties += 1;
+                    if rng.gen_range(ties) == 0 {
+                        best = Some((next, cost));
+                    }
+                }
+                _ => {
+                    best = Some((next, cost));
+                    ties = 1;
+                }
+            }
+        }
+        let Some((next, cost)) = best else {
+            return (f64::INFINITY, Vec::new()); // dead end, every neighbor already visited
+        };
+        visited.set(next, true);
+        path.push(next);
+        total_cost += cost;
+        current = next;
+    }
+    (total_cost, path)
+}
+
+pub fn multi_start_nearest_neighbor(distances: &HashMap<usize,HashMap<usize,f64>>, config: &MultiStartConfig) -> (f64, Vec<usize>) {
+    // nearest neighbor from many start nodes spread over a thread pool, keeps the cheapest path.
+    // every start gets its own generator, so the result doesn't depend on the thread count
+    let n = distances.len();
+    if n == 0 {
+        return (f64::INFINITY, Vec::new());
+    }
+    let neighbors: Vec<Vec<(usize, f64)>> = (0..n).map(|node| { // sorted so the walk never sees HashMap order
+        let mut arcs: Vec<(usize, f64)> = distances.get(&node).map(|inner_map| inner_map.iter().map(|(&to, &cost)| (to, cost)).collect()).unwrap_or_default();
+        arcs.sort_by_key(|&(to, _)| to);
+        arcs
+    }).collect();
+    let mut starts: Vec<usize> = (0..n).collect();
+    let mut rng = SeededRng::new(config.seed);
+    if let Some(count) = config.starts {
+        rng.shuffle(&mut starts);
+        starts.truncate(count.clamp(1, n));
+    }
+
+    // the generator of a start only depends on the seed and the start node, so a sampled run repeats the full run's walks
+    let run = || starts.par_iter().map(|&start| {
+        let mut rng = SeededRng::new(config.seed ^ (start as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        randomized_nearest_neighbor(&neighbors, start, &mut rng)
+    }).collect::<Vec<_>>();
+    let results = match rayon::ThreadPoolBuilder::new().num_threads(config.threads).build() {
+        Ok(pool) => pool.install(run),
+        Err(_) => run(), // no pool, the global one still gives the same answer
+    };
+    // first cheapest in start order, ties don't depend on which thread finished first
+    results.into_iter().fold((f64::INFINITY, Vec::new()), |best, result| if result.0 < best.0 { result } else { best })
+}
+
+fn find_root(parent: &mut [usize], node: usize) -> usize { // union-find lookup with path halving
+    let mut node = node;
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
 pub fn nearest_neighbor_sparse(distances: &HashMap<usize,HashMap<usize,f64>>) -> (f64, Vec<usize>) {
-    let mut total_min_cost:f64 = 0.0;
-    // Collect the keys of distances into a Vec<usize>
-    let nodes = distances.keys();
-    let mut collection: Vec<usize> = nodes.cloned().collect();
-    collection.sort(); // Ensure deterministic order
-    
-    let mut paths: Vec<Vec<usize>> = Vec::new();
+    // greedy edge construction: take arcs cheapest first while every node keeps at most one arc in and one out and no
+    // cycle closes (union-find), which leaves a set of path fragments. Any arc from a fragment's tail to another's head
+    // was already taken, so the fragments are patched by splicing a whole fragment between two consecutive nodes of
+    // another one. Sorting is O(E log E) and so is every splicing pass, a pass only repeats if it merged something.
+    // If fragments are still left they are chained along the cheapest tail to head arcs and repaired, see patch_fragments.
+    // Infinity only if the world is infeasible or the repair and the search both fail
+    let report = feasibility::check(distances);
+    if !report.is_feasible() || report.nodes == 0 {
+        return (f64::INFINITY, Vec::new());
+    }
+    let mut fragments = greedy_edge_fragments(distances, report.nodes); // sinks are not keys of the map
+    let path = if fragments.len() == 1 {
+        fragments.remove(0)
+    } else {
+        match patch_fragments(distances, fragments) {
+            Some(path) => path,
+            None => return (f64::INFINITY, Vec::new()),
+        }
+    };
+    (path_cost(distances, &path), path)
+}
 
-    // Iterate over each element in collection
-    for node in collection { // makes a vector of length one paths ex - [[0],[1], [2]]
-        let mut new_path: Vec<usize> = Vec::new();
-        new_path.push(node); 
-        paths.push(new_path); 
+const SEARCH_EXPANSIONS_PER_ARC: usize = 50; // budget of the backtracking search behind patch_fragments
+
+fn patch_fragments(distances: &HashMap<usize,HashMap<usize,f64>>, fragments: Vec<Vec<usize>>) -> Option<Vec<usize>> {
+    // fragments the splices could not merge. Starting from the fragment nobody can reach (or the first one) every next
+    // fragment is the one whose head is cheapest to reach from the current tail, then the relocation repair removes the
+    // missing arcs (at most n moves of O(n^2)) and local search cleans up the seams. If arcs are still missing a
+    // backtracking search takes over, it makes at most SEARCH_EXPANSIONS_PER_ARC * E extensions of O(d log d) each for
+    // out degree d, so this can cost far more than the greedy edge part
+    let n: usize = fragments.iter().map(Vec::len).sum();
+    let mut has_in_arc = vec![false; n];
+    for (&from, inner_map) in distances {
+        for &to in inner_map.keys() {
+            if from != to && to < n {
+                has_in_arc[to] = true;
+            }
+        }
     }
-    
-    
-    
-    let mut banned_paths: Vec<Vec<usize>> = Vec::new(); // prevents cycles has a large worse case memory usage need to manage better
-    let mut _banned_counter:usize = 0;
-    let mut counter = 0;
-    while paths.len() > 1 { // TODO Fix infinite loop that can happend if nothing can connect. 
-           
-        
-            if let Some(path) = paths.pop() { // grab path at front of list
-                let mut min_cost: f64 = f64::INFINITY; 
-                let mut path_to_add:Vec<usize> = Vec::new();
-                    for possible_path_to_add in paths.clone(){
-                            if let Some(front_node) = path.last() { // gets the last location of the popped path
-                                if let Some(last_node) = possible_path_to_add.first() { // gets the front location of another path (going to try to connect the two paths)
-                                    if let Some(inner_map) = distances.get(&front_node) { // use hashmap to get cost
-                                        if let Some(&cost) = inner_map.get(&last_node) { // use hashmap to get cost
-                                                if path != possible_path_to_add &&cost < min_cost // checks if visited yet and cost is cheaper
-                                                {
-                                                    let mut temp_path = path.clone();
-                                                    temp_path.extend(possible_path_to_add.clone());
-                                                    if !banned_paths.contains(&temp_path){
-                                                        min_cost = cost;
-                                                        path_to_add = possible_path_to_add;
-                                                    }
-                                                } 
-
-                                            }
-                                            else{
-                                                //println!("No arc between paths");
-                                            }
-                                        } 
-                                        else {
-                                            //println!("front node has no valid arcs to anywhere");
-                                        }
-                                        
-                                }      
-                                else {
-                                    println!("Path has no nodes");
-                                    process::exit(1);
-                                } 
-                            }
-                            else { //covers path.last and path.first in above if statement
-                                println!("Path has no nodes");
-                                process::exit(1);
-                            }
-                    }      
-                    if min_cost != f64::INFINITY { // found a node to visit
-                                paths.retain(|x| *x != path_to_add); // get rid of path from main vector of paths
-                                let mut new_path = path.clone();
-                                new_path.extend(path_to_add.clone());
-                                paths.insert(0,new_path.clone()); // adds the new combined paths to the paths vector
-                                total_min_cost = total_min_cost + min_cost;
-
-                
-                    }
-                    else {// no path found so pop one in path and stufff
-                        if path.len() > 1{ 
-                            let mut new_path = path;
-                            banned_paths.push(new_path.clone()); // bans path to prevent cycles from repeating paths
-                            _banned_counter += 1;
-                            if let Some(last_node) = new_path.pop() { // pop node out of path
-                                if let Some(temp_node) = new_path.last() { 
-                                    total_min_cost = total_min_cost - distances[temp_node][&last_node]; // undoes the cost of that path
-                                    paths.push(new_path); // adds path with popped node back to main paths
-                                    let mut temp_path: Vec<usize> = Vec::new();
-                                    temp_path.push(last_node);
-                                    paths.insert(0,temp_path) // adds the node that was popped out of path back to front of vector of paths
-                                    
-                                }
-                                else { // should never run unless path somehow becomes empty
-                                    println!("No node to fall back to, path does not contain start node anymore!");
-                                    process::exit(1);
-                                }
-                            } else { //handles error of possible empty vector
-                                println!("The start node had no valid edges to go to ERROR!");
-                                process::exit(1);
-                            }
+    let mut remaining = fragments;
+    let first = remaining.iter().position(|fragment| !has_in_arc[fragment[0]]).unwrap_or(0);
+    let mut path = remaining.swap_remove(first);
+    while !remaining.is_empty() {
+        let tail = path[path.len() - 1];
+        let cheapest = (0..remaining.len()).min_by(|&a, &b| {
+            arc_cost(distances, tail, remaining[a][0]).total_cmp(&arc_cost(distances, tail, remaining[b][0])).then(remaining[a][0].cmp(&remaining[b][0]))
+        }).unwrap_or(0);
+        path.extend(remaining.swap_remove(cheapest));
+    }
+    repair_path(distances, &mut path);
+    if missing_arcs(distances, &path) > 0 {
+        path = bounded_path_search(distances, n, SEARCH_EXPANSIONS_PER_ARC * distances.values().map(HashMap::len).sum::<usize>().max(n))?;
+    }
+    Some(local_search(distances, &path).1)
+}
 
+fn greedy_edge_fragments(distances: &HashMap<usize,HashMap<usize,f64>>, n: usize) -> Vec<Vec<usize>> {
+    // the fragments of the greedy edge construction after the splicing passes, in head order
+    let mut arcs: Vec<(f64, usize, usize)> = distances.iter()
+        .flat_map(|(&from, inner_map)| inner_map.iter().map(move |(&to, &cost)| (cost, from, to)))
+        .filter(|&(cost, from, to)| from != to && from < n && to < n && cost.is_finite())
+        .collect();
+    arcs.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2))); // ties by node so the result is deterministic
+
+    let mut next: Vec<Option<usize>> = vec![None; n];
+    let mut previous: Vec<Option<usize>> = vec![None; n];
+    let mut component: Vec<usize> = (0..n).collect();
+    let mut fragments = n;
+    for &(_, from, to) in &arcs {
+        if next[from].is_none() && previous[to].is_none() {
+            let (from_root, to_root) = (find_root(&mut component, from), find_root(&mut component, to));
+            if from_root != to_root {
+                next[from] = Some(to);
+                previous[to] = Some(from);
+                component[from_root] = to_root;
+                fragments -= 1;
+            }
+        }
+    }
+
+    // tail of every fragment kept at its union-find root, so a spliced fragment can be closed back into its host
+    let mut tail = vec![usize::MAX; n];
+    for head in (0..n).filter(|&node| previous[node].is_none()) {
+        let mut node = head;
+        while let Some(following) = next[node] {
+            node = following;
+        }
+        let root = find_root(&mut component, head);
+        tail[root] = node;
+    }
+    while fragments > 1 {
+        // candidate splices u -> head ... tail -> v where u -> v is currently in another fragment, cheapest increase first
+        let mut splices: Vec<(f64, usize, usize)> = Vec::new();
+        for &(cost, u, head) in &arcs {
+            let (u_root, head_root) = (find_root(&mut component, u), find_root(&mut component, head));
+            if previous[head].is_some() || u_root == head_root {
+                continue;
+            }
+            match next[u] {
+                Some(v) => {
+                    if let Some(&back) = distances.get(&tail[head_root]).and_then(|inner_map| inner_map.get(&v)) {
+                        splices.push((cost + back - distances[&u][&v], u, head));
+                    }
+                }
+                None => splices.push((cost, u, head)), // tail to head join, only possible if greedy missed it
+            }
+        }
+        splices.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+        let before = fragments;
+        for &(_, u, head) in &splices {
+            // earlier splices in this pass may have used the head, the gap after u or merged the two fragments already
+            let (u_root, head_root) = (find_root(&mut component, u), find_root(&mut component, head));
+            if previous[head].is_some() || u_root == head_root {
+                continue;
+            }
+            let last = tail[head_root];
+            match next[u] {
+                Some(v) if distances.get(&last).is_some_and(|inner_map| inner_map.contains_key(&v)) => {
+                    next[last] = Some(v);
+                    previous[v] = Some(last);
+                }
+                Some(_) => continue,
+                None => {}
+            }
+            let joins_tail = next[u].is_none(); // the spliced fragment's tail becomes the host's tail
+            next[u] = Some(head);
+            previous[head] = Some(u);
+            component[u_root] = head_root;
+            if !joins_tail {
+                tail[head_root] = tail[u_root];
+            }
+            fragments -= 1;
+        }
+        if fragments == before {
+            break; // no splice left, every pass that gets here merged at least one fragment so there are at most n passes
+        }
+    }
+
+    (0..n).filter(|&node| previous[node].is_none()).map(|head| {
+        let mut fragment = vec![head];
+        let mut node = head;
+        while let Some(following) = next[node] {
+            fragment.push(following);
+            node = following;
+        }
+        fragment
+    }).collect()
+}
+
+fn bounded_path_search(distances: &HashMap<usize,HashMap<usize,f64>>, n: usize, max_expansions: usize) -> Option<Vec<usize>> {
+    // last resort for greedy edge, depth first search for any feasible path that extends to the neighbor with the fewest
+    // unvisited onward arcs first (Warnsdorff), gives up after max_expansions extensions so it always terminates
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut in_degree = vec![0; n];
+    for (&from, inner_map) in distances {
+        for &to in inner_map.keys() {
+            if from != to && from < n && to < n {
+                successors[from].push(to);
+                in_degree[to] += 1;
+            }
+        }
+    }
+    for list in successors.iter_mut() {
+        list.sort_unstable();
+    }
+    let mut starts: Vec<usize> = (0..n).collect();
+    starts.sort_by_key(|&node| (in_degree[node], node)); // a node nobody can reach has to start the path
+    let mut visited = vec![false; n];
+    let mut expansions = 0;
+    for &start in &starts {
+        let mut path = vec![start];
+        visited[start] = true;
+        let mut options: Vec<Vec<usize>> = Vec::new(); // untried next nodes for every node of the path, best last
+        let ordered = |node: usize, visited: &[bool]| {
+            let mut next: Vec<usize> = successors[node].iter().copied().filter(|&to| !visited[to]).collect();
+            next.sort_by_key(|&to| std::cmp::Reverse((successors[to].iter().filter(|&&onward| !visited[onward]).count(), to)));
+            next
+        };
+        options.push(ordered(start, &visited));
+        while let Some(untried) = options.last_mut() {
+            if path.len() == n {
+                return Some(path);
+            }
+            match untried.pop() {
+                Some(next) => {
+                    expansions += 1;
+                    if expansions > max_expansions {
+                        return None;
+                    }
+                    visited[next] = true;
+                    path.push(next);
+                    let next_options = ordered(next, &visited);
+                    options.push(next_options);
+                }
+                None => { // dead end, step back
+                    options.pop();
+                    if let Some(node) = path.pop() {
+                        visited[node] = false;
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+
+pub fn arc_cost(distances: &HashMap<usize,HashMap<usize,f64>>, from: usize, to: usize) -> f64 { // cost of a single arc, infinity if the arc does not exist
+    distances.get(&from).and_then(|inner_map| inner_map.get(&to)).copied().unwrap_or(f64::INFINITY)
+}
+
+pub fn path_cost(distances: &HashMap<usize,HashMap<usize,f64>>, path: &[usize]) -> f64 { // total cost of an open path, infinity if any arc is missing
+    path.windows(2).map(|arc| arc_cost(distances, arc[0], arc[1])).sum()
+}
+
+pub fn two_opt(distances: &HashMap<usize,HashMap<usize,f64>>, path: &[usize]) -> (f64, Vec<usize>) {
+    // 2-opt local search for an open path, reverses path[i..=j] whenever that lowers the total cost until no move helps (local optimum)
+    // costs are directed so a reversed segment is priced with its reversed arcs, and a move that needs a missing arc is skipped
+    const EPSILON: f64 = 1e-9; // ignore improvements that are only floating point noise
+    let mut path = path.to_vec();
+    let n = path.len();
+    let mut improved = n >= 3;
+
+    while improved {
+        improved = false;
+        // prefix sums of the arc costs walking forward and walking backward along the path
+        // missing backward arcs are counted instead of summed so a range can be checked in O(1)
+        let mut forward = vec![0.0; n];
+        let mut backward = vec![0.0; n];
+        let mut backward_missing = vec![0usize; n];
+        for k in 1..n {
+            forward[k] = forward[k - 1] + arc_cost(distances, path[k - 1], path[k]);
+            let reverse_cost = arc_cost(distances, path[k], path[k - 1]);
+            if reverse_cost.is_finite() {
+                backward[k] = backward[k - 1] + reverse_cost;
+                backward_missing[k] = backward_missing[k - 1];
+            } else {
+                backward[k] = backward[k - 1];
+                backward_missing[k] = backward_missing[k - 1] + 1;
+            }
+        }
+
+        'search: for i in 0..n - 1 {
+            for j in i + 1..n {
+                if backward_missing[j] != backward_missing[i] { // reversed segment needs an arc that does not exist, longer segments will too
+                    break;
+                }
+                let mut old_cost = forward[j] - forward[i];
+                let mut new_cost = backward[j] - backward[i];
+                if i > 0 { // arc coming into the segment
+                    old_cost += arc_cost(distances, path[i - 1], path[i]);
+                    new_cost += arc_cost(distances, path[i - 1], path[j]);
+                }
+                if j < n - 1 { // arc leaving the segment
+                    old_cost += arc_cost(distances, path[j], path[j + 1]);
+                    new_cost += arc_cost(distances, path[i], path[j + 1]);
+                }
+                if new_cost + EPSILON < old_cost { // infinite new_cost never passes this check
+                    path[i..=j].reverse();
+                    improved = true;
+                    break 'search; // prefix sums are stale now, rebuild them
+                }
+            }
+        }
+    }
+
+    (path_cost(distances, &path), path)
+}
+
+fn optional_arc_cost(distances: &HashMap<usize,HashMap<usize,f64>>, from: Option<usize>, to: Option<usize>) -> f64 { // arc cost where a missing end of the path costs nothing
+    match (from, to) {
+        (Some(from), Some(to)) => arc_cost(distances, from, to),
+        _ => 0.0,
+    }
+}
+
+pub fn or_opt(distances: &HashMap<usize,HashMap<usize,f64>>, path: &[usize]) -> (f64, Vec<usize>) {
+    // Or-opt local search, moves a chain of 1 to 3 consecutive nodes somewhere else in the path without reversing it
+    // only arcs that exist in distances are ever created so a feasible path stays feasible
+    const EPSILON: f64 = 1e-9;
+    const MAX_CHAIN_LENGTH: usize = 3;
+    let mut path = path.to_vec();
+    let n = path.len();
+    let mut improved = n >= 3;
+
+    while improved {
+        improved = false;
+        'search: for chain_length in 1..=MAX_CHAIN_LENGTH.min(n - 1) {
+            for i in 0..=n - chain_length {
+                let chain_first = path[i];
+                let chain_last = path[i + chain_length - 1];
+                let before = if i > 0 { Some(path[i - 1]) } else { None };
+                let after = path.get(i + chain_length).copied();
+                let reconnect_cost = optional_arc_cost(distances, before, after);
+                if reconnect_cost.is_infinite() { // closing the gap needs an arc that does not exist
+                    continue;
+                }
+                let removal_gain = optional_arc_cost(distances, before, Some(chain_first))
+                    + optional_arc_cost(distances, Some(chain_last), after)
+                    - reconnect_cost;
+
+                let mut rest = Vec::with_capacity(n - chain_length); // path with the chain taken out
+                rest.extend_from_slice(&path[..i]);
+                rest.extend_from_slice(&path[i + chain_length..]);
+                for position in 0..=rest.len() { // chain goes in front of rest[position]
+                    if position == i { // original spot
+                        continue;
+                    }
+                    let a = if position > 0 { Some(rest[position - 1]) } else { None };
+                    let b = rest.get(position).copied();
+                    let insertion_cost = optional_arc_cost(distances, a, Some(chain_first))
+                        + optional_arc_cost(distances, Some(chain_last), b)
+                        - optional_arc_cost(distances, a, b);
+                    if insertion_cost + EPSILON < removal_gain {
+                        let chain = path[i..i + chain_length].to_vec();
+                        rest.splice(position..position, chain);
+                        path = rest;
+                        improved = true;
+                        break 'search;
+                    }
+                }
+            }
+        }
+    }
+
+    (path_cost(distances, &path), path)
+}
+
+pub fn or_three_opt(distances: &HashMap<usize,HashMap<usize,f64>>, path: &[usize]) -> (f64, Vec<usize>) {
+    // orientation preserving 3-opt (segment swap), splits the path into A B C D and tries A C B D
+    // no segment is reversed so directed costs inside each segment never change, only the three joining arcs do
+    const EPSILON: f64 = 1e-9;
+    let mut path = path.to_vec();
+    let n = path.len();
+    let mut improved = n >= 3;
+
+    while improved {
+        improved = false;
+        'search: for i in 0..n - 1 { // B = path[i..j], C = path[j..k]
+            let a_last = if i > 0 { Some(path[i - 1]) } else { None };
+            let b_first = Some(path[i]);
+            let old_into_b = optional_arc_cost(distances, a_last, b_first);
+            for j in i + 1..n {
+                let b_last = Some(path[j - 1]);
+                let c_first = Some(path[j]);
+                let old_b_to_c = optional_arc_cost(distances, b_last, c_first);
+                let new_into_c = optional_arc_cost(distances, a_last, c_first);
+                if new_into_c.is_infinite() { // every k would need this missing arc
+                    continue;
+                }
+                for k in j + 1..=n {
+                    let c_last = Some(path[k - 1]);
+                    let d_first = path.get(k).copied();
+                    let old_cost = old_into_b + old_b_to_c + optional_arc_cost(distances, c_last, d_first);
+                    let new_cost = new_into_c
+                        + optional_arc_cost(distances, c_last, b_first)
+                        + optional_arc_cost(distances, b_last, d_first);
+                    if new_cost + EPSILON < old_cost {
+                        path[i..k].rotate_left(j - i); // B C -> C B
+                        improved = true;
+                        break 'search;
+                    }
+                }
+            }
+        }
+    }
+
+    (path_cost(distances, &path), path)
+}
+
+pub fn local_search(distances: &HashMap<usize,HashMap<usize,f64>>, path: &[usize]) -> (f64, Vec<usize>) {
+    // runs 2-opt, Or-opt and segment swap in turn until none of them can improve the path
+    const EPSILON: f64 = 1e-9;
+    let mut cost = path_cost(distances, path);
+    let mut path = path.to_vec();
+    loop {
+        let (_, two_opt_path) = two_opt(distances, &path);
+        let (_, or_opt_path) = or_opt(distances, &two_opt_path);
+        let (new_cost, new_path) = or_three_opt(distances, &or_opt_path);
+        if new_cost + EPSILON < cost {
+            cost = new_cost;
+            path = new_path;
+        } else {
+            return (cost, path);
+        }
+    }
+}
+
+pub struct GeneticConfig {
+    pub seed: u64,
+    pub population_size: usize,
+    pub generations: usize,
+    pub elite_count: usize, // best individuals copied unchanged into the next generation
+    pub tournament_size: usize,
+    pub mutation_rate: f64, // chance a child gets an insertion or swap mutation
+    pub seed_with_nearest_neighbor: bool, // put the nearest_neighbor_sparse path after local search (and mutated copies) in the first population, otherwise it is all random
+}
+
+impl Default for GeneticConfig {
+    fn default() -> Self {
+        GeneticConfig {
+            seed: 0,
+            population_size: 60,
+            generations: 300,
+            elite_count: 2,
+            tournament_size: 3,
+            mutation_rate: 0.3,
+            seed_with_nearest_neighbor: true,
+        }
+    }
+}
+
+fn missing_arcs(distances: &HashMap<usize,HashMap<usize,f64>>, path: &[usize]) -> usize { // how many arcs of the path do not exist
+    path.windows(2).filter(|arc| arc_cost(distances, arc[0], arc[1]).is_infinite()).count()
+}
+
+fn penalized_path_cost(distances: &HashMap<usize,HashMap<usize,f64>>, path: &[usize]) -> (usize, f64) {
+    // (missing arcs, cost of the arcs that exist), compared as a tuple so any feasible path beats any infeasible one
+    path.windows(2).fold((0, 0.0), |(missing, cost), arc| {
+        let arc_cost = arc_cost(distances, arc[0], arc[1]);
+        if arc_cost.is_finite() { (missing, cost + arc_cost) } else { (missing + 1, cost) }
+    })
+}
+
+fn repair_path(distances: &HashMap<usize,HashMap<usize,f64>>, path: &mut Vec<usize>) {
+    // greedy feasibility repair, the node after each missing arc is moved to the spot that removes the most missing arcs
+    // stops when the path is feasible or no single relocation reduces the number of missing arcs
+    let mut missing = missing_arcs(distances, path);
+    let mut attempts = 0;
+    while missing > 0 && attempts < path.len() {
+        attempts += 1;
+        let mut best: Option<(usize, usize, usize)> = None; // (missing after, node position, insert position)
+        for k in 0..path.len() - 1 {
+            if arc_cost(distances, path[k], path[k + 1]).is_finite() {
+                continue;
+            }
+            for node_position in [k, k + 1] { // either end of the missing arc can move
+                let node = path[node_position];
+                let mut rest = path.clone();
+                rest.remove(node_position);
+                let rest_missing = missing_arcs(distances, &rest);
+                for insert_position in 0..=rest.len() {
+                    let before = if insert_position > 0 { Some(rest[insert_position - 1]) } else { None };
+                    let after = rest.get(insert_position).copied();
+                    let mut new_missing = rest_missing;
+                    if before.is_some() && after.is_some() && optional_arc_cost(distances, before, after).is_infinite() {
+                        new_missing -= 1; // the arc we split was missing
+                    }
+                    new_missing += usize::from(optional_arc_cost(distances, before, Some(node)).is_infinite());
+                    new_missing += usize::from(optional_arc_cost(distances, Some(node), after).is_infinite());
+                    if new_missing < missing && best.is_none_or(|(best_missing, _, _)| new_missing < best_missing) {
+                        best = Some((new_missing, node_position, insert_position));
+                    }
+                }
+            }
+        }
+        match best {
+            Some((new_missing, node_position, insert_position)) => {
+                let node = path.remove(node_position);
+                path.insert(insert_position, node);
+                missing = new_missing;
+            }
+            None => return,
+        }
+    }
+}
+
+fn order_crossover(rng: &mut SeededRng, first_parent: &[usize], second_parent: &[usize], node_slots: usize) -> Vec<usize> {
+    // OX, the child keeps a slice of the first parent in place and the rest of the nodes in the order they appear in the second parent
+    let n = first_parent.len();
+    let mut a = rng.gen_range(n);
+    let mut b = rng.gen_range(n);
+    if a > b {
+        std::mem::swap(&mut a, &mut b);
+    }
+    let mut in_slice = vec![false; node_slots];
+    for &node in &first_parent[a..=b] {
+        in_slice[node] = true;
+    }
+    let mut fill = second_parent.iter().copied().filter(|&node| !in_slice[node]);
+    let mut child = Vec::with_capacity(n);
+    for (position, &node) in first_parent.iter().enumerate() {
+        if position >= a && position <= b {
+            child.push(node);
+        } else if let Some(node) = fill.next() {
+            child.push(node);
+        }
+    }
+    child
+}
+
+fn mutate(rng: &mut SeededRng, path: &mut [usize]) { // insertion or swap mutation
+    let n = path.len();
+    if n < 2 {
+        return;
+    }
+    let i = rng.gen_range(n);
+    let j = rng.gen_range(n);
+    if rng.gen_range(2) == 0 {
+        path.swap(i, j);
+    } else if i < j {
+        path[i..=j].rotate_left(1);
+    } else {
+        path[j..=i].rotate_right(1);
+    }
+}
+
+pub fn genetic_algorithm(distances: &HashMap<usize,HashMap<usize,f64>>, config: &GeneticConfig) -> (f64, Vec<usize>) {
+    // genetic algorithm with order crossover, insertion/swap mutation, tournament selection and elitism
+    // children that pick up missing arcs are repaired and infeasible individuals always rank below feasible ones
+    let mut nodes: Vec<usize> = distances.iter().flat_map(|(from, inner_map)| std::iter::once(*from).chain(inner_map.keys().copied())).collect();
+    nodes.sort();
+    nodes.dedup();
+    let node_slots = nodes.last().map_or(0, |last| last + 1);
+    if nodes.len() < 2 {
+        return (if nodes.is_empty() { f64::INFINITY } else { 0.0 }, nodes);
+    }
+    let mut rng = SeededRng::new(config.seed);
+    let population_size = config.population_size.max(2);
+    let elite_count = config.elite_count.min(population_size);
+
+    let mut population: Vec<Vec<usize>> = Vec::with_capacity(population_size);
+    if config.seed_with_nearest_neighbor {
+        let (_, nearest_path) = nearest_neighbor_sparse(distances);
+        if nearest_path.len() == nodes.len() {
+            let (_, nearest_path) = local_search(distances, &nearest_path);
+            for copy in 0..population_size / 4 { // the path itself plus mutated copies so the start is not a single point
+                let mut individual = nearest_path.clone();
+                for _ in 0..copy.min(3) {
+                    mutate(&mut rng, &mut individual);
+                }
+                repair_path(distances, &mut individual);
+                population.push(individual);
+            }
+        }
+    }
+    while population.len() < population_size {
+        let mut individual = nodes.clone();
+        rng.shuffle(&mut individual);
+        repair_path(distances, &mut individual);
+        population.push(individual);
+    }
+    let mut fitness: Vec<(usize, f64)> = population.iter().map(|individual| penalized_path_cost(distances, individual)).collect();
+    let better = |a: (usize, f64), b: (usize, f64)| a.0 < b.0 || (a.0 == b.0 && a.1 < b.1);
+
+    for _ in 0..config.generations {
+        let mut order: Vec<usize> = (0..population_size).collect();
+        order.sort_by(|&a, &b| fitness[a].0.cmp(&fitness[b].0).then(fitness[a].1.total_cmp(&fitness[b].1)));
+        let mut next_population: Vec<Vec<usize>> = order[..elite_count].iter().map(|&index| population[index].clone()).collect();
+        let mut next_fitness: Vec<(usize, f64)> = order[..elite_count].iter().map(|&index| fitness[index]).collect();
+
+        while next_population.len() < population_size {
+            let mut parents = [0; 2];
+            for parent in parents.iter_mut() { // tournament selection
+                *parent = rng.gen_range(population_size);
+                for _ in 1..config.tournament_size {
+                    let challenger = rng.gen_range(population_size);
+                    if better(fitness[challenger], fitness[*parent]) {
+                        *parent = challenger;
+                    }
+                }
+            }
+            let mut child = order_crossover(&mut rng, &population[parents[0]], &population[parents[1]], node_slots);
+            if rng.next_f64() < config.mutation_rate {
+                mutate(&mut rng, &mut child);
+            }
+            repair_path(distances, &mut child);
+            next_fitness.push(penalized_path_cost(distances, &child));
+            next_population.push(child);
+        }
+        population = next_population;
+        fitness = next_fitness;
+    }
+
+    let best_index = (0..population_size).fold(0, |best, index| if better(fitness[index], fitness[best]) { index } else { best });
+    if fitness[best_index].0 > 0 { // nothing feasible survived
+        return (f64::INFINITY, Vec::new());
+    }
+    local_search(distances, &population[best_index]) // final polish, the population only ever saw crossover and mutation
+}
+
+pub struct AntColonyConfig {
+    pub seed: u64,
+    pub ants: usize, // ants per iteration
+    pub iterations: usize,
+    pub alpha: f64, // weight of pheromone
+    pub beta: f64, // weight of 1 / arc cost
+    pub rho: f64, // evaporation rate of the global update on the best path
+    pub local_evaporation: f64, // ACS local update, pulls arcs an ant just used back towards the starting pheromone
+    pub exploitation: f64, // q0, chance an ant takes the best looking arc instead of sampling
+    pub candidate_list_size: usize, // cheapest outgoing arcs considered first at each node
+    pub max_backtracks: usize, // steps an ant may undo after a dead end before it is counted as failed
+}
+
+impl Default for AntColonyConfig {
+    fn default() -> Self {
+        AntColonyConfig {
+            seed: 0,
+            ants: 20,
+            iterations: 200,
+            alpha: 1.0,
+            beta: 2.0,
+            rho: 0.1,
+            local_evaporation: 0.1,
+            exploitation: 0.9,
+            candidate_list_size: 15,
+            max_backtracks: 50,
+        }
+    }
+}
+
+pub struct AntColonyResult {
+    pub cost: f64, // infinity and an empty path if no ant ever finished
+    pub path: Vec<usize>,
+    pub failed_ants: usize, // ants that hit a dead end and ran out of backtracks
+    pub total_ants: usize,
+}
+
+pub fn ant_colony_system(distances: &HashMap<usize,HashMap<usize,f64>>, config: &AntColonyConfig) -> AntColonyResult {
+    // Ant Colony System, ants only walk arcs that exist in distances so every finished ant is a feasible open path
+    // an ant at a dead end undoes its last step and tries another arc, up to max_backtracks times
+    let mut nodes: Vec<usize> = distances.iter().flat_map(|(from, inner_map)| std::iter::once(*from).chain(inner_map.keys().copied())).collect();
+    nodes.sort();
+    nodes.dedup();
+    let node_slots = nodes.last().map_or(0, |last| last + 1);
+    let total_ants = config.ants * config.iterations;
+    if nodes.len() < 2 {
+        return AntColonyResult { cost: if nodes.is_empty() { f64::INFINITY } else { 0.0 }, path: nodes, failed_ants: 0, total_ants: 0 };
+    }
+
+    // outgoing arcs sorted by cost, pheromone[u][k] belongs to out_arcs[u][k]
+    let mut out_arcs: Vec<Vec<(usize, f64)>> = vec![Vec::new(); node_slots];
+    for (&from, inner_map) in distances {
+        for (&to, &cost) in inner_map {
+            if to != from && cost.is_finite() {
+                out_arcs[from].push((to, cost));
+            }
+        }
+    }
+    let mut arc_count = 0;
+    let mut total_arc_cost = 0.0;
+    for arcs in out_arcs.iter_mut() {
+        arcs.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0))); // ties by node so HashMap order never matters
+        arc_count += arcs.len();
+        total_arc_cost += arcs.iter().map(|arc| arc.1).sum::<f64>();
+    }
+    let estimated_path_cost = (total_arc_cost / arc_count.max(1) as f64).max(f64::EPSILON) * nodes.len() as f64;
+    let initial_pheromone = 1.0 / (nodes.len() as f64 * estimated_path_cost);
+    let mut pheromone: Vec<Vec<f64>> = out_arcs.iter().map(|arcs| vec![initial_pheromone; arcs.len()]).collect();
+    let attractiveness = |pheromone: f64, cost: f64| pheromone.powf(config.alpha) * (1.0 / cost.max(f64::EPSILON)).powf(config.beta);
+
+    let mut rng = SeededRng::new(config.seed);
+    let mut best_cost = f64::INFINITY;
+    let mut best_arcs: Vec<(usize, usize)> = Vec::new(); // (node, index into out_arcs[node]) of the best path
+    let mut best_path: Vec<usize> = Vec::new();
+    let mut failed_ants = 0;
+    let mut visited = vec![false; node_slots];
+
+    for _ in 0..config.iterations {
+        for _ in 0..config.ants {
+            visited.iter_mut().for_each(|seen| *seen = false);
+            let start = nodes[rng.gen_range(nodes.len())];
+            visited[start] = true;
+            let mut path = vec![start];
+            let mut arcs_taken: Vec<usize> = Vec::new(); // arcs_taken[k] is the out_arcs index used to leave path[k]
+            let mut tried: Vec<Vec<usize>> = vec![Vec::new()]; // tried[k], next nodes after path[k] that already led to a dead end
+            let mut backtracks = 0;
+
+            while path.len() < nodes.len() {
+                let current = *path.last().unwrap();
+                let open = |&k: &usize| {
+                    let to = out_arcs[current][k].0;
+                    !visited[to] && !tried[path.len() - 1].contains(&to)
+                };
+                let candidate_end = config.candidate_list_size.min(out_arcs[current].len());
+                let mut choices: Vec<usize> = (0..candidate_end).filter(open).collect();
+                if choices.is_empty() { // candidate list used up, look at every other arc
+                    choices = (candidate_end..out_arcs[current].len()).filter(open).collect();
+                }
+                if choices.is_empty() { // dead end
+                    if path.len() == 1 || backtracks == config.max_backtracks {
+                        break;
+                    }
+                    backtracks += 1;
+                    let dead_end = path.pop().unwrap();
+                    visited[dead_end] = false;
+                    arcs_taken.pop();
+                    tried.pop();
+                    tried.last_mut().unwrap().push(dead_end);
+                    continue;
+                }
+
+                let weights: Vec<f64> = choices.iter().map(|&k| attractiveness(pheromone[current][k], out_arcs[current][k].1)).collect();
+                let chosen = if rng.next_f64() < config.exploitation { // exploit, best looking arc
+                    (0..choices.len()).fold(0, |best, index| if weights[index] > weights[best] { index } else { best })
+                } else { // explore, roulette wheel over the weights
+                    let total: f64 = weights.iter().sum();
+                    let mut target = rng.next_f64() * total;
+                    let mut chosen = choices.len() - 1;
+                    for (index, weight) in weights.iter().enumerate() {
+                        if target < *weight {
+                            chosen = index;
+                            break;
                         }
-                        else {
-                            paths.insert(0,path) // insert single length paths at front of list
-                        }
-                        
-                    
-                        
+                        target -= weight;
                     }
-                } else {
-                    println!("No paths left to process!");
-                    process::exit(1);
+                    chosen
+                };
+                let k = choices[chosen];
+                // local update
+                pheromone[current][k] = (1.0 - config.local_evaporation) * pheromone[current][k] + config.local_evaporation * initial_pheromone;
+                let next = out_arcs[current][k].0;
+                visited[next] = true;
+                path.push(next);
+                arcs_taken.push(k);
+                tried.push(Vec::new());
+            }
+
+            if path.len() < nodes.len() {
+                failed_ants += 1;
+                continue;
+            }
+            let cost: f64 = path.iter().zip(arcs_taken.iter()).map(|(&node, &k)| out_arcs[node][k].1).sum();
+            if cost < best_cost {
+                best_cost = cost;
+                best_arcs = path.iter().copied().zip(arcs_taken.iter().copied()).collect();
+                best_path = path;
+            }
+        }
+
+        // global update, evaporate and deposit only on the best path found so far
+        for &(node, k) in &best_arcs {
+            pheromone[node][k] = (1.0 - config.rho) * pheromone[node][k] + config.rho / best_cost;
+        }
+    }
+
+    AntColonyResult { cost: best_cost, path: best_path, failed_ants, total_ants }
+}
+
+pub struct BranchAndBoundConfig {
+    pub time_limit: Duration, // stop and report the best path and bound found so far
+}
+
+impl Default for BranchAndBoundConfig {
+    fn default() -> Self {
+        BranchAndBoundConfig { time_limit: Duration::from_secs(60) }
+    }
+}
+
+pub struct BranchAndBoundResult {
+    pub cost: f64, // infinity and an empty path if no path was found
+    pub path: Vec<usize>,
+    pub proved_optimal: bool, // search finished, cost is optimal (or no path exists when cost is infinite)
+    pub nodes_explored: usize,
+    pub lower_bound: f64, // best proven bound on the optimal cost, equal to cost when proved_optimal
+}
+
+fn completion_bound(distance_matrix: &[Vec<f64>], last: Option<usize>, unvisited: &[usize]) -> (f64, Option<Vec<usize>>) {
+    // assignment problem bound on finishing a partial path that ends at last (None = nothing placed yet)
+    // rows are last plus the unvisited nodes, columns are the unvisited nodes plus the end of the path
+    // when the assignment happens to be a single chain from last to the end it is a real completion and is returned too
+    let m = unvisited.len() + 1;
+    let end = m - 1;
+    let cost_matrix: Vec<Vec<f64>> = (0..m).map(|row| {
+        let from = if row == 0 { last } else { Some(unvisited[row - 1]) };
+        (0..m).map(|column| {
+            if column == end {
+                if row == 0 && !unvisited.is_empty() { f64::INFINITY } else { 0.0 } // last cannot end the path early
+            } else {
+                match from {
+                    None => 0.0, // free choice of start node
+                    Some(from) if from == unvisited[column] => f64::INFINITY,
+                    Some(from) => distance_matrix[from][unvisited[column]],
                 }
-            
-    
-        counter = counter + 1;
-        
+            }
+        }).collect()
+    }).collect();
+    let (bound, assignment) = crate::lower_bounds::assignment_problem(&cost_matrix);
+    if bound.is_infinite() {
+        return (f64::INFINITY, None);
     }
-    //println!("Banned- {:?}, Amount of banned paths - {}", banned_paths, banned_paths.len()); // for developement and testing
-    //println!("Amount of banned paths - {}", _banned_counter);
-    if let Some(path) = paths.first(){
-        (total_min_cost, path.clone())
+    let mut completion = Vec::with_capacity(unvisited.len());
+    let mut row = 0;
+    while assignment[row] != end { // row 0 is never a column and the end is never a row, so this chain always reaches the end
+        completion.push(unvisited[assignment[row]]);
+        row = assignment[row] + 1;
     }
-    else
-    {
-        println!("No path generated");
-        process::exit(1);
+    if completion.len() == unvisited.len() {
+        (bound, Some(completion))
+    } else {
+        (bound, None)
+    }
+}
+
+pub fn branch_and_bound(distance_matrix: &[Vec<f64>], config: &BranchAndBoundConfig) -> BranchAndBoundResult {
+    // exact depth first branch and bound over open paths, branches on the next node of the path
+    // each child is bounded with the assignment problem on the remaining nodes, incumbent comes from a short simulated annealing run
+    // stops at config.time_limit and then reports the best path found and the smallest bound left on the stack
+    const EPSILON: f64 = 1e-9;
+    struct SearchNode {
+        path: Vec<usize>,
+        cost: f64,
+        bound: f64,
+    }
+    let start_time = Instant::now();
+    let n = distance_matrix.len();
+    let annealing_config = AnnealingConfig { max_iterations: Some(200_000), time_budget: config.time_limit / 10, ..Default::default() };
+    let annealing_result = simulated_annealing(distance_matrix, &annealing_config);
+    let mut best_cost = annealing_result.cost;
+    let mut best_path = annealing_result.path;
+
+    let mut stack = Vec::new();
+    let (root_bound, root_completion) = completion_bound(distance_matrix, None, &(0..n).collect::<Vec<usize>>());
+    if let Some(completion) = root_completion.filter(|_| root_bound < best_cost - EPSILON) {
+        best_cost = root_bound;
+        best_path = completion;
+    } else if root_bound < best_cost - EPSILON {
+        stack.push(SearchNode { path: Vec::new(), cost: 0.0, bound: root_bound });
+    }
+
+    let mut nodes_explored = 0;
+    let mut visited = vec![false; n];
+    while let Some(node) = stack.pop() {
+        if node.bound >= best_cost - EPSILON { // incumbent improved after this node was pushed
+            continue;
+        }
+        if start_time.elapsed() >= config.time_limit {
+            stack.push(node);
+            break;
+        }
+        nodes_explored += 1;
+        visited.iter_mut().for_each(|seen| *seen = false);
+        for &placed in &node.path {
+            visited[placed] = true;
+        }
+        let last = node.path.last().copied();
+        let mut children = Vec::new();
+        for next in (0..n).filter(|&next| !visited[next]) {
+            let arc = last.map_or(0.0, |last| distance_matrix[last][next]);
+            if arc.is_infinite() {
+                continue;
+            }
+            let unvisited: Vec<usize> = (0..n).filter(|&other| !visited[other] && other != next).collect();
+            let cost = node.cost + arc;
+            let (remaining_bound, completion) = completion_bound(distance_matrix, Some(next), &unvisited);
+            let bound = cost + remaining_bound;
+            if bound >= best_cost - EPSILON {
+                continue;
+            }
+            let mut path = node.path.clone();
+            path.push(next);
+            if let Some(completion) = completion { // bound is reached by a real path, nothing below this child can beat it
+                path.extend(completion);
+                best_cost = bound;
+                best_path = path;
+                continue;
+            }
+            children.push(SearchNode { path, cost, bound });
+        }
+        children.sort_by(|a, b| b.bound.total_cmp(&a.bound)); // smallest bound ends up on top of the stack
+        stack.extend(children);
+    }
+
+    let open_bound = stack.iter().map(|node| node.bound).filter(|&bound| bound < best_cost - EPSILON).fold(f64::INFINITY, f64::min);
+    let proved_optimal = open_bound.is_infinite();
+    if !best_path.is_empty() {
+        best_cost = best_path.windows(2).map(|w| distance_matrix[w[0]][w[1]]).sum(); // exact cost of the path, not the running sum
+    }
+    let lower_bound = if proved_optimal { best_cost } else { open_bound.min(best_cost) };
+    BranchAndBoundResult { cost: best_cost, path: best_path, proved_optimal, nodes_explored, lower_bound }
+}
+
+#[derive(Clone)]
+pub struct SymmetryReport {
+    pub symmetric: bool,     // every arc has a reverse arc with the same cost (within the tolerance)
+    pub metric: bool,        // complete and the triangle inequality holds on the undirected costs
+    pub max_asymmetry: f64,  // largest |cost(i, j) - cost(j, i)| over pairs that exist both ways
+    pub one_way_pairs: usize, // pairs with an arc in only one direction
+}
+
+pub fn detect_symmetry(distance_matrix: &[Vec<f64>], tolerance: f64) -> SymmetryReport {
+    let n = distance_matrix.len();
+    let mut max_asymmetry: f64 = 0.0;
+    let mut one_way_pairs = 0;
+    for (i, row) in distance_matrix.iter().enumerate() {
+        for (j, &there) in row.iter().enumerate().skip(i + 1) {
+            let back = distance_matrix[j][i];
+            if there.is_finite() && back.is_finite() {
+                max_asymmetry = max_asymmetry.max((there - back).abs());
+            } else if there.is_finite() || back.is_finite() {
+                one_way_pairs += 1;
+            }
+        }
+    }
+    // the triangle inequality is checked on the costs the symmetric solvers actually use
+    let weights = undirected_weights(distance_matrix);
+    let mut metric = (0..n).all(|i| (0..n).all(|j| i == j || weights[i][j].is_finite()));
+    'triangle: for i in 0..n {
+        for j in 0..n {
+            for k in 0..n {
+                if metric && i != j && j != k && i != k && weights[i][j] > weights[i][k] + weights[k][j] + tolerance {
+                    metric = false;
+                    break 'triangle;
+                }
+            }
+        }
+    }
+    SymmetryReport {
+        symmetric: one_way_pairs == 0 && max_asymmetry <= tolerance,
+        metric,
+        max_asymmetry,
+        one_way_pairs,
+    }
+}
+
+fn undirected_weights(distance_matrix: &[Vec<f64>]) -> Vec<Vec<f64>> { // cheaper direction of every pair, same as the matrix when it is symmetric
+    let n = distance_matrix.len();
+    (0..n).map(|i| (0..n).map(|j| if i == j { 0.0 } else { distance_matrix[i][j].min(distance_matrix[j][i]) }).collect()).collect()
+}
+
+fn minimum_spanning_tree(weights: &[Vec<f64>]) -> Option<Vec<(usize, usize)>> { // Prim on the dense matrix, None if the graph is disconnected
+    let n = weights.len();
+    let mut in_tree = vec![false; n];
+    let mut closest = vec![(f64::INFINITY, usize::MAX); n]; // (cost, tree node) of the cheapest edge into the tree
+    let mut edges = Vec::with_capacity(n.saturating_sub(1));
+    closest[0] = (0.0, usize::MAX);
+    for _ in 0..n {
+        let next = (0..n).filter(|&node| !in_tree[node]).min_by(|&a, &b| closest[a].0.total_cmp(&closest[b].0))?;
+        if closest[next].0 == f64::INFINITY {
+            return None;
+        }
+        in_tree[next] = true;
+        if closest[next].1 != usize::MAX {
+            edges.push((closest[next].1, next));
+        }
+        for node in 0..n {
+            if !in_tree[node] && weights[next][node] < closest[node].0 {
+                closest[node] = (weights[next][node], next);
+            }
+        }
+    }
+    Some(edges)
+}
+
+const MATCHING_MAX_ODD_NODES: usize = 20; // the exact matching is a DP over subsets of the odd degree nodes
+
+fn minimum_weight_matching(weights: &[Vec<f64>], odd_nodes: &[usize]) -> Option<Vec<(usize, usize)>> {
+    // exact minimum weight perfect matching, always pairs the lowest unmatched node so every set is reached one way.
+    // Only call it with up to MATCHING_MAX_ODD_NODES nodes
+    let k = odd_nodes.len();
+    let mut dp = vec![f64::INFINITY; 1 << k];
+    let mut choice = vec![(0u8, 0u8); 1 << k];
+    dp[0] = 0.0;
+    for mask in 0..(1usize << k) {
+        if dp[mask] == f64::INFINITY || mask == (1 << k) - 1 {
+            continue;
+        }
+        let first = (!mask).trailing_zeros() as usize;
+        for second in first + 1..k {
+            if mask & (1 << second) == 0 {
+                let next = mask | (1 << first) | (1 << second);
+                let cost = dp[mask] + weights[odd_nodes[first]][odd_nodes[second]];
+                if cost < dp[next] {
+                    dp[next] = cost;
+                    choice[next] = (first as u8, second as u8);
+                }
+            }
+        }
+    }
+    let mut mask = (1 << k) - 1;
+    if dp[mask] == f64::INFINITY {
+        return None;
+    }
+    let mut pairs = Vec::with_capacity(k / 2);
+    while mask != 0 {
+        let (first, second) = choice[mask];
+        pairs.push((odd_nodes[first as usize], odd_nodes[second as usize]));
+        mask ^= (1 << first) | (1 << second);
+    }
+    Some(pairs)
+}
+
+fn greedy_matching(weights: &[Vec<f64>], odd_nodes: &[usize]) -> Option<Vec<(usize, usize)>> {
+    // heuristic perfect matching for too many odd nodes for the DP, O(k^2 log k) plus the passes: pairs the cheapest
+    // edges first, then swaps partners between two pairs while that is cheaper. Not minimum, so no 1.5 guarantee.
+    // None only if some odd nodes can't be paired at all
+    const EPSILON: f64 = 1e-9;
+    let k = odd_nodes.len();
+    let mut edges: Vec<(f64, usize, usize)> = (0..k).flat_map(|a| (a + 1..k).map(move |b| (a, b)))
+        .map(|(a, b)| (weights[odd_nodes[a]][odd_nodes[b]], a, b))
+        .filter(|edge| edge.0.is_finite())
+        .collect();
+    edges.sort_by(|x, y| x.0.total_cmp(&y.0));
+    let mut matched = vec![false; k];
+    let mut pairs = Vec::with_capacity(k / 2);
+    for (_, a, b) in edges {
+        if !matched[a] && !matched[b] {
+            matched[a] = true;
+            matched[b] = true;
+            pairs.push((odd_nodes[a], odd_nodes[b]));
+        }
+    }
+    if pairs.len() * 2 != k {
+        return None;
+    }
+    let weight = |(a, b): (usize, usize)| weights[a][b];
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..pairs.len() {
+            for j in i + 1..pairs.len() {
+                let ((a, b), (c, d)) = (pairs[i], pairs[j]);
+                let current = weight((a, b)) + weight((c, d));
+                for ((p, q), (r, t)) in [((a, c), (b, d)), ((a, d), (b, c))] {
+                    if weight((p, q)) + weight((r, t)) < current - EPSILON {
+                        pairs[i] = (p, q);
+                        pairs[j] = (r, t);
+                        improved = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    Some(pairs)
+}
+
+fn euler_shortcut(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    // Hierholzer's walk over the (multi)graph of even degree, keeping only the first visit of every node
+    let mut adjacency: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n]; // (neighbor, edge id)
+    for (id, &(a, b)) in edges.iter().enumerate() {
+        adjacency[a].push((b, id));
+        adjacency[b].push((a, id));
+    }
+    let mut used = vec![false; edges.len()];
+    let mut next_edge = vec![0; n];
+    let mut stack = vec![0];
+    let mut seen = bitvec![0; n];
+    let mut tour = Vec::with_capacity(n);
+    while let Some(&node) = stack.last() {
+        while next_edge[node] < adjacency[node].len() && used[adjacency[node][next_edge[node]].1] {
+            next_edge[node] += 1;
+        }
+        if let Some(&(neighbor, id)) = adjacency[node].get(next_edge[node]) {
+            used[id] = true;
+            stack.push(neighbor);
+        } else {
+            stack.pop();
+            if !seen[node] {
+                seen.set(node, true);
+                tour.push(node);
+            }
+        }
+    }
+    tour
+}
+
+fn open_path_from_tour(distance_matrix: &[Vec<f64>], tour: &[usize]) -> (f64, Vec<usize>) {
+    // the symmetric solvers build a closed tour, drop its most expensive directed arc in whichever direction is cheaper
+    let n = tour.len();
+    let mut best = (f64::INFINITY, Vec::new());
+    for direction in [tour.to_vec(), tour.iter().rev().copied().collect()] {
+        let arcs: Vec<f64> = (0..n).map(|i| distance_matrix[direction[i]][direction[(i + 1) % n]]).collect();
+        let cut = (0..n).max_by(|&a, &b| arcs[a].total_cmp(&arcs[b])).unwrap_or(0);
+        let mut path = direction.clone();
+        path.rotate_left((cut + 1) % n.max(1));
+        let cost: f64 = path.windows(2).map(|arc| distance_matrix[arc[0]][arc[1]]).sum();
+        if cost < best.0 {
+            best = (cost, path);
+        }
+    }
+    best
+}
+
+pub struct ChristofidesResult {
+    pub cost: f64,                        // directed cost of the open path
+    pub path: Vec<usize>,
+    pub tour_cost: f64,                   // closed tour cost on the undirected weights, this is what the ratio bounds
+    pub used_matching: bool,              // false when it fell back to the double tree
+    pub exact_matching: bool,             // the matching was the minimum one, the greedy heuristic otherwise
+    pub approximation_ratio: Option<f64>, // 1.5 or 2 against the optimal closed tour, only for symmetric metric input
+    pub symmetry: SymmetryReport,
+}
+
+fn symmetric_tour_result(distance_matrix: &[Vec<f64>], weights: &[Vec<f64>], tour: Vec<usize>, matching: Option<bool>, symmetry: &SymmetryReport) -> ChristofidesResult {
+    // matching is None for the double tree and whether the matching was exact otherwise
+    let n = tour.len();
+    let tour_cost = (0..n).map(|i| weights[tour[i]][tour[(i + 1) % n]]).sum();
+    let (cost, path) = open_path_from_tour(distance_matrix, &tour);
+    let approximation_ratio = match matching {
+        _ if !(symmetry.symmetric && symmetry.metric) => None,
+        None => Some(2.0),
+        Some(true) => Some(1.5),
+        Some(false) => None, // the greedy matching has no constant factor against the minimum one
+    };
+    ChristofidesResult { cost, path, tour_cost, used_matching: matching.is_some(), exact_matching: matching == Some(true), approximation_ratio, symmetry: symmetry.clone() }
+}
+
+fn christofides_failure(symmetry: &SymmetryReport) -> ChristofidesResult {
+    ChristofidesResult { cost: f64::INFINITY, path: Vec::new(), tour_cost: f64::INFINITY, used_matching: false, exact_matching: false, approximation_ratio: None, symmetry: symmetry.clone() }
+}
+
+pub fn double_tree(distance_matrix: &[Vec<f64>], symmetry: &SymmetryReport) -> ChristofidesResult {
+    // walk around the doubled MST and shortcut repeats (2-approximation for symmetric metric costs).
+    // Asymmetric input is solved on the cheaper direction of every pair, the guarantee is lost but the tour still helps.
+    // symmetry is detect_symmetry of the same matrix, computed once by the caller
+    let n = distance_matrix.len();
+    let weights = undirected_weights(distance_matrix);
+    let Some(tree) = minimum_spanning_tree(&weights) else {
+        return christofides_failure(symmetry);
+    };
+    if n == 0 {
+        return christofides_failure(symmetry);
+    }
+    let doubled: Vec<(usize, usize)> = tree.iter().chain(tree.iter()).copied().collect();
+    let tour = euler_shortcut(n, &doubled);
+    symmetric_tour_result(distance_matrix, &weights, tour, None, symmetry)
+}
+
+pub fn christofides(distance_matrix: &[Vec<f64>], symmetry: &SymmetryReport) -> ChristofidesResult {
+    // MST + perfect matching on its odd degree nodes + Euler tour shortcutting (1.5-approximation for symmetric metric
+    // costs). The matching is exact up to MATCHING_MAX_ODD_NODES odd nodes and greedy above that, the double tree is
+    // only the fallback when the odd nodes can't be matched at all. symmetry is detect_symmetry of the same matrix
+    let n = distance_matrix.len();
+    let weights = undirected_weights(distance_matrix);
+    let Some(mut edges) = minimum_spanning_tree(&weights) else {
+        return christofides_failure(symmetry);
+    };
+    if n == 0 {
+        return christofides_failure(symmetry);
+    }
+    let mut degree = vec![0; n];
+    for &(a, b) in &edges {
+        degree[a] += 1;
+        degree[b] += 1;
+    }
+    let odd_nodes: Vec<usize> = (0..n).filter(|&node| degree[node] % 2 == 1).collect();
+    let exact = odd_nodes.len() <= MATCHING_MAX_ODD_NODES;
+    let matching = if exact { minimum_weight_matching(&weights, &odd_nodes) } else { greedy_matching(&weights, &odd_nodes) };
+    match matching {
+        Some(matching) => {
+            edges.extend(matching);
+            let tour = euler_shortcut(n, &edges);
+            symmetric_tour_result(distance_matrix, &weights, tour, Some(exact), symmetry)
+        }
+        None => double_tree(distance_matrix, symmetry),
     }
-    
 }
@@ -1,9 +1,163 @@
 
 use std::fs;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+const SIZE:usize = 7; // Avg cost, min cost, max cost, avg time, max time, total gap %, routes with a gap
 
+struct InstanceGaps { // best lower bound of one world and the gap of every algorithm run on it
+    lower_bound: f64,
+    gaps: Vec<(&'static str, f64)>,
+}
 
+// a run that errors out or finds no path is recorded with infinite cost, it counts as a route with no answer
+fn record_run(data: &mut [f64; SIZE], instance: &mut InstanceGaps, name: &'static str, cost: f64, duration: f64) { // adds one route to an algorithms running totals
+    data[0] += cost;
+    data[3] += duration;
+    if data[1] > cost { // set min cost
+        data[1] = cost;
+    }
+    if data[2] < cost { // set max cost
+        data[2] = cost;
+    }
+    if data[4] < duration { // set max time
+        data[4] = duration;
+    }
+    if cost.is_finite() && instance.lower_bound.is_finite() && instance.lower_bound > 0.0 { // gap to the lower bound, failed routes have none
+        let gap = 100.0 * (cost - instance.lower_bound) / instance.lower_bound;
+        data[5] += gap;
+        data[6] += 1.0;
+        instance.gaps.push((name, gap));
+    }
+}
+
+fn record_improvement(improvement: &mut [f64; 3], cost: f64, improved_cost: f64) { // only routes where both ends found a path count
+    if cost.is_finite() && improved_cost.is_finite() && cost > 0.0 {
+        improvement[0] += cost - improved_cost;
+        improvement[1] += 100.0 * (cost - improved_cost) / cost;
+        improvement[2] += 1.0;
+    }
+}
+
+fn improvement_summary(improvement: &[f64; 3]) -> String {
+    if improvement[2] > 0.0 {
+        format!("avg cost saved {:.6}, avg {:.2}%", improvement[0] / improvement[2], improvement[1] / improvement[2])
+    } else {
+        "avg cost saved -, avg -%".to_string()
+    }
+}
+
+fn print_row(name: &str, data: &[f64; SIZE], routes_planned_counter: usize, works_on_sparse: &str, mult_aircraft: &str, twenty_locations: &str, thousand_locations: &str) {
+    let avg_gap = if data[6] > 0.0 { format!("{:.2}", data[5] / data[6]) } else { "-".to_string() };
+    println!(
+        "|{:<20}|{:<19.6}|{:<13.6}|{:<13.6}|{:<13.6}|{:<13.6}|{:<13}|{:<16}|{:<14}|{:<13}|{:<15}|",
+        name,
+        data[0] / routes_planned_counter as f64,  // Ensure floating-point division, avg cost
+        data[1], //min cost
+        data[2], // max cost
+        data[3] / routes_planned_counter as f64,  // Ensure floating-point division, avg time
+        data[4], // max time
+        avg_gap, // avg gap % over the routes that found a path
+        works_on_sparse,
+        mult_aircraft,
+        twenty_locations,
+        thousand_locations
+    );
+}
+
+fn fuel_limits(distance_matrix: &[Vec<f64>]) -> crate::fuel::FuelLimits {
+    // same rules on every world: fuel at every third airport, endurance three average arcs and legs up to two
+    let arcs: Vec<f64> = distance_matrix.iter().flatten().copied().filter(|cost| cost.is_finite()).collect();
+    let average = arcs.iter().sum::<f64>() / arcs.len().max(1) as f64;
+    crate::fuel::FuelLimits { max_leg_cost: 2.0 * average, endurance: 3.0 * average, refuel_nodes: (0..distance_matrix.len()).step_by(3).collect() }
+}
+
+// the generators below draw from a SeededRng with a fixed seed, so every analyzer run sees the same instances
+fn time_windows(distance_matrix: &[Vec<f64>], reference_path: &[usize]) -> Vec<crate::time_windows::TimeWindow> {
+    // windows two average arcs wide that the reference path arrives in at a random point, so the reference
+    // is on time without waiting anywhere but the windows don't give it away. Without a reference every window stays open
+    let arcs: Vec<f64> = distance_matrix.iter().flatten().copied().filter(|cost| cost.is_finite()).collect();
+    let width = 2.0 * arcs.iter().sum::<f64>() / arcs.len().max(1) as f64;
+    let mut rng = crate::rng::SeededRng::new(42);
+    let mut windows = vec![crate::time_windows::TimeWindow::default(); distance_matrix.len()];
+    let reference = crate::time_windows::schedule_path(distance_matrix, &windows, reference_path, 0.0);
+    for stop in &reference.stops {
+        let earliest = (stop.arrival - rng.next_f64() * width).max(0.0);
+        windows[stop.node] = crate::time_windows::TimeWindow { earliest, latest: earliest + width, service: 0.0 };
+    }
+    windows
+}
+
+fn precedences(locations: usize) -> Vec<(usize, usize)> {
+    // a quarter as many pairs as locations, both ends picked from one shuffled order so the pairs never
+    // form a cycle and some route always keeps them all
+    let mut rng = crate::rng::SeededRng::new(42);
+    let mut order: Vec<usize> = (0..locations).collect();
+    rng.shuffle(&mut order);
+    let mut pairs = Vec::new();
+    while locations >= 2 && pairs.len() < locations / 4 {
+        let (a, b) = (rng.gen_range(locations), rng.gen_range(locations));
+        if a != b && !pairs.contains(&(order[a.min(b)], order[a.max(b)])) {
+            pairs.push((order[a.min(b)], order[a.max(b)]));
+        }
+    }
+    pairs
+}
+
+fn demands(locations: usize) -> Vec<f64> {
+    // 1 to 10 units of cargo for every location but the depot at 0
+    let mut rng = crate::rng::SeededRng::new(42);
+    (0..locations).map(|node| if node == 0 { 0.0 } else { 1.0 + rng.gen_range(10) as f64 }).collect()
+}
+
+fn prizes(distance_matrix: &[Vec<f64>]) -> (Vec<f64>, f64) {
+    // 1 to 10 prize for every location but the depot at 0 and a budget of a fifth as many average arcs as
+    // locations, routes fly the short arcs so that reaches around half of the world
+    let locations = distance_matrix.len();
+    let arcs: Vec<f64> = distance_matrix.iter().flatten().copied().filter(|cost| cost.is_finite()).collect();
+    let average = arcs.iter().sum::<f64>() / arcs.len().max(1) as f64;
+    let mut rng = crate::rng::SeededRng::new(7);
+    let prizes = (0..locations).map(|node| if node == 0 { 0.0 } else { 1.0 + rng.gen_range(10) as f64 }).collect();
+    (prizes, average * locations as f64 / 5.0)
+}
+
+fn required_flags(locations: usize) -> Vec<bool> {
+    // about a third of the locations are optional transit points, location 0 is always required
+    let mut rng = crate::rng::SeededRng::new(42);
+    (0..locations).map(|node| node == 0 || rng.gen_range(3) != 0).collect()
+}
+
+fn clusters(locations: usize) -> Vec<usize> {
+    // the locations shuffled and cut into clusters of three, the last one may be smaller
+    let mut rng = crate::rng::SeededRng::new(42);
+    let mut order: Vec<usize> = (0..locations).collect();
+    rng.shuffle(&mut order);
+    let mut cluster_of = vec![0; locations];
+    for (position, &node) in order.iter().enumerate() {
+        cluster_of[node] = position / 3;
+    }
+    cluster_of
+}
+
+fn aircraft_types(locations: usize) -> Vec<crate::fleet::AircraftType> {
+    // three airframes out of location 0: the baseline, a cheaper one barred from a few airports and a
+    // pricier one barred from some arcs
+    let mut rng = crate::rng::SeededRng::new(42);
+    let mut prohibited_nodes: Vec<usize> = (0..locations / 8).map(|_| 1 + rng.gen_range(locations.max(2) - 1)).collect();
+    prohibited_nodes.sort_unstable();
+    prohibited_nodes.dedup();
+    let prohibited_arcs = (0..locations / 2).map(|_| (rng.gen_range(locations), rng.gen_range(locations))).collect();
+    vec![
+        crate::fleet::AircraftType::default(),
+        crate::fleet::AircraftType { cost_multiplier: 0.8, prohibited_nodes, ..Default::default() },
+        crate::fleet::AircraftType { cost_multiplier: 1.3, prohibited_arcs, ..Default::default() },
+    ]
+}
+
+fn new_run_data() -> [f64; SIZE] {
+    let mut data: [f64; SIZE] = [0.0; SIZE];
+    data[1] = f64::INFINITY;
+    data
+}
 
 
 
@@ -12,23 +166,87 @@ use std::time::Instant;
 
 pub fn algorithm_analyzer(table_name:String, folder_name:String, lower_bound: usize, upper_bound: usize ) {
     //--------------Initalize avg time and cost for algorithms here----------------// 
-    const SIZE:usize = 5;
     // held karp nearest
-    let mut held_karp_data: [f64; SIZE] = [0.0; SIZE]; // Avg cost, min cost, max cost, avg time, max time (only need worse case and avg for time really)
-    held_karp_data[1] = f64::INFINITY;
+    let mut held_karp_data = new_run_data(); // Avg cost, min cost, max cost, avg time, max time (only need worse case and avg for time really)
     //sparse nearest
-    let mut sparse_nearest_data: [f64; SIZE] = [0.0; SIZE]; // Avg cost, min cost, max cost, avg time, max time
-    sparse_nearest_data[1] = f64::INFINITY;
+    let mut sparse_nearest_data = new_run_data();
     // full nearest
-    let mut full_nearest_data: [f64; SIZE] = [0.0; SIZE]; // Avg cost, min cost, max cost, avg time, max time
-    full_nearest_data[1] = f64::INFINITY;
+    let mut full_nearest_data = new_run_data();
+    // nearest neighbor followed by 2-opt, improvement holds total cost saved, total percent saved over the starting route
+    // and how many routes those cover
+    let mut sparse_two_opt_data = new_run_data();
+    let mut sparse_two_opt_improvement: [f64; 3] = [0.0; 3];
+    let mut full_two_opt_data = new_run_data();
+    let mut full_two_opt_improvement: [f64; 3] = [0.0; 3];
+    let mut multi_start_data = new_run_data();
+    let mut christofides_data = new_run_data();
+    let mut double_tree_data = new_run_data();
+    let mut symmetry_counts: [usize; 4] = [0; 4]; // symmetric, symmetric and metric, exact matching used, greedy matching used
+    let mut max_asymmetry: f64 = 0.0;
+    // solvers run on the metric closure, legs may fly through airports visited elsewhere. Pass-throughs holds
+    // total pass-through stops and routes that only exist because of them (held karp had no path)
+    let mut ferry_held_karp_data = new_run_data();
+    let mut ferry_annealing_data = new_run_data();
+    let mut ferry_pass_throughs: [usize; 2] = [0; 2];
+    // nearest neighbor followed by 2-opt, Or-opt and segment swap until none improve
+    let mut sparse_local_search_data = new_run_data();
+    // simulated annealing seeded from sparse nearest neighbor, fixed seed so tables are comparable between runs
+    let mut annealing_data = new_run_data();
+    // genetic algorithm seeded with sparse nearest neighbor
+    let mut genetic_data = new_run_data();
+    // ant colony system, failed ants holds ants that dead ended and total ants run
+    let mut ant_colony_data = new_run_data();
+    let mut ant_colony_failed_ants: [usize; 2] = [0; 2];
+    // fuel limited routes, refuel stops holds the refuel stops in the Held Karp and the annealing routes
+    let mut fuel_held_karp_data = new_run_data();
+    let mut fuel_annealing_data = new_run_data();
+    let mut fuel_refuel_stops: [usize; 2] = [0; 2];
+    // MILP with lazy subtour cuts, cuts holds total solves and total cut rows added
+    let mut milp_data = new_run_data();
+    let mut milp_cuts: [usize; 2] = [0; 2];
+    // time windows, totals holds total waiting, total lateness and worlds strict mode rejected
+    let mut time_window_data = new_run_data();
+    let mut time_window_totals: [f64; 3] = [0.0; 3];
+    // precedence pairs, missed counts worlds where nearest+Or-opt and annealing found no route keeping every pair
+    let mut precedence_held_karp_data = new_run_data();
+    let mut precedence_nearest_data = new_run_data();
+    let mut precedence_annealing_data = new_run_data();
+    let mut precedence_missed: [usize; 2] = [0; 2];
+    // three aircraft from a shared depot, the total cost row records the fleet total and the min-max row the longest route
+    let mut fleet_total_data = new_run_data();
+    let mut fleet_min_max_data = new_run_data();
+    // the same three aircraft as a mixed fleet, different cost multipliers and prohibited airports and arcs
+    let mut fleet_mixed_data = new_run_data();
+    // prize-collecting routes out of location 0 under a budget, the rows hold the cost spent. totals holds the heuristic
+    // and DP prizes on worlds the DP solved, how many those were and locations the heuristic skipped
+    let mut orienteering_heuristic_data = new_run_data();
+    let mut orienteering_dp_data = new_run_data();
+    let mut orienteering_totals: [f64; 4] = [0.0; 4];
+    // only the required locations have to be visited, totals holds optional airports flown through by the annealing
+    // routes and worlds the pre-check calls infeasible that get a route this way
+    let mut steiner_held_karp_data = new_run_data();
+    let mut steiner_annealing_data = new_run_data();
+    let mut steiner_totals: [usize; 2] = [0; 2];
+    // one location from every cluster of three on the metric closure, totals holds the heuristic and DP costs on worlds
+    // the DP solved and how many those were
+    let mut gtsp_heuristic_data = new_run_data();
+    let mut gtsp_dp_data = new_run_data();
+    let mut gtsp_totals: [f64; 3] = [0.0; 3];
+    // capacity 30 cargo routes out of location 0, totals holds routes flown and cargo carried by the local search plans
+    let mut cvrp_savings_data = new_run_data();
+    let mut cvrp_local_search_data = new_run_data();
+    let mut cvrp_totals: [f64; 2] = [0.0; 2];
+    // branch and bound, proofs holds routes proved optimal, routes attempted and total search nodes
+    let mut branch_and_bound_data = new_run_data();
+    let mut branch_and_bound_proofs: [usize; 3] = [0; 3];
     //----------------------------------------------------------------------------//
     let folder_path = folder_name; 
     let mut routes_planned_counter = 0;
+    let mut infeasible_worlds = 0;
     for entry in fs::read_dir(&folder_path).expect("Failed to read directory") {
         let entry = entry.expect("Failed to read entry");
         let file_path = entry.path();
-        if file_path.is_file() && file_path.extension().map_or(false, |ext| ext == "csv") {
+        if file_path.is_file() && file_path.extension().is_some_and(|ext| ext == "csv") {
             let distances = match crate::csv_readers::read_csv_as_hashmap(file_path.to_str().unwrap()) {   // gets hashmap for route algorithm
                 Ok(distances) => distances,
                 Err(e) => {
@@ -47,108 +265,394 @@ pub fn algorithm_analyzer(table_name:String, folder_name:String, lower_bound: us
             //println!("{}-{}-{}",amount_of_nodes, lower_bound,upper_bound );
             if amount_of_nodes >= lower_bound && amount_of_nodes <= upper_bound{
                 routes_planned_counter += 1;
+                // feasibility pre-check, an obstruction proves no route visits every location once
+                let report = crate::feasibility::check(&distances);
+                if !report.is_feasible() {
+                    infeasible_worlds += 1;
+                    println!("{}: {}", file_path.display(), report);
+                }
+                // lower bounds, the sparse nearest route is the target for the subgradient steps
+                let target_cost = crate::algorithms::nearest_neighbor_sparse(&distances).0;
+                let bounds = crate::lower_bounds::lower_bounds(&distance_matrix, target_cost, &Default::default());
+                let mut instance = InstanceGaps { lower_bound: bounds.best(), gaps: Vec::new() };
+                // rows that don't solve the visit once path (ferry legs, fleets, subsets of the airports) have no gap to its bound
+                let mut no_bound = InstanceGaps { lower_bound: f64::INFINITY, gaps: Vec::new() };
                 //-------------------------Put new algorithms here and their limits here -------------------------------------//
                 // held karp
-                if distance_matrix.len() <= 20 {
+                let mut held_karp_cost = f64::INFINITY;
+                if distance_matrix.len() <= 25 {
                     let start_time = Instant::now();  // Start timer
-                    let (cost,_path) = crate::algorithms::held_karp_algorithm(&distance_matrix);
-                    let duration = start_time.elapsed().as_secs_f64();  // get total time in fractional seconds
-                    held_karp_data[0] += cost;
-                    held_karp_data[3] += duration;
-                    if held_karp_data[1] > cost { // set min cost
-                        held_karp_data[1] = cost;
+                    match crate::algorithms::held_karp_algorithm(&distance_matrix, crate::algorithms::HeldKarpMode::BestOpenPath, &Default::default()) {
+                        Ok((cost, _path)) => {
+                            let duration = start_time.elapsed().as_secs_f64();  // get total time in fractional seconds
+                            record_run(&mut held_karp_data, &mut instance, "Held Karp", cost, duration);
+                            held_karp_cost = cost;
+                        }
+                        Err(crate::algorithms::HeldKarpError::Infeasible(_)) => { // failed fast
+                            record_run(&mut held_karp_data, &mut instance, "Held Karp", f64::INFINITY, start_time.elapsed().as_secs_f64());
+                        }
+                        Err(e) => println!("Held Karp skipped {}: {}", file_path.display(), e),
+                    }
+                }
+                // held karp and simulated annealing with ferry legs through already visited airports, repeating airports can
+                // go below the visit once bound so these rows get no gap
+                if distance_matrix.len() <= 25 {
+                    let start_time = Instant::now();
+                    let route = crate::metric_closure::solve_with_ferry_legs(&distance_matrix, |closure| {
+                        crate::algorithms::held_karp_algorithm(closure, crate::algorithms::HeldKarpMode::BestOpenPath, &Default::default())
+                            .unwrap_or((f64::INFINITY, Vec::new()))
+                    });
+                    record_run(&mut ferry_held_karp_data, &mut no_bound, "Held Karp ferry legs", route.cost, start_time.elapsed().as_secs_f64());
+                    ferry_pass_throughs[0] += route.stops.iter().filter(|stop| stop.pass_through).count();
+                    if route.cost.is_finite() && route.stops.len() > route.visit_order.len() && held_karp_cost == f64::INFINITY {
+                        ferry_pass_throughs[1] += 1;
+                    }
+                }
+                let start_time = Instant::now();
+                let route = crate::metric_closure::solve_with_ferry_legs(&distance_matrix, |closure| {
+                    let config = crate::algorithms::AnnealingConfig { seed: 42, time_budget: Duration::from_secs(1), ..Default::default() };
+                    let result = crate::algorithms::simulated_annealing(closure, &config);
+                    (result.cost, result.path)
+                });
+                record_run(&mut ferry_annealing_data, &mut no_bound, "Annealing ferry legs", route.cost, start_time.elapsed().as_secs_f64());
+                // fuel limited routes, exact for small worlds and annealing on the in range world with refuel stops fitted after
+                let limits = fuel_limits(&distance_matrix);
+                let start_time = Instant::now();
+                match crate::fuel::fuel_held_karp(&distance_matrix, &limits) {
+                    Ok(route) => {
+                        record_run(&mut fuel_held_karp_data, &mut instance, "Held Karp fuel", route.cost, start_time.elapsed().as_secs_f64());
+                        fuel_refuel_stops[0] += route.stops.iter().filter(|stop| stop.refuel_stop).count();
                     }
-                    if held_karp_data[2] < cost{ // set max cost
-                        held_karp_data[2] = cost;
+                    Err(crate::algorithms::HeldKarpError::TooManyNodes { .. }) => {} // only small worlds
+                    Err(e) => println!("Fuel Held Karp skipped {}: {}", file_path.display(), e),
+                }
+                let start_time = Instant::now();
+                let route = crate::fuel::solve_with_refuel_stops(&distance_matrix, &limits, |in_range| {
+                    let config = crate::algorithms::AnnealingConfig { seed: 42, time_budget: Duration::from_secs(1), ..Default::default() };
+                    let result = crate::algorithms::simulated_annealing(in_range, &config);
+                    (result.cost, result.path)
+                });
+                record_run(&mut fuel_annealing_data, &mut instance, "Annealing fuel", route.cost, start_time.elapsed().as_secs_f64());
+                fuel_refuel_stops[1] += route.stops.iter().filter(|stop| stop.refuel_stop).count();
+                // MILP with lazy subtour cuts, a second exact method
+                let milp_config = crate::milp::MilpConfig::default();
+                if distance_matrix.len() <= milp_config.max_nodes {
+                    let start_time = Instant::now();
+                    match crate::milp::solve_with_subtour_cuts(&distance_matrix, &milp_config) {
+                        Ok(result) => {
+                            record_run(&mut milp_data, &mut instance, "MILP subtour cuts", result.cost, start_time.elapsed().as_secs_f64());
+                            milp_cuts[0] += result.cut_rounds;
+                            milp_cuts[1] += result.cuts;
+                        }
+                        Err(e) => println!("MILP skipped {}: {}", file_path.display(), e),
                     }
-                    if held_karp_data[4] < duration{ // set max time
-                        held_karp_data[4] = duration;
+                }
+                // branch and bound, exact up to about 60 locations within the time limit
+                if distance_matrix.len() <= 60 {
+                    let start_time = Instant::now();
+                    let result = crate::algorithms::branch_and_bound(&distance_matrix, &Default::default());
+                    record_run(&mut branch_and_bound_data, &mut instance, "Branch and Bound", result.cost, start_time.elapsed().as_secs_f64());
+                    if result.proved_optimal {
+                        branch_and_bound_proofs[0] += 1;
+                    } else {
+                        println!("Branch and Bound hit its time limit on {}, best {} and bound {}", file_path.display(), result.cost, result.lower_bound);
                     }
+                    branch_and_bound_proofs[1] += 1;
+                    branch_and_bound_proofs[2] += result.nodes_explored;
                 }
                 // sparse nearest neighbor
                 let start_time = Instant::now();  // Start timer
-                let (cost,_path) = crate::algorithms::nearest_neighbor_sparse(&distances);
+                let (cost,path) = crate::algorithms::nearest_neighbor_sparse(&distances);
                 let duration = start_time.elapsed().as_secs_f64();  // get total time in fractional seconds
-                sparse_nearest_data[0] += cost;
-                sparse_nearest_data[3] += duration;
-                if sparse_nearest_data[1] > cost { // set min cost
-                    sparse_nearest_data[1] = cost;
+                record_run(&mut sparse_nearest_data, &mut instance, "Sparse Nearest", cost, duration);
+                // sparse nearest neighbor + 2-opt, time includes building the starting route
+                let start_time = Instant::now();
+                let (two_opt_cost,_two_opt_path) = crate::algorithms::two_opt(&distances, &path);
+                let two_opt_duration = duration + start_time.elapsed().as_secs_f64();
+                record_run(&mut sparse_two_opt_data, &mut instance, "Sparse Nearest+2opt", two_opt_cost, two_opt_duration);
+                record_improvement(&mut sparse_two_opt_improvement, cost, two_opt_cost);
+                // sparse nearest neighbor + full local search
+                let start_time = Instant::now();
+                let (local_search_cost,_local_search_path) = crate::algorithms::local_search(&distances, &path);
+                record_run(&mut sparse_local_search_data, &mut instance, "Sparse Nearest+LS", local_search_cost, duration + start_time.elapsed().as_secs_f64());
+                // simulated annealing
+                let start_time = Instant::now();
+                let annealing_config = crate::algorithms::AnnealingConfig {
+                    seed: 42,
+                    time_budget: Duration::from_secs(5),
+                    initial_path: Some(path.clone()),
+                    ..Default::default()
+                };
+                let annealing_result = crate::algorithms::simulated_annealing(&distance_matrix, &annealing_config);
+                record_run(&mut annealing_data, &mut instance, "Simulated Annealing", annealing_result.cost, duration + start_time.elapsed().as_secs_f64());
+                // genetic algorithm, builds its own nearest neighbor seed so the time covers everything
+                let start_time = Instant::now();
+                let genetic_config = crate::algorithms::GeneticConfig { seed: 42, ..Default::default() };
+                let (genetic_cost,_genetic_path) = crate::algorithms::genetic_algorithm(&distances, &genetic_config);
+                record_run(&mut genetic_data, &mut instance, "Genetic Algorithm", genetic_cost, start_time.elapsed().as_secs_f64());
+                // ant colony system
+                let start_time = Instant::now();
+                let ant_colony_config = crate::algorithms::AntColonyConfig { seed: 42, ..Default::default() };
+                let ant_colony_result = crate::algorithms::ant_colony_system(&distances, &ant_colony_config);
+                record_run(&mut ant_colony_data, &mut instance, "Ant Colony", ant_colony_result.cost, start_time.elapsed().as_secs_f64());
+                ant_colony_failed_ants[0] += ant_colony_result.failed_ants;
+                ant_colony_failed_ants[1] += ant_colony_result.total_ants;
+                // multi-start nearest neighbor, every start node with random tie breaking
+                let start_time = Instant::now();
+                let multi_start_config = crate::algorithms::MultiStartConfig { seed: 42, ..Default::default() };
+                let (multi_start_cost,_multi_start_path) = crate::algorithms::multi_start_nearest_neighbor(&distances, &multi_start_config);
+                record_run(&mut multi_start_data, &mut instance, "Multi-start Nearest", multi_start_cost, start_time.elapsed().as_secs_f64());
+                // time windows around the sparse nearest route, relaxed mode gets a row and strict mode is only counted
+                let windows = time_windows(&distance_matrix, &path);
+                let start_time = Instant::now();
+                match crate::time_windows::time_window_route(&distance_matrix, &windows, &Default::default()) {
+                    Ok(schedule) => {
+                        record_run(&mut time_window_data, &mut instance, "Time windows", schedule.cost, start_time.elapsed().as_secs_f64());
+                        time_window_totals[0] += schedule.total_wait;
+                        time_window_totals[1] += schedule.total_lateness;
+                    }
+                    Err(e) => println!("Time windows skipped {}: {}", file_path.display(), e),
+                }
+                let strict_config = crate::time_windows::TimeWindowConfig { strict: true, ..Default::default() };
+                if crate::time_windows::time_window_route(&distance_matrix, &windows, &strict_config).is_err() {
+                    time_window_totals[2] += 1.0;
+                }
+                // precedence pairs, exact for small worlds, nearest neighbor + Or-opt and annealing that keep every pair
+                let pairs = precedences(distance_matrix.len());
+                if distance_matrix.len() <= 25 {
+                    let start_time = Instant::now();
+                    let config = crate::algorithms::HeldKarpConfig { precedences: pairs.clone(), ..Default::default() };
+                    match crate::algorithms::held_karp_algorithm(&distance_matrix, crate::algorithms::HeldKarpMode::BestOpenPath, &config) {
+                        Ok((cost, _path)) => record_run(&mut precedence_held_karp_data, &mut instance, "Held Karp precedence", cost, start_time.elapsed().as_secs_f64()),
+                        Err(crate::algorithms::HeldKarpError::Infeasible(_)) => {
+                            record_run(&mut precedence_held_karp_data, &mut instance, "Held Karp precedence", f64::INFINITY, start_time.elapsed().as_secs_f64());
+                        }
+                        Err(e) => println!("Precedence Held Karp skipped {}: {}", file_path.display(), e),
+                    }
                 }
-                if sparse_nearest_data[2] < cost{ // set max cost
-                    sparse_nearest_data[2] = cost;
+                let start_time = Instant::now();
+                let (_, precedence_path) = crate::precedence::precedence_nearest_neighbor(&distance_matrix, &pairs);
+                let (precedence_cost, precedence_path) = crate::precedence::precedence_or_opt(&distance_matrix, &pairs, &precedence_path);
+                record_run(&mut precedence_nearest_data, &mut instance, "Precedence NN+Or-opt", precedence_cost, start_time.elapsed().as_secs_f64());
+                precedence_missed[0] += precedence_cost.is_infinite() as usize;
+                let start_time = Instant::now();
+                let config = crate::algorithms::AnnealingConfig {
+                    seed: 42,
+                    time_budget: Duration::from_secs(1),
+                    initial_path: Some(precedence_path),
+                    precedences: pairs,
+                    ..Default::default()
+                };
+                let result = crate::algorithms::simulated_annealing(&distance_matrix, &config);
+                record_run(&mut precedence_annealing_data, &mut instance, "Precedence Annealing", result.cost, start_time.elapsed().as_secs_f64());
+                precedence_missed[1] += result.cost.is_infinite() as usize;
+                // three aircraft out of location 0, the fleet costs can't be compared with the single path bound
+                for (objective, data) in [(crate::fleet::FleetObjective::TotalCost, &mut fleet_total_data), (crate::fleet::FleetObjective::MinMax, &mut fleet_min_max_data)] {
+                    let start_time = Instant::now();
+                    let config = crate::fleet::FleetConfig { aircraft: 3, objective, ..Default::default() };
+                    let cost = match crate::fleet::plan_fleet(&distances, &config) {
+                        Ok(plan) if objective == crate::fleet::FleetObjective::MinMax => plan.max_route_cost,
+                        Ok(plan) => plan.total_cost,
+                        Err(_) => f64::INFINITY,
+                    };
+                    record_run(data, &mut no_bound, "Fleet", cost, start_time.elapsed().as_secs_f64());
+                }
+                let start_time = Instant::now();
+                let config = crate::fleet::FleetConfig { aircraft: 3, aircraft_types: aircraft_types(distance_matrix.len()), ..Default::default() };
+                let cost = crate::fleet::plan_fleet(&distances, &config).map_or(f64::INFINITY, |plan| plan.total_cost);
+                record_run(&mut fleet_mixed_data, &mut no_bound, "Fleet Mixed", cost, start_time.elapsed().as_secs_f64());
+                // budgeted prize collection, the heuristic everywhere and the exact DP up to 20 locations
+                let (world_prizes, budget) = prizes(&distance_matrix);
+                let config = crate::orienteering::OrienteeringConfig { budget, ..Default::default() };
+                // these rows record the prize collected, every route spends at most the budget so its cost says little
+                // a rejected config has no prize to record, infinity would read as the best one, so those rows are skipped
+                let start_time = Instant::now();
+                let heuristic = crate::orienteering::orienteering_heuristic(&distance_matrix, &world_prizes, &config);
+                if let Ok(route) = &heuristic {
+                    record_run(&mut orienteering_heuristic_data, &mut no_bound, "Orienteering", route.prize, start_time.elapsed().as_secs_f64());
+                    orienteering_totals[3] += route.skipped.len() as f64;
+                }
+                let start_time = Instant::now();
+                if let Ok(exact) = crate::orienteering::orienteering_held_karp(&distance_matrix, &world_prizes, &config) {
+                    record_run(&mut orienteering_dp_data, &mut no_bound, "Orienteering DP", exact.prize, start_time.elapsed().as_secs_f64());
+                    if let Ok(route) = &heuristic {
+                        orienteering_totals[0] += route.prize;
+                        orienteering_totals[1] += exact.prize;
+                        orienteering_totals[2] += 1.0;
+                    }
+                }
+                // required and optional locations, exact on the closure between the required ones for small worlds and
+                // 1 s annealing everywhere
+                let required = required_flags(distance_matrix.len());
+                if distance_matrix.len() <= 25 {
+                    let start_time = Instant::now();
+                    let route = crate::steiner::solve_steiner(&distance_matrix, &required, |closure| {
+                        crate::algorithms::held_karp_algorithm(closure, crate::algorithms::HeldKarpMode::BestOpenPath, &Default::default())
+                            .unwrap_or((f64::INFINITY, Vec::new()))
+                    });
+                    record_run(&mut steiner_held_karp_data, &mut no_bound, "Held Karp required", route.cost, start_time.elapsed().as_secs_f64());
+                }
+                let start_time = Instant::now();
+                let route = crate::steiner::solve_steiner(&distance_matrix, &required, |closure| {
+                    let config = crate::algorithms::AnnealingConfig { seed: 42, time_budget: Duration::from_secs(1), ..Default::default() };
+                    let result = crate::algorithms::simulated_annealing(closure, &config);
+                    (result.cost, result.path)
+                });
+                record_run(&mut steiner_annealing_data, &mut no_bound, "Annealing required", route.cost, start_time.elapsed().as_secs_f64());
+                steiner_totals[0] += route.optional_flown.len();
+                if route.cost.is_finite() && !report.is_feasible() {
+                    steiner_totals[1] += 1;
+                }
+                // generalized TSP over clusters of three, on the metric closure so sparse worlds don't dead end. The heuristic
+                // everywhere and the exact DP while the tables stay small
+                let cluster_of = clusters(distance_matrix.len());
+                let start_time = Instant::now();
+                let closure = crate::metric_closure::metric_closure(&distance_matrix);
+                let closure_time = start_time.elapsed().as_secs_f64();
+                let route = crate::gtsp::gtsp_heuristic(&closure.distances, &cluster_of, &Default::default());
+                record_run(&mut gtsp_heuristic_data, &mut no_bound, "GTSP", route.cost, start_time.elapsed().as_secs_f64());
+                if distance_matrix.len() <= 36 {
+                    let start_time = Instant::now();
+                    if let Ok(exact) = crate::gtsp::gtsp_held_karp(&closure.distances, &cluster_of, &Default::default()) {
+                        record_run(&mut gtsp_dp_data, &mut no_bound, "GTSP DP", exact.cost, closure_time + start_time.elapsed().as_secs_f64());
+                        gtsp_totals[0] += route.cost;
+                        gtsp_totals[1] += exact.cost;
+                        gtsp_totals[2] += 1.0;
+                    }
                 }
-                if sparse_nearest_data[4] < duration{ // set max time
-                    sparse_nearest_data[4] = duration;
+                // capacitated cargo routes with ferry legs so the sparse worlds can loop through the depot, savings alone and
+                // with the local search
+                let cargo = demands(distance_matrix.len());
+                for (local_search, data) in [(false, &mut cvrp_savings_data), (true, &mut cvrp_local_search_data)] {
+                    let start_time = Instant::now();
+                    let config = crate::cvrp::CvrpConfig { local_search, ferry_legs: true, ..Default::default() };
+                    let solution = crate::cvrp::solve_cvrp(&distances, &cargo, &config);
+                    let cost = solution.as_ref().map_or(f64::INFINITY, |solution| solution.total_cost);
+                    record_run(data, &mut no_bound, "CVRP", cost, start_time.elapsed().as_secs_f64());
+                    if let (true, Ok(solution)) = (local_search, &solution) {
+                        cvrp_totals[0] += solution.routes.len() as f64;
+                        cvrp_totals[1] += solution.routes.iter().map(|route| route.load).sum::<f64>();
+                    }
                 }
                 // full graph nearest neighbor
                 if folder_path == "test-csv-full-world" {
                     let start_time = Instant::now();  // Start timer
-                    let (cost,_path) = crate::algorithms::nearest_neighbor_full_graph(&distances);
+                    let (cost,path) = crate::algorithms::nearest_neighbor_full_graph(&distances);
                     let duration = start_time.elapsed().as_secs_f64();  // get total time in fractional seconds
-                    full_nearest_data[0] += cost;
-                    full_nearest_data[3] += duration;
-                    if full_nearest_data[1] > cost { // set min cost
-                        full_nearest_data[1] = cost;
-                    }
-                    if full_nearest_data[2] < cost{ // set max cost
-                        full_nearest_data[2] = cost;
-                    }
-                    if full_nearest_data[4] < duration{ // set max time
-                        full_nearest_data[4] = duration;
-                    }
+                    record_run(&mut full_nearest_data, &mut instance, "full Nearest", cost, duration);
+                    // full nearest neighbor + 2-opt
+                    let start_time = Instant::now();
+                    let (two_opt_cost,_two_opt_path) = crate::algorithms::two_opt(&distances, &path);
+                    let two_opt_duration = duration + start_time.elapsed().as_secs_f64();
+                    record_run(&mut full_two_opt_data, &mut instance, "full Nearest+2opt", two_opt_cost, two_opt_duration);
+                    record_improvement(&mut full_two_opt_improvement, cost, two_opt_cost);
+                    // christofides and double tree. Their guarantees need symmetric metric costs, the full worlds are random
+                    // both ways so there they run on the cheaper direction without one and stay out of the gap column
+                    let symmetry = crate::algorithms::detect_symmetry(&distance_matrix, 1e-9);
+                    let guaranteed = symmetry.symmetric && symmetry.metric;
+                    let start_time = Instant::now();
+                    let christofides_result = crate::algorithms::christofides(&distance_matrix, &symmetry);
+                    let bound = if guaranteed { &mut instance } else { &mut no_bound };
+                    record_run(&mut christofides_data, bound, "full Christofides", christofides_result.cost, start_time.elapsed().as_secs_f64());
+                    let start_time = Instant::now();
+                    let double_tree_result = crate::algorithms::double_tree(&distance_matrix, &symmetry);
+                    let bound = if guaranteed { &mut instance } else { &mut no_bound };
+                    record_run(&mut double_tree_data, bound, "full Double Tree", double_tree_result.cost, start_time.elapsed().as_secs_f64());
+                    symmetry_counts[0] += symmetry.symmetric as usize;
+                    symmetry_counts[1] += christofides_result.approximation_ratio.is_some() as usize;
+                    symmetry_counts[2] += christofides_result.exact_matching as usize;
+                    symmetry_counts[3] += (christofides_result.used_matching && !christofides_result.exact_matching) as usize;
+                    max_asymmetry = max_asymmetry.max(symmetry.max_asymmetry);
                 }
                 //--------------------------------------------------------------------------------------------------------------//
+                // per world gaps of the rows solving the visit once path
+                let gaps: Vec<String> = instance.gaps.iter().map(|(name, gap)| format!("{} {:.2}%", name, gap)).collect();
+                println!("{}: lower bound assignment {:.4}, 1-arborescence {:.4}, Held Karp {:.4}; gap {}",
+                    file_path.display(), bounds.assignment, bounds.arborescence, bounds.held_karp, gaps.join(", "));
             }
             
         }
     }
 
     println!("{}", table_name);
-    println!("_______________________________________________________________________________________________________________________________________________________________");
-    println!("|{:<20}|{:<19}|{:<13}|{:<13}|{:<13}|{:<13}|{:<16}|{:<14}|{:<13}|{:<15}|"
-    , "Algorithms", "Avg Cost(per route)", "Min Cost", "Max Cost","Avg Time(Sec)", "Max Time" ,"Works on Sparse?" ,"Mult Aircraft?" , "20 locations?", "1000 locations?");
+    if routes_planned_counter == 0 { // every average below would be 0 / 0
+        println!("No worlds in {} with {} to {} locations", folder_path, lower_bound, upper_bound);
+        return;
+    }
+    println!("_____________________________________________________________________________________________________________________________________________________________________________");
+    println!("|{:<20}|{:<19}|{:<13}|{:<13}|{:<13}|{:<13}|{:<13}|{:<16}|{:<14}|{:<13}|{:<15}|"
+    , "Algorithms", "Avg Cost(per route)", "Min Cost", "Max Cost","Avg Time(Sec)", "Max Time", "Avg Gap %","Works on Sparse?" ,"Mult Aircraft?" , "20 locations?", "1000 locations?");
     //------------------------------Add algorithms info here----------------------------------//
-    println!( // held karp algorithm
-        "|{:<20}|{:<19.6}|{:<13.6}|{:<13.6}|{:<13.6}|{:<13.6}|{:<16}|{:<14}|{:<13}|{:<15}|",
-        "Held Karp",
-        held_karp_data[0] as f64 / routes_planned_counter as f64,  // Ensure floating-point division, avg cost
-        held_karp_data[1], //min cost
-        held_karp_data[2], // max cost
-        held_karp_data[3] as f64 / routes_planned_counter as f64,  // Ensure floating-point division, avg time
-        held_karp_data[4], // max cost
-        "Yes",
-        "No",
-        "Yes",
-        "No"
-    );
-    println!( // sparse nearest algorithm
-        "|{:<20}|{:<19.6}|{:<13.6}|{:<13.6}|{:<13.6}|{:<13.6}|{:<16}|{:<14}|{:<13}|{:<15}|",
-        "Sparse Nearest",
-        sparse_nearest_data[0] as f64 / routes_planned_counter as f64,  // Ensure floating-point division, avg cost
-        sparse_nearest_data[1], //min cost
-        sparse_nearest_data[2], // max cost
-        sparse_nearest_data[3] as f64 / routes_planned_counter as f64,  // Ensure floating-point division, avg time
-        sparse_nearest_data[4], // max cost
-        "Yes",
-        "No",
-        "Yes",
-        "Yes"
-    );
-    println!( // full nearest algorithm
-        "|{:<20}|{:<19.6}|{:<13.6}|{:<13.6}|{:<13.6}|{:<13.6}|{:<16}|{:<14}|{:<13}|{:<15}|",
-        "full Nearest",
-        full_nearest_data[0] as f64 / routes_planned_counter as f64,  // Ensure floating-point division, avg cost
-        full_nearest_data[1], //min cost
-        full_nearest_data[2], // max cost
-        full_nearest_data[3] as f64 / routes_planned_counter as f64,  // Ensure floating-point division, avg time
-        full_nearest_data[4], // max cost
-        "No",
-        "No",
-        "Yes",
-        "Yes"
-    );
+    print_row("Held Karp", &held_karp_data, routes_planned_counter, "Yes", "No", "Yes", "No"); // held karp algorithm
+    print_row("Branch and Bound", &branch_and_bound_data, routes_planned_counter, "Yes", "No", "Yes", "No"); // exact, up to 60 locations
+    print_row("MILP subtour cuts", &milp_data, routes_planned_counter, "Yes", "No", "Yes", "No"); // microlp with lazy subtour cuts, up to 120 locations
+    print_row("Held Karp ferry legs", &ferry_held_karp_data, routes_planned_counter, "Yes", "No", "Yes", "No"); // exact on the metric closure
+    print_row("Annealing ferry legs", &ferry_annealing_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // 1 s annealing on the metric closure
+    print_row("Held Karp fuel", &fuel_held_karp_data, routes_planned_counter, "Yes", "No", "No", "No"); // range limited, exact up to 16 locations
+    print_row("Annealing fuel", &fuel_annealing_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // range limited, refuel stops fitted to a 1 s annealing route
+    print_row("Time windows", &time_window_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // flight time, relaxed windows around the sparse nearest route
+    print_row("Held Karp precedence", &precedence_held_karp_data, routes_planned_counter, "Yes", "No", "Yes", "No"); // n / 4 visit in order pairs, exact
+    print_row("Precedence NN+Or-opt", &precedence_nearest_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // nearest neighbor over released locations + Or-opt
+    print_row("Precedence Annealing", &precedence_annealing_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // 1 s annealing from the nearest + Or-opt route
+    print_row("Fleet x3 total", &fleet_total_data, routes_planned_counter, "Yes", "Yes", "Yes", "Yes"); // total cost of three aircraft from location 0
+    print_row("Fleet x3 min-max", &fleet_min_max_data, routes_planned_counter, "Yes", "Yes", "Yes", "Yes"); // longest of three aircraft routes from location 0
+    print_row("Fleet x3 mixed", &fleet_mixed_data, routes_planned_counter, "Yes", "Yes", "Yes", "Yes"); // total cost of three different airframes from location 0
+    print_row("Orient. heur. prize", &orienteering_heuristic_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // prize collected in place of cost, budget of a fifth of the locations in average arcs
+    print_row("Orient. DP prize", &orienteering_dp_data, routes_planned_counter, "Yes", "No", "Yes", "No"); // exact subset DP, only up to 20 locations
+    print_row("Held Karp required", &steiner_held_karp_data, routes_planned_counter, "Yes", "No", "Yes", "No"); // about two thirds of the locations required, exact on the closure between them
+    print_row("Annealing required", &steiner_annealing_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // 1 s annealing on the closure between the required locations
+    print_row("GTSP heuristic", &gtsp_heuristic_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // one location per cluster of three, nearest neighbor + relocate and reselection
+    print_row("GTSP DP", &gtsp_dp_data, routes_planned_counter, "Yes", "No", "Yes", "No"); // exact (cluster set, location) DP, up to 12 clusters here
+    print_row("CVRP savings", &cvrp_savings_data, routes_planned_counter, "Yes", "Yes", "Yes", "Yes"); // Clarke-Wright with ferry legs, capacity 30 and 1 to 10 cargo per location
+    print_row("CVRP savings+LS", &cvrp_local_search_data, routes_planned_counter, "Yes", "Yes", "Yes", "Yes"); // plus relocate, swap and reversal moves
+    print_row("Sparse Nearest", &sparse_nearest_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // sparse nearest algorithm
+    print_row("full Nearest", &full_nearest_data, routes_planned_counter, "No", "No", "Yes", "Yes"); // full nearest algorithm
+    print_row("Multi-start Nearest", &multi_start_data, routes_planned_counter, "No", "No", "Yes", "Yes"); // nearest from every start node in parallel, no backtracking so sparse worlds can dead end
+    print_row("full Christofides", &christofides_data, routes_planned_counter, "No", "No", "Yes", "Yes"); // MST + matching (greedy above 20 odd nodes), 1.5 bound when symmetric and metric
+    print_row("full Double Tree", &double_tree_data, routes_planned_counter, "No", "No", "Yes", "Yes"); // doubled MST, 2 bound when symmetric and metric
+    print_row("Sparse Nearest+2opt", &sparse_two_opt_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // sparse nearest + 2-opt
+    print_row("full Nearest+2opt", &full_two_opt_data, routes_planned_counter, "No", "No", "Yes", "Yes"); // full nearest + 2-opt
+    print_row("Sparse Nearest+LS", &sparse_local_search_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // sparse nearest + 2-opt, Or-opt, segment swap
+    print_row("Simulated Annealing", &annealing_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // seeded from sparse nearest
+    print_row("Genetic Algorithm", &genetic_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // seeded from sparse nearest
+    print_row("Ant Colony", &ant_colony_data, routes_planned_counter, "Yes", "No", "Yes", "Yes"); // ant colony system
 
     //----------------------------------------------------------------------------------------//
-    println!("_______________________________________________________________________________________________________________________________________________________________");
+    println!("_____________________________________________________________________________________________________________________________________________________________________________");
+    println!("2-opt improvement over Sparse Nearest: {}", improvement_summary(&sparse_two_opt_improvement));
+    if branch_and_bound_proofs[1] > 0 {
+        println!("Branch and Bound proved optimal: {} of {}, avg search nodes {:.1}", branch_and_bound_proofs[0], branch_and_bound_proofs[1],
+            branch_and_bound_proofs[2] as f64 / branch_and_bound_proofs[1] as f64);
+    }
+    if milp_cuts[0] > 0 {
+        println!("MILP subtour cuts: {} solves and {} cuts in total", milp_cuts[0], milp_cuts[1]);
+    }
+    println!("Feasibility pre-check: {} of {} worlds have no route that visits every location once", infeasible_worlds, routes_planned_counter);
+    println!("Ferry legs: {} pass-through stops in the Held Karp routes, {} routes only possible with them", ferry_pass_throughs[0], ferry_pass_throughs[1]);
+    println!("Fuel limits: {} refuel stops in the Held Karp routes, {} in the annealing routes", fuel_refuel_stops[0], fuel_refuel_stops[1]);
+    println!("Time windows: avg waiting {:.4}, avg lateness {:.4}, strict mode rejected {} of {} worlds",
+        time_window_totals[0] / routes_planned_counter as f64, time_window_totals[1] / routes_planned_counter as f64, time_window_totals[2], routes_planned_counter);
+    println!("Precedence: no route keeping every pair from nearest+Or-opt in {} worlds, from annealing in {} of {}",
+        precedence_missed[0], precedence_missed[1], routes_planned_counter);
+    println!("CVRP: avg {:.2} routes, avg load factor {:.2}%", cvrp_totals[0] / routes_planned_counter as f64,
+        100.0 * cvrp_totals[1] / (cvrp_totals[0] * crate::cvrp::CvrpConfig::default().capacity));
+    println!("Orienteering: avg {:.2} locations skipped by the heuristic", orienteering_totals[3] / routes_planned_counter as f64);
+    if orienteering_totals[2] > 0.0 {
+        println!("Orienteering heuristic prize: {:.2}% of the exact DP's on the {} worlds it solved", 100.0 * orienteering_totals[0] / orienteering_totals[1], orienteering_totals[2]);
+    }
+    println!("Required locations: {} optional airports flown through by the annealing routes, {} infeasible worlds solved without the optional ones",
+        steiner_totals[0], steiner_totals[1]);
+    if gtsp_totals[2] > 0.0 {
+        println!("GTSP heuristic cost: {:.2}% above the exact DP's on the {} worlds it solved", 100.0 * (gtsp_totals[0] - gtsp_totals[1]) / gtsp_totals[1], gtsp_totals[2]);
+    }
+    println!("Ant Colony failed ants: {} of {}", ant_colony_failed_ants[0], ant_colony_failed_ants[1]);
+    if folder_path == "test-csv-full-world" {
+        println!("2-opt improvement over full Nearest: {}", improvement_summary(&full_two_opt_improvement));
+        println!("Symmetric worlds: {} of {}, with a proven Christofides bound: {}, largest asymmetry {:.2}, exact matching used on {}, greedy matching on {}",
+            symmetry_counts[0], routes_planned_counter, symmetry_counts[1], max_asymmetry, symmetry_counts[2], symmetry_counts[3]);
+        if symmetry_counts[1] < routes_planned_counter {
+            println!("Christofides and Double Tree ran without a guarantee on {} worlds, those have no gap", routes_planned_counter - symmetry_counts[1]);
+        }
+    }
 
 }
\ No newline at end of file
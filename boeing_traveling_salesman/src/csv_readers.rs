@@ -95,9 +95,164 @@ pub fn read_csv_as_hashmap(filename: &str) -> Result<HashMap<usize, HashMap<usiz
     for (i, j, cost) in edges {
         // Insert or append the (j, cost) tuple to the vector at distances[i]
         distances.entry(i)
-            .or_insert_with(|| HashMap::new())  // If the key i doesn't exist, initialize a new vector
+            .or_default()  // If the key i doesn't exist, initialize a new vector
             .insert(j, cost);         // Append (j, cost) to the vector
     
     }
     Ok(distances) // Return the HashMap wrapped in Result
 }
+
+fn read_rows<F>(filename: &str, columns: usize, mut read_row: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&[&str]) -> Result<(), Box<dyn Error>>,
+{
+    // the skeleton of the second csvs: skip the header and blank lines, split every row into trimmed fields and hand
+    // them to read_row. A row with the wrong number of fields or one read_row rejects fails with its line number
+    let contents = fs::read_to_string(filename)?;
+    for (index, line) in contents.lines().enumerate().skip(1) { // Skip the header
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+        if parts.len() != columns {
+            return Err(format!("{} line {}: expected {} fields but found {} in \"{}\"", filename, index + 1, columns, parts.len(), line).into());
+        }
+        read_row(&parts).map_err(|e| format!("{} line {}: {}", filename, index + 1, e))?;
+    }
+    Ok(())
+}
+
+fn location(field: &str, locations: usize, what: &str) -> Result<usize, Box<dyn Error>> { // a location column, inside the world
+    let node: usize = field.parse().map_err(|_| format!("{} location \"{}\" is not a location number", what, field))?;
+    if node >= locations {
+        return Err(format!("{} for location {} but the world has {} locations", what, node, locations).into());
+    }
+    Ok(node)
+}
+
+fn aircraft_index(field: &str, aircraft: usize, what: &str) -> Result<usize, Box<dyn Error>> { // an aircraft column, inside the fleet
+    let a: usize = field.parse().map_err(|_| format!("{} aircraft \"{}\" is not an aircraft number", what, field))?;
+    if a >= aircraft {
+        return Err(format!("{} for aircraft {} but the fleet has {} aircraft", what, a, aircraft).into());
+    }
+    Ok(a)
+}
+
+fn number(field: &str, what: &str) -> Result<f64, Box<dyn Error>> {
+    field.parse().map_err(|_| format!("{} \"{}\" is not a number", what, field).into())
+}
+
+pub fn read_time_windows_csv(filename: &str, locations: usize) -> Result<Vec<crate::time_windows::TimeWindow>, Box<dyn Error>> {
+    // Location,Earliest,Latest,Service rows, locations without a row get an always open window and no service time.
+    // Empty fields keep their default too, so "4,,12," is only a closing time
+    let mut windows = vec![crate::time_windows::TimeWindow::default(); locations];
+    read_rows(filename, 4, |parts| {
+        let window = &mut windows[location(parts[0], locations, "time window")?];
+        if !parts[1].is_empty() {
+            window.earliest = number(parts[1], "earliest time")?;
+        }
+        if !parts[2].is_empty() {
+            window.latest = number(parts[2], "latest time")?;
+        }
+        if !parts[3].is_empty() {
+            window.service = number(parts[3], "service time")?;
+        }
+        Ok(())
+    })?;
+    Ok(windows)
+}
+
+pub fn read_precedence_csv(filename: &str, locations: usize) -> Result<Vec<(usize, usize)>, Box<dyn Error>> {
+    // Before,After rows, each one a pair the route has to visit in that order
+    let mut precedences = Vec::new();
+    read_rows(filename, 2, |parts| {
+        precedences.push((location(parts[0], locations, "precedence")?, location(parts[1], locations, "precedence")?));
+        Ok(())
+    })?;
+    Ok(precedences)
+}
+
+pub fn read_demands_csv(filename: &str, locations: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+    // Location,Demand rows, cargo to deliver there. Locations without a row need nothing but are still visited
+    let mut demands = vec![0.0; locations];
+    read_rows(filename, 2, |parts| {
+        demands[location(parts[0], locations, "demand")?] = number(parts[1], "demand")?;
+        Ok(())
+    })?;
+    Ok(demands)
+}
+
+pub fn read_aircraft_types_csv(filename: &str, aircraft: usize, locations: usize) -> Result<Vec<crate::fleet::AircraftType>, Box<dyn Error>> {
+    // Aircraft,Multiplier,Prohibited rows, Prohibited is a ; separated list of airports ("4") and arcs ("4>7") the
+    // aircraft can't use. Aircraft without a row fly the world as it is, an empty multiplier keeps 1
+    let mut types = vec![crate::fleet::AircraftType::default(); aircraft];
+    read_rows(filename, 3, |parts| {
+        let aircraft_type = &mut types[aircraft_index(parts[0], aircraft, "aircraft type")?];
+        if !parts[1].is_empty() {
+            aircraft_type.cost_multiplier = number(parts[1], "cost multiplier")?;
+        }
+        for item in parts[2].split(';').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            if let Some((from, to)) = item.split_once('>') {
+                aircraft_type.prohibited_arcs.push((location(from.trim(), locations, "prohibited arc")?, location(to.trim(), locations, "prohibited arc")?));
+            } else {
+                aircraft_type.prohibited_nodes.push(location(item, locations, "prohibited airport")?);
+            }
+        }
+        Ok(())
+    })?;
+    Ok(types)
+}
+
+pub fn read_aircraft_costs_csv(filename: &str, types: &mut [crate::fleet::AircraftType], locations: usize) -> Result<(), Box<dyn Error>> {
+    // Aircraft,From,To,Cost rows, the From,To,Cost model with an aircraft column. An aircraft with rows flies on its own
+    // table (still scaled by its multiplier), one without keeps the world's costs
+    let aircraft = types.len();
+    read_rows(filename, 4, |parts| {
+        let a = aircraft_index(parts[0], aircraft, "cost")?;
+        let (from, to) = (location(parts[1], locations, "aircraft cost")?, location(parts[2], locations, "aircraft cost")?);
+        let cost = number(parts[3], "cost")?;
+        types[a].cost_table.get_or_insert_with(HashMap::new).entry(from).or_default().insert(to, cost);
+        Ok(())
+    })
+}
+
+pub fn read_prizes_csv(filename: &str, locations: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+    // Location,Prize rows, what visiting a location is worth to a budgeted route. Locations without a row are worth nothing
+    let mut prizes = vec![0.0; locations];
+    read_rows(filename, 2, |parts| {
+        prizes[location(parts[0], locations, "prize")?] = number(parts[1], "prize")?;
+        Ok(())
+    })?;
+    Ok(prizes)
+}
+
+pub fn read_required_csv(filename: &str, locations: usize) -> Result<Vec<bool>, Box<dyn Error>> {
+    // Location,Required rows, Required is 1/0, true/false or required/optional. Optional locations are transit points a
+    // route may fly through or leave out, locations without a row have to be visited
+    let mut required = vec![true; locations];
+    read_rows(filename, 2, |parts| {
+        let node = location(parts[0], locations, "required flag")?;
+        required[node] = match parts[1].to_ascii_lowercase().as_str() {
+            "1" | "true" | "required" => true,
+            "0" | "false" | "optional" => false,
+            flag => return Err(format!("location {} has required flag {}, expected 1/0, true/false or required/optional", node, flag).into()),
+        };
+        Ok(())
+    })?;
+    Ok(required)
+}
+
+pub fn read_clusters_csv(filename: &str, locations: usize) -> Result<Vec<usize>, Box<dyn Error>> {
+    // Location,Cluster rows, the route visits exactly one location of every cluster. Locations without a row are a
+    // cluster of their own so they have to be visited like in a plain world
+    let mut cluster_of = vec![usize::MAX; locations];
+    read_rows(filename, 2, |parts| {
+        cluster_of[location(parts[0], locations, "cluster")?] = parts[1].parse().map_err(|_| format!("cluster \"{}\" is not a cluster number", parts[1]))?;
+        Ok(())
+    })?;
+    let first_free = cluster_of.iter().copied().filter(|&id| id != usize::MAX).max().map_or(0, |id| id + 1);
+    for (next_id, id) in (first_free..).zip(cluster_of.iter_mut().filter(|id| **id == usize::MAX)) {
+        *id = next_id;
+    }
+    Ok(cluster_of)
+}
@@ -0,0 +1,166 @@
+// Capacitated routing (CVRP). Every location may have a cargo demand and every aircraft carries at most the payload
+// capacity, so the locations are split into routes that each leave the depot loaded with their deliveries and come back
+// empty. Clarke-Wright savings builds the routes, then moves inside one route and between routes improve them while
+// keeping every route within capacity. As many aircraft fly as there are routes. Sparse worlds rarely have arcs for
+// that many loops through the depot, with ferry legs the routes are planned on the metric closure instead.
+
+use std::collections::HashMap;
+use std::fmt;
+use crate::feasibility;
+use crate::fleet::{self, FleetObjective, RouteModel, MISSING_ARC_PENALTY};
+use crate::metric_closure::{self, RouteStop};
+
+const EPSILON: f64 = 1e-9;
+
+pub struct CvrpConfig {
+    pub depot: usize,
+    pub capacity: f64,      // payload of one aircraft
+    pub local_search: bool, // false keeps the savings routes as built
+    pub ferry_legs: bool,   // legs may fly through other airports, see metric_closure
+}
+
+impl Default for CvrpConfig {
+    fn default() -> Self {
+        CvrpConfig {
+            depot: 0,
+            capacity: 30.0,
+            local_search: true,
+            ferry_legs: false,
+        }
+    }
+}
+
+pub struct CargoRoute {
+    pub path: Vec<usize>,       // depot, deliveries, depot
+    pub stops: Vec<RouteStop>,  // every landing, the airports flown through on ferry legs are pass-throughs
+    pub cost: f64,              // infinity if the route needs an arc that doesn't exist
+    pub load: f64,              // cargo loaded at the depot, the sum of the demands delivered
+    pub load_profile: Vec<f64>, // cargo on board leaving each stop
+}
+
+pub struct CvrpSolution {
+    pub routes: Vec<CargoRoute>,
+    pub total_cost: f64,
+}
+
+#[derive(Debug)]
+pub enum CvrpError {
+    DepotOutOfRange { depot: usize, demands: usize, nodes: usize }, // also too few demands for the world
+    DemandTooLarge { location: usize, demand: f64, capacity: f64 },
+}
+
+impl fmt::Display for CvrpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CvrpError::DepotOutOfRange { depot, demands, nodes } =>
+                write!(f, "depot {} and {} demands don't fit a world of {} locations", depot, demands, nodes),
+            CvrpError::DemandTooLarge { location, demand, capacity } =>
+                write!(f, "location {} needs {} but an aircraft only carries {}", location, demand, capacity),
+        }
+    }
+}
+
+impl std::error::Error for CvrpError {}
+
+fn route_load(demands: &[f64], route: &[usize]) -> f64 {
+    route.iter().map(|&node| demands[node]).sum()
+}
+
+fn savings_routes(legs: &[Vec<f64>], demands: &[f64], customers: &[usize], config: &CvrpConfig) -> Vec<Vec<usize>> {
+    // parallel Clarke-Wright: every location starts on its own out and back route, then the route ending at i and the
+    // route starting at j are joined by the arc i -> j in order of the saving legs[i][depot] + legs[depot][j] - legs[i][j]
+    // as long as the joined load fits
+    let depot = config.depot;
+    let mut routes: Vec<Vec<usize>> = customers.iter().map(|&node| vec![node]).collect();
+    let mut loads: Vec<f64> = customers.iter().map(|&node| demands[node]).collect();
+    let mut route_of: HashMap<usize, usize> = customers.iter().enumerate().map(|(r, &node)| (node, r)).collect();
+    let mut savings: Vec<(f64, usize, usize)> = Vec::new();
+    for &i in customers {
+        for &j in customers {
+            if i != j && legs[i][j] < MISSING_ARC_PENALTY {
+                savings.push((legs[i][depot] + legs[depot][j] - legs[i][j], i, j));
+            }
+        }
+    }
+    savings.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (saving, i, j) in savings {
+        if saving <= EPSILON {
+            break;
+        }
+        let (a, b) = (route_of[&i], route_of[&j]);
+        if a == b || routes[a].last() != Some(&i) || routes[b].first() != Some(&j) || loads[a] + loads[b] > config.capacity + EPSILON {
+            continue;
+        }
+        let tail = std::mem::take(&mut routes[b]);
+        for &node in &tail {
+            route_of.insert(node, a);
+        }
+        routes[a].extend(tail);
+        loads[a] += loads[b];
+        loads[b] = 0.0;
+    }
+    routes.retain(|route| !route.is_empty());
+    routes
+}
+
+pub fn solve_cvrp(distances: &HashMap<usize,HashMap<usize,f64>>, demands: &[f64], config: &CvrpConfig) -> Result<CvrpSolution, CvrpError> {
+    // errors for demands no route can carry, a solution that needs arcs the world doesn't have costs infinity
+    let n = feasibility::node_count(distances);
+    if config.depot >= n || demands.len() < n {
+        return Err(CvrpError::DepotOutOfRange { depot: config.depot, demands: demands.len(), nodes: n });
+    }
+    if let Some(node) = (0..n).find(|&node| node != config.depot && demands[node] > config.capacity) {
+        return Err(CvrpError::DemandTooLarge { location: node, demand: demands[node], capacity: config.capacity });
+    }
+    let mut distance_matrix = vec![vec![f64::INFINITY; n]; n];
+    for (&from, inner_map) in distances {
+        for (&to, &cost) in inner_map {
+            if from != to {
+                distance_matrix[from][to] = cost;
+            }
+        }
+    }
+    let closure = config.ferry_legs.then(|| metric_closure::metric_closure(&distance_matrix));
+    let planning = closure.as_ref().map_or(&distance_matrix, |closure| &closure.distances);
+    let legs = fleet::penalized_legs(planning);
+    let customers: Vec<usize> = (0..n).filter(|&node| node != config.depot).collect();
+    let mut routes = savings_routes(&legs, demands, &customers, config);
+    if config.local_search {
+        // the fleet's moves with one aircraft per savings route, all flying back to the depot and none overloaded. A
+        // route emptied by the moves is one aircraft fewer
+        let model = RouteModel {
+            legs: vec![legs.as_slice(); routes.len()],
+            depots: vec![config.depot; routes.len()],
+            objective: FleetObjective::TotalCost,
+            return_to_depot: true,
+            payload: Some((demands, config.capacity)),
+        };
+        fleet::improve_routes(&model, &mut routes);
+        routes.retain(|route| !route.is_empty());
+    }
+
+    let routes: Vec<CargoRoute> = routes.into_iter().map(|route| {
+        let mut path = vec![config.depot];
+        path.extend_from_slice(&route);
+        path.push(config.depot);
+        let (stops, cost) = match &closure {
+            Some(closure) => {
+                let flown = closure.expand(&distance_matrix, &path);
+                (flown.stops, flown.cost)
+            }
+            None => (path.iter().map(|&node| RouteStop { node, pass_through: false }).collect(), crate::algorithms::path_cost(distances, &path)),
+        };
+        let load = route_load(demands, &route);
+        let mut on_board = load;
+        let last = stops.len().saturating_sub(1);
+        let load_profile = stops.iter().enumerate().map(|(k, stop)| {
+            if k > 0 && k < last && !stop.pass_through {
+                on_board -= demands[stop.node];
+            }
+            on_board
+        }).collect();
+        CargoRoute { path, stops, cost, load, load_profile }
+    }).collect();
+    let total_cost = routes.iter().map(|route| route.cost).sum();
+    Ok(CvrpSolution { routes, total_cost })
+}
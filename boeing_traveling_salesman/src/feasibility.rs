@@ -0,0 +1,233 @@
+// Quick checks on the adjacency map before a solver spends any real work on it. Everything here is a necessary
+// condition for an open path that lands at every airport once, so a report with obstructions proves there is no
+// such path, while a clean report only means none of these cheap tests found a reason.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Obstruction {
+    IsolatedNode(usize),          // no arcs in or out
+    TooManySources(Vec<usize>),   // only the first airport may have no way in
+    TooManySinks(Vec<usize>),     // only the last airport may have no way out
+    BrokenChain { from: usize, to: usize }, // consecutive strongly connected components with no arc between them
+    ConflictingForcedArcs { node: usize, arcs: Vec<(usize, usize)> }, // two forced arcs leave or enter the same node
+    ForcedCycle(Vec<usize>),      // forced arcs close a loop
+}
+
+impl fmt::Display for Obstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Obstruction::IsolatedNode(node) => write!(f, "node {} has no arcs in or out", node),
+            Obstruction::TooManySources(nodes) => write!(f, "nodes {:?} have no incoming arcs but only one can start the path", nodes),
+            Obstruction::TooManySinks(nodes) => write!(f, "nodes {:?} have no outgoing arcs but only one can end the path", nodes),
+            Obstruction::BrokenChain { from, to } => write!(f, "no arc from strongly connected component {} to component {}, the components don't form a chain", from, to),
+            Obstruction::ConflictingForcedArcs { node, arcs } => write!(f, "node {} needs all of the forced arcs {:?}", node, arcs),
+            Obstruction::ForcedCycle(nodes) => write!(f, "forced arcs close the loop {:?}", nodes),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeasibilityReport {
+    pub nodes: usize,
+    pub zero_in_degree: Vec<usize>,
+    pub zero_out_degree: Vec<usize>,
+    pub components: Vec<Vec<usize>>, // strongly connected components in topological order of the condensation DAG
+    pub forced_arcs: Vec<(usize, usize)>, // arcs every path that visits everything has to use
+    pub obstructions: Vec<Obstruction>,
+}
+
+impl FeasibilityReport {
+    pub fn is_feasible(&self) -> bool { // no obstruction found, not a proof that a path exists
+        self.obstructions.is_empty()
+    }
+}
+
+impl fmt::Display for FeasibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_feasible() {
+            return write!(f, "no obstruction found ({} nodes, {} strongly connected components, {} forced arcs)",
+                self.nodes, self.components.len(), self.forced_arcs.len());
+        }
+        write!(f, "no path can visit all {} nodes: ", self.nodes)?;
+        for (k, obstruction) in self.obstructions.iter().enumerate() {
+            if k > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", obstruction)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn node_count(distances: &HashMap<usize,HashMap<usize,f64>>) -> usize { // nodes with no outgoing arcs are not keys of the map
+    distances.iter().flat_map(|(&from, inner_map)| std::iter::once(from).chain(inner_map.keys().copied())).max().map_or(0, |largest| largest + 1)
+}
+
+fn strongly_connected_components(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    // iterative Tarjan, components come out in reverse topological order
+    let n = successors.len();
+    let mut index = vec![usize::MAX; n];
+    let mut low_link = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        let mut call_stack = vec![(root, 0)]; // (node, next successor to look at)
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some(&mut (node, ref mut position)) = call_stack.last_mut() {
+            if let Some(&next) = successors[node].get(*position) {
+                *position += 1;
+                if index[next] == usize::MAX {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    call_stack.push((next, 0));
+                } else if on_stack[next] {
+                    low_link[node] = low_link[node].min(index[next]);
+                }
+                continue;
+            }
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if low_link[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+fn push_unique(obstructions: &mut Vec<Obstruction>, obstruction: Obstruction) {
+    if !obstructions.contains(&obstruction) {
+        obstructions.push(obstruction);
+    }
+}
+
+pub fn check(distances: &HashMap<usize,HashMap<usize,f64>>) -> FeasibilityReport {
+    let n = node_count(distances);
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (&from, inner_map) in distances {
+        for (&to, cost) in inner_map {
+            if from != to && cost.is_finite() {
+                successors[from].push(to);
+                predecessors[to].push(from);
+            }
+        }
+    }
+    for list in successors.iter_mut().chain(predecessors.iter_mut()) {
+        list.sort_unstable(); // HashMap order would make the report change between runs
+    }
+    let zero_in_degree: Vec<usize> = (0..n).filter(|&node| predecessors[node].is_empty()).collect();
+    let zero_out_degree: Vec<usize> = (0..n).filter(|&node| successors[node].is_empty()).collect();
+    let mut obstructions = Vec::new();
+    if n > 1 {
+        obstructions.extend((0..n).filter(|&node| predecessors[node].is_empty() && successors[node].is_empty()).map(Obstruction::IsolatedNode));
+    }
+    if zero_in_degree.len() > 1 {
+        obstructions.push(Obstruction::TooManySources(zero_in_degree.clone()));
+    }
+    if zero_out_degree.len() > 1 {
+        obstructions.push(Obstruction::TooManySinks(zero_out_degree.clone()));
+    }
+
+    // the path enters every component once and never comes back, so the condensation has to be a single chain
+    let mut components = strongly_connected_components(&successors);
+    components.reverse();
+    let mut component_of = vec![0; n];
+    for (k, component) in components.iter().enumerate() {
+        for &node in component {
+            component_of[node] = k;
+        }
+    }
+    let mut forced = BTreeSet::new();
+    for k in 1..components.len() {
+        let bridges: Vec<(usize, usize)> = components[k - 1].iter()
+            .flat_map(|&from| successors[from].iter().filter(|&&to| component_of[to] == k).map(move |&to| (from, to)))
+            .collect();
+        match bridges.len() {
+            0 => obstructions.push(Obstruction::BrokenChain { from: k - 1, to: k }),
+            1 => {
+                forced.insert(bridges[0]);
+            }
+            _ => {}
+        }
+    }
+    // once the start is known (the only source) every other node with one way in has to use it, same for the end
+    if zero_in_degree.len() == 1 {
+        forced.extend((0..n).filter(|&node| predecessors[node].len() == 1).map(|node| (predecessors[node][0], node)));
+    }
+    if zero_out_degree.len() == 1 {
+        forced.extend((0..n).filter(|&node| successors[node].len() == 1).map(|node| (node, successors[node][0])));
+    }
+    let forced_arcs: Vec<(usize, usize)> = forced.into_iter().collect();
+
+    // forced arcs have to form disjoint simple paths
+    let mut forced_next: Vec<Option<usize>> = vec![None; n];
+    let mut forced_previous: Vec<Option<usize>> = vec![None; n];
+    for &(from, to) in &forced_arcs {
+        if forced_next[from].is_some_and(|other| other != to) {
+            let arcs = forced_arcs.iter().copied().filter(|&(a, _)| a == from).collect();
+            push_unique(&mut obstructions, Obstruction::ConflictingForcedArcs { node: from, arcs });
+        }
+        forced_next[from] = Some(to);
+        if forced_previous[to].is_some_and(|other| other != from) {
+            let arcs = forced_arcs.iter().copied().filter(|&(_, b)| b == to).collect();
+            push_unique(&mut obstructions, Obstruction::ConflictingForcedArcs { node: to, arcs });
+        }
+        forced_previous[to] = Some(from);
+    }
+    let mut seen = vec![false; n];
+    for start in 0..n {
+        if seen[start] {
+            continue;
+        }
+        let mut walk = vec![start];
+        let mut node = start;
+        seen[start] = true;
+        while let Some(next) = forced_next[node] {
+            if next == start {
+                obstructions.push(Obstruction::ForcedCycle(walk.clone()));
+                break;
+            }
+            if seen[next] {
+                break;
+            }
+            seen[next] = true;
+            walk.push(next);
+            node = next;
+        }
+    }
+
+    FeasibilityReport { nodes: n, zero_in_degree, zero_out_degree, components, forced_arcs, obstructions }
+}
+
+pub fn check_matrix(distance_matrix: &[Vec<f64>]) -> FeasibilityReport { // same checks for a distance matrix, infinity is a missing arc
+    let distances: HashMap<usize,HashMap<usize,f64>> = distance_matrix.iter().enumerate()
+        .map(|(from, row)| (from, row.iter().enumerate().filter(|&(to, cost)| to != from && cost.is_finite()).map(|(to, &cost)| (to, cost)).collect()))
+        .collect();
+    check(&distances)
+}
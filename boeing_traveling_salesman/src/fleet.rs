@@ -0,0 +1,497 @@
+// Multiple aircraft routing (mTSP). The locations are split across a fleet of k aircraft that start at one shared depot
+// or at a depot each, minimizing either the total cost or the longest single route (min-max).
+// Route first, cluster second: one path over every location that isn't a depot is built with the single aircraft
+// solvers, an exact DP cuts it into k consecutive pieces, then moves inside and between routes improve the plan.
+// Aircraft can differ: each prices the arcs its own way and may be barred from some airports and arcs, every step works
+// on the aircraft's own legs so locations end up on aircraft that can reach them.
+
+use std::collections::HashMap;
+use std::fmt;
+use crate::algorithms;
+use crate::feasibility;
+
+pub(crate) const MISSING_ARC_PENALTY: f64 = 1e6; // lets the local search repair routes that use arcs the world doesn't have
+const EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Depots {
+    Shared(usize),        // every aircraft starts at this location
+    Separate(Vec<usize>), // aircraft a starts at depots[a]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FleetObjective {
+    TotalCost,
+    MinMax, // longest single route, ties broken by total cost
+}
+
+#[derive(Clone)]
+pub struct AircraftType {
+    pub cost_multiplier: f64, // scales every arc of the world (or of the own cost table)
+    pub cost_table: Option<HashMap<usize,HashMap<usize,f64>>>, // From,To,Cost for this airframe instead of the world's
+    pub prohibited_nodes: Vec<usize>,
+    pub prohibited_arcs: Vec<(usize, usize)>,
+}
+
+impl Default for AircraftType {
+    fn default() -> Self {
+        AircraftType {
+            cost_multiplier: 1.0,
+            cost_table: None,
+            prohibited_nodes: Vec::new(),
+            prohibited_arcs: Vec::new(),
+        }
+    }
+}
+
+pub struct FleetConfig {
+    pub aircraft: usize,
+    pub depots: Depots,
+    pub objective: FleetObjective,
+    pub return_to_depot: bool, // routes end back at their depot instead of at their last location
+    pub aircraft_types: Vec<AircraftType>, // one per aircraft, empty flies every aircraft on the world as it is
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        FleetConfig {
+            aircraft: 2,
+            depots: Depots::Shared(0),
+            objective: FleetObjective::TotalCost,
+            return_to_depot: false,
+            aircraft_types: Vec::new(),
+        }
+    }
+}
+
+pub struct AircraftRoute {
+    pub depot: usize,
+    pub path: Vec<usize>, // starts at the depot (and ends there when returning), just the depot if the aircraft stays home
+    pub cost: f64,        // infinity if the route needs an arc that doesn't exist
+}
+
+pub struct FleetPlan {
+    pub routes: Vec<AircraftRoute>, // one per aircraft in config order
+    pub total_cost: f64,
+    pub max_route_cost: f64,
+}
+
+#[derive(Debug)]
+pub enum FleetError {
+    DepotsOutOfRange { aircraft: usize, depots: Vec<usize>, nodes: usize }, // no aircraft, a depot per aircraft missing or past the world
+    AircraftTypeCount { types: usize, aircraft: usize },
+    DepotProhibited { aircraft: usize, depot: usize },
+}
+
+impl fmt::Display for FleetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FleetError::DepotsOutOfRange { aircraft, depots, nodes } =>
+                write!(f, "{} aircraft can't start from depots {:?} in a world of {} locations", aircraft, depots, nodes),
+            FleetError::AircraftTypeCount { types, aircraft } => write!(f, "{} aircraft types for {} aircraft", types, aircraft),
+            FleetError::DepotProhibited { aircraft, depot } => write!(f, "aircraft {} is prohibited from its own depot {}", aircraft, depot),
+        }
+    }
+}
+
+impl std::error::Error for FleetError {}
+
+type Legs = [Vec<Vec<f64>>]; // legs[a][from][to], aircraft a's cost with missing and prohibited arcs penalized
+
+pub(crate) struct RouteModel<'a> { // what the route improvement needs to know about the fleet, shared with the cargo routes
+    pub legs: Vec<&'a [Vec<f64>]>, // one penalized matrix per aircraft, see penalized_legs
+    pub depots: Vec<usize>,
+    pub objective: FleetObjective,
+    pub return_to_depot: bool,
+    pub payload: Option<(&'a [f64], f64)>, // demand of every location and the capacity of one aircraft
+}
+
+impl RouteModel<'_> {
+    fn route_cost(&self, a: usize, route: &[usize]) -> f64 { // aircraft a flying route
+        route_cost(self.legs[a], self.depots[a], route, self.return_to_depot)
+    }
+}
+
+pub(crate) fn penalized_legs(matrix: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    matrix.iter().map(|row| row.iter().map(|&cost| if cost.is_finite() { cost } else { MISSING_ARC_PENALTY }).collect()).collect()
+}
+
+pub(crate) fn route_cost(legs: &[Vec<f64>], depot: usize, route: &[usize], return_to_depot: bool) -> f64 {
+    // route holds only the locations visited, the legs from and back to the depot are added here
+    let (Some(&first), Some(&last)) = (route.first(), route.last()) else {
+        return 0.0;
+    };
+    let back = if return_to_depot { legs[last][depot] } else { 0.0 };
+    legs[depot][first] + route.windows(2).map(|arc| legs[arc[0]][arc[1]]).sum::<f64>() + back
+}
+
+fn objective_key(costs: &[f64], objective: FleetObjective) -> (f64, f64) {
+    let total: f64 = costs.iter().sum();
+    let longest = costs.iter().fold(0.0, |a: f64, &b| a.max(b));
+    match objective {
+        FleetObjective::TotalCost => (total, longest),
+        FleetObjective::MinMax => (longest, total),
+    }
+}
+
+fn better(candidate: (f64, f64), current: (f64, f64)) -> bool { // lexicographic with noise tolerance
+    candidate.0 < current.0 - EPSILON || (candidate.0 <= current.0 + EPSILON && candidate.1 < current.1 - EPSILON)
+}
+
+fn giant_tour(distances: &HashMap<usize,HashMap<usize,f64>>, customers: &[usize]) -> Vec<usize> {
+    // single aircraft path over the locations that aren't depots, found on the relabeled sub-world
+    let mut index = HashMap::new();
+    for (k, &node) in customers.iter().enumerate() {
+        index.insert(node, k);
+    }
+    let mut sub_world: HashMap<usize,HashMap<usize,f64>> = HashMap::new();
+    for (&from, inner_map) in distances {
+        if let Some(&sub_from) = index.get(&from) {
+            let arcs = inner_map.iter().filter_map(|(to, &cost)| index.get(to).map(|&sub_to| (sub_to, cost))).collect();
+            sub_world.insert(sub_from, arcs);
+        }
+    }
+    let (cost, path) = algorithms::nearest_neighbor_sparse(&sub_world);
+    if cost.is_infinite() || path.len() != customers.len() {
+        return customers.to_vec(); // no single path, the split and the local search work with the penalized arcs
+    }
+    let (_, path) = algorithms::local_search(&sub_world, &path);
+    path.iter().map(|&k| customers[k]).collect()
+}
+
+fn split_tour(legs: &Legs, depots: &[usize], tour: &[usize], config: &FleetConfig) -> Vec<Vec<usize>> {
+    // best cut of the tour into consecutive pieces, aircraft a flies the a-th piece. best[a][j] is the objective key
+    // for the first a aircraft covering tour[..j], empty pieces are allowed
+    let (k, m) = (depots.len(), tour.len());
+    let prefix: Vec<Vec<f64>> = legs.iter().map(|legs| { // prefix[a][t] = cost of tour[0..=t] walked in order by aircraft a
+        let mut prefix = vec![0.0; m + 1];
+        for t in 1..m {
+            prefix[t] = prefix[t - 1] + legs[tour[t - 1]][tour[t]];
+        }
+        prefix
+    }).collect();
+    let piece_cost = |a: usize, i: usize, j: usize| { // tour[i..j] flown by aircraft a
+        if i == j {
+            return 0.0;
+        }
+        let back = if config.return_to_depot { legs[a][tour[j - 1]][depots[a]] } else { 0.0 };
+        legs[a][depots[a]][tour[i]] + prefix[a][j - 1] - prefix[a][i] + back
+    };
+    let combine = |key: (f64, f64), cost: f64| match config.objective {
+        FleetObjective::TotalCost => (key.0 + cost, key.1.max(cost)),
+        FleetObjective::MinMax => (key.0.max(cost), key.1 + cost),
+    };
+    let mut best = vec![vec![(f64::INFINITY, f64::INFINITY); m + 1]; k + 1];
+    let mut cut = vec![vec![0; m + 1]; k + 1];
+    best[0][0] = (0.0, 0.0);
+    for a in 0..k {
+        for j in 0..=m {
+            for i in 0..=j {
+                if best[a][i].0.is_infinite() {
+                    continue;
+                }
+                let candidate = combine(best[a][i], piece_cost(a, i, j));
+                if better(candidate, best[a + 1][j]) {
+                    best[a + 1][j] = candidate;
+                    cut[a + 1][j] = i;
+                }
+            }
+        }
+    }
+    let mut routes = vec![Vec::new(); k];
+    let mut end = m;
+    for a in (0..k).rev() {
+        let start = cut[a + 1][end];
+        routes[a] = tour[start..end].to_vec();
+        end = start;
+    }
+    routes
+}
+
+type Insertion = ((f64, f64), usize, usize, usize, f64); // objective key, remaining index, route, position, new route cost
+
+fn insertion_routes(legs: &Legs, depots: &[usize], customers: &[usize], config: &FleetConfig) -> Vec<Vec<usize>> {
+    // cheapest insertion, every step puts the location whose best spot hurts the objective least. A second start for
+    // sparse worlds where the single path doesn't exist or can't be cut into routes with real arcs
+    let mut routes = vec![Vec::new(); depots.len()];
+    let mut costs = vec![0.0; depots.len()];
+    let mut remaining = customers.to_vec();
+    while !remaining.is_empty() {
+        let mut best: Option<Insertion> = None;
+        for (r, &node) in remaining.iter().enumerate() {
+            for (a, route) in routes.iter().enumerate() {
+                for j in 0..=route.len() {
+                    let mut with: Vec<usize> = route.clone();
+                    with.insert(j, node);
+                    let cost = route_cost(&legs[a], depots[a], &with, config.return_to_depot);
+                    let mut new_costs = costs.clone();
+                    new_costs[a] = cost;
+                    let key = objective_key(&new_costs, config.objective);
+                    if best.is_none_or(|(best_key, ..)| better(key, best_key)) {
+                        best = Some((key, r, a, j, cost));
+                    }
+                }
+            }
+        }
+        let (_, r, a, j, cost) = best.unwrap();
+        routes[a].insert(j, remaining.swap_remove(r));
+        costs[a] = cost;
+    }
+    routes
+}
+
+fn key_with(costs: &[f64], changed: &[(usize, f64)], objective: FleetObjective) -> (f64, f64) {
+    // objective key with some route costs replaced, without copying the costs
+    let cost = |a: usize| changed.iter().rev().find(|&&(b, _)| b == a).map_or(costs[a], |&(_, cost)| cost);
+    let total: f64 = (0..costs.len()).map(cost).sum();
+    let longest = (0..costs.len()).map(cost).fold(0.0, f64::max);
+    match objective {
+        FleetObjective::TotalCost => (total, longest),
+        FleetObjective::MinMax => (longest, total),
+    }
+}
+
+fn apply_if_better<F>(model: &RouteModel, routes: &mut [Vec<usize>], current: (f64, f64), touched: &[usize], apply: F) -> bool
+where
+    F: FnOnce(&mut [Vec<usize>]),
+{
+    // the delta pricing only says a move looks better, the penalties make the sums large enough for rounding to matter.
+    // The move is kept if the touched routes priced in full still beat current, so the search can't cycle on noise
+    let saved: Vec<Vec<usize>> = touched.iter().map(|&a| routes[a].clone()).collect();
+    apply(routes);
+    let costs: Vec<f64> = routes.iter().enumerate().map(|(a, route)| model.route_cost(a, route)).collect();
+    if better(objective_key(&costs, model.objective), current) {
+        return true;
+    }
+    for (&a, route) in touched.iter().zip(saved) {
+        routes[a] = route;
+    }
+    false
+}
+
+pub(crate) fn improve_routes(model: &RouteModel, routes: &mut [Vec<usize>]) {
+    // first improvement over relocating a location (same or other route), swapping two locations between routes,
+    // handing two aircraft each other's whole route and reversing a piece of one route, until no move helps the objective.
+    // A location relocated to another route may also take that route over to its own aircraft, the other aircraft
+    // flying what is left, for spots only one of the airframes can reach.
+    // Every move is priced in O(1) from the arcs it changes, what each aircraft would pay for each route and prefix sums
+    // for reversals (the legs are directed). Only a move that looks better is priced in full, and an applied one
+    // rebuilds the tables in O(k^2 n) before the scan starts over. With a payload no move overloads a route
+    let k = routes.len();
+    let demand = |node: usize| model.payload.map_or(0.0, |(demands, _)| demands[node]);
+    let fits = |load: f64| model.payload.is_none_or(|(_, capacity)| load <= capacity + EPSILON);
+    let end = |a: usize| if model.return_to_depot { Some(model.depots[a]) } else { None };
+    let link = |a: usize, from: usize, to: Option<usize>| match to {
+        Some(to) if !(from == model.depots[a] && to == model.depots[a]) => model.legs[a][from][to],
+        _ => 0.0, // open end of the route, or depot to depot for a route that is empty
+    };
+    let before = |a: usize, route: &[usize], i: usize| if i == 0 { model.depots[a] } else { route[i - 1] };
+    // cost change for aircraft a flying route without route[i], with node put in before route[j] and with route[i] replaced by node
+    let removal = |a: usize, route: &[usize], i: usize| {
+        let (previous, next) = (before(a, route, i), route.get(i + 1).copied().or(end(a)));
+        link(a, previous, next) - link(a, previous, Some(route[i])) - link(a, route[i], next)
+    };
+    let insertion = |a: usize, route: &[usize], j: usize, node: usize| {
+        let (previous, next) = (before(a, route, j), route.get(j).copied().or(end(a)));
+        link(a, previous, Some(node)) + link(a, node, next) - link(a, previous, next)
+    };
+    let replacement = |a: usize, route: &[usize], i: usize, node: usize| {
+        let (previous, next) = (before(a, route, i), route.get(i + 1).copied().or(end(a)));
+        link(a, previous, Some(node)) + link(a, node, next) - link(a, previous, Some(route[i])) - link(a, route[i], next)
+    };
+    'search: loop {
+        // flown[a][b] = aircraft a flying route b, forward[a][t] and backward[a][t] = route a up to t walked either way
+        let flown: Vec<Vec<f64>> = (0..k).map(|a| routes.iter().map(|route| model.route_cost(a, route)).collect()).collect();
+        let costs: Vec<f64> = (0..k).map(|a| flown[a][a]).collect();
+        let loads: Vec<f64> = routes.iter().map(|route| route.iter().map(|&node| demand(node)).sum()).collect();
+        let (mut forward, mut backward) = (Vec::with_capacity(k), Vec::with_capacity(k));
+        for (a, route) in routes.iter().enumerate() {
+            let (mut ahead, mut back) = (vec![0.0; route.len()], vec![0.0; route.len()]);
+            for t in 1..route.len() {
+                ahead[t] = ahead[t - 1] + model.legs[a][route[t - 1]][route[t]];
+                back[t] = back[t - 1] + model.legs[a][route[t]][route[t - 1]];
+            }
+            forward.push(ahead);
+            backward.push(back);
+        }
+        let current = objective_key(&costs, model.objective);
+        for a in 0..k {
+            for i in 0..routes[a].len() {
+                let node = routes[a][i];
+                let cost_without = costs[a] + removal(a, &routes[a], i);
+                for b in 0..k {
+                    if b != a && !fits(loads[b] + demand(node)) {
+                        continue;
+                    }
+                    for j in 0..=routes[b].len() {
+                        if b == a {
+                            // gap j of the route without node, j == i puts it back where it was
+                            if j == i || j == routes[a].len() {
+                                continue;
+                            }
+                            let (previous, next) = if j < i {
+                                (before(a, &routes[a], j), Some(routes[a][j]))
+                            } else {
+                                (routes[a][j], routes[a].get(j + 1).copied().or(end(a)))
+                            };
+                            let cost = cost_without + link(a, previous, Some(node)) + link(a, node, next) - link(a, previous, next);
+                            if better(key_with(&costs, &[(a, cost)], model.objective), current)
+                                && apply_if_better(model, routes, current, &[a], |routes| {
+                                    let node = routes[a].remove(i);
+                                    routes[a].insert(j, node);
+                                }) {
+                                continue 'search;
+                            }
+                            continue;
+                        }
+                        let changed = [(a, cost_without), (b, costs[b] + insertion(b, &routes[b], j, node))];
+                        if better(key_with(&costs, &changed, model.objective), current)
+                            && apply_if_better(model, routes, current, &[a, b], |routes| {
+                                routes[a].remove(i);
+                                routes[b].insert(j, node);
+                            }) {
+                            continue 'search;
+                        }
+                        let taken_over = [(a, flown[a][b] + insertion(a, &routes[b], j, node)), (b, flown[b][a] + removal(b, &routes[a], i))];
+                        if better(key_with(&costs, &taken_over, model.objective), current)
+                            && apply_if_better(model, routes, current, &[a, b], |routes| {
+                                routes[a].remove(i);
+                                routes[b].insert(j, node);
+                                routes.swap(a, b);
+                            }) {
+                            continue 'search;
+                        }
+                    }
+                }
+            }
+        }
+        for a in 0..k {
+            for b in a + 1..k {
+                for i in 0..routes[a].len() {
+                    for j in 0..routes[b].len() {
+                        let shift = demand(routes[b][j]) - demand(routes[a][i]);
+                        if !fits(loads[a] + shift) || !fits(loads[b] - shift) {
+                            continue;
+                        }
+                        let changed = [(a, costs[a] + replacement(a, &routes[a], i, routes[b][j])), (b, costs[b] + replacement(b, &routes[b], j, routes[a][i]))];
+                        if better(key_with(&costs, &changed, model.objective), current)
+                            && apply_if_better(model, routes, current, &[a, b], |routes| {
+                                let (first, second) = routes.split_at_mut(b);
+                                std::mem::swap(&mut first[a][i], &mut second[0][j]);
+                            }) {
+                            continue 'search;
+                        }
+                    }
+                }
+            }
+        }
+        for (a, b) in (0..k).flat_map(|a| (a + 1..k).map(move |b| (a, b))) { // only changes anything when the aircraft or their depots differ
+            if better(key_with(&costs, &[(a, flown[a][b]), (b, flown[b][a])], model.objective), current)
+                && apply_if_better(model, routes, current, &[a, b], |routes| routes.swap(a, b)) {
+                continue 'search;
+            }
+        }
+        for a in 0..k {
+            for i in 0..routes[a].len() {
+                for j in i + 1..routes[a].len() {
+                    // route[i..=j] flown backwards, the inside arcs turn around and the ends swap their neighbors
+                    let route = &routes[a];
+                    let (previous, next) = (before(a, route, i), route.get(j + 1).copied().or(end(a)));
+                    let cost = costs[a] - forward[a][j] + forward[a][i] + backward[a][j] - backward[a][i]
+                        - link(a, previous, Some(route[i])) - link(a, route[j], next) + link(a, previous, Some(route[j])) + link(a, route[i], next);
+                    if better(key_with(&costs, &[(a, cost)], model.objective), current)
+                        && apply_if_better(model, routes, current, &[a], |routes| routes[a][i..=j].reverse()) {
+                        continue 'search;
+                    }
+                }
+            }
+        }
+        return;
+    }
+}
+
+pub fn aircraft_matrices(distances: &HashMap<usize,HashMap<usize,f64>>, config: &FleetConfig) -> Vec<Vec<Vec<f64>>> {
+    // what every aircraft pays per arc: its own table or the world, times its multiplier, infinity where the arc is
+    // missing, prohibited or touches a prohibited airport
+    let n = feasibility::node_count(distances);
+    let same_as_world = AircraftType::default();
+    (0..config.aircraft).map(|a| {
+        let aircraft_type = config.aircraft_types.get(a).unwrap_or(&same_as_world);
+        let table = aircraft_type.cost_table.as_ref().unwrap_or(distances);
+        let mut matrix = vec![vec![f64::INFINITY; n]; n];
+        for (&from, inner_map) in table {
+            for (&to, &cost) in inner_map {
+                if from != to && from < n && to < n {
+                    matrix[from][to] = cost * aircraft_type.cost_multiplier;
+                }
+            }
+        }
+        for &(from, to) in aircraft_type.prohibited_arcs.iter().filter(|&&(from, to)| from < n && to < n) {
+            matrix[from][to] = f64::INFINITY;
+        }
+        for &node in aircraft_type.prohibited_nodes.iter().filter(|&&node| node < n) {
+            matrix[node].fill(f64::INFINITY);
+            for row in matrix.iter_mut() {
+                row[node] = f64::INFINITY;
+            }
+        }
+        matrix
+    }).collect()
+}
+
+pub fn plan_fleet(distances: &HashMap<usize,HashMap<usize,f64>>, config: &FleetConfig) -> Result<FleetPlan, FleetError> {
+    // errors for a fleet that can't be set up, a plan that needs arcs the world doesn't have costs infinity
+    let n = feasibility::node_count(distances);
+    let depots: Vec<usize> = match &config.depots {
+        Depots::Shared(depot) => vec![*depot; config.aircraft],
+        Depots::Separate(depots) => depots.clone(),
+    };
+    if config.aircraft == 0 || depots.len() != config.aircraft || depots.iter().any(|&depot| depot >= n) {
+        return Err(FleetError::DepotsOutOfRange { aircraft: config.aircraft, depots, nodes: n });
+    }
+    if !config.aircraft_types.is_empty() && config.aircraft_types.len() != config.aircraft {
+        return Err(FleetError::AircraftTypeCount { types: config.aircraft_types.len(), aircraft: config.aircraft });
+    }
+    if let Some(a) = (0..config.aircraft_types.len()).find(|&a| config.aircraft_types[a].prohibited_nodes.contains(&depots[a])) {
+        return Err(FleetError::DepotProhibited { aircraft: a, depot: depots[a] });
+    }
+    let matrices = aircraft_matrices(distances, config);
+    let legs: Vec<Vec<Vec<f64>>> = matrices.iter().map(|matrix| penalized_legs(matrix)).collect();
+    let mut is_depot = vec![false; n];
+    for &depot in &depots {
+        is_depot[depot] = true;
+    }
+    let customers: Vec<usize> = (0..n).filter(|&node| !is_depot[node]).collect();
+    let tour = giant_tour(distances, &customers);
+    let model = RouteModel {
+        legs: legs.iter().map(|legs| legs.as_slice()).collect(),
+        depots: depots.clone(),
+        objective: config.objective,
+        return_to_depot: config.return_to_depot,
+        payload: None,
+    };
+    let mut routes = split_tour(&legs, &depots, &tour, config);
+    improve_routes(&model, &mut routes);
+    let mut inserted = insertion_routes(&legs, &depots, &customers, config);
+    improve_routes(&model, &mut inserted);
+    let key_of = |routes: &[Vec<usize>]| {
+        let costs: Vec<f64> = routes.iter().enumerate().map(|(a, route)| route_cost(&legs[a], depots[a], route, config.return_to_depot)).collect();
+        objective_key(&costs, config.objective)
+    };
+    if better(key_of(&inserted), key_of(&routes)) {
+        routes = inserted;
+    }
+
+    let routes: Vec<AircraftRoute> = routes.into_iter().zip(&depots).zip(&matrices).map(|((route, &depot), matrix)| {
+        let mut path = vec![depot];
+        path.extend_from_slice(&route);
+        if config.return_to_depot && !route.is_empty() {
+            path.push(depot);
+        }
+        let cost = if route.is_empty() { 0.0 } else { path.windows(2).map(|arc| matrix[arc[0]][arc[1]]).sum() };
+        AircraftRoute { depot, cost, path }
+    }).collect();
+    let total_cost = routes.iter().map(|route| route.cost).sum();
+    let max_route_cost = routes.iter().fold(0.0, |a: f64, route| a.max(route.cost));
+    Ok(FleetPlan { routes, total_cost, max_route_cost })
+}
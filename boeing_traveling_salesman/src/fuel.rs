@@ -0,0 +1,277 @@
+// Fuel range limits. An aircraft can't fly a leg costing more than its max leg cost, and can fly at most its endurance
+// between refuels. It starts full and refills at refuel capable airports, both where it visits them and where it lands
+// only to take on fuel (a refuel stop, like a ferry leg pass-through the airport may be visited somewhere else too).
+// Between two visits the aircraft either flies the arc directly or hops through a chain of refuel airports. Fuel left
+// and cost pull in different directions, so the DPs keep every label (cost, fuel) that no other label beats on both.
+
+use crate::algorithms::HeldKarpError;
+
+const FUEL_HELD_KARP_MAX_NODES: usize = 16; // one label list per (mask, last) state
+const EPSILON: f64 = 1e-9;
+
+pub struct FuelLimits {
+    pub max_leg_cost: f64,
+    pub endurance: f64,          // cost that can be flown between refuels, the tank holds this much
+    pub refuel_nodes: Vec<usize>, // airports that sell fuel
+}
+
+pub struct FuelStop {
+    pub node: usize,
+    pub refuel_stop: bool, // landed only for fuel, not one of the planned visits
+    pub fuel: f64,         // fuel left after landing (and refueling)
+}
+
+pub struct FuelRoute {
+    pub cost: f64,
+    pub visit_order: Vec<usize>,
+    pub stops: Vec<FuelStop>, // every landing in order, the planned visits and the refuel stops
+}
+
+struct Detour { // the cheapest ways from one location to another through refuel airports, per last refuel airport
+    total: f64,
+    first_hop: f64,    // fuel needed to reach the first refuel airport
+    arrival_fuel: f64, // fuel left landing at the destination
+    first: usize,
+    last: usize,
+}
+
+struct FuelNetwork<'a> {
+    matrix: &'a [Vec<f64>],
+    limits: &'a FuelLimits,
+    can_refuel: Vec<bool>,
+    hop_next: Vec<Vec<usize>>, // next refuel airport on the cheapest refuel-only chain between two refuel airports
+    detours: Vec<Vec<Vec<Detour>>>,
+}
+
+type RefuelDetour = Option<(usize, usize)>; // first and last refuel stop flown through before a visit
+
+#[derive(Clone, Copy)]
+struct Label {
+    cost: f64,
+    fuel: f64,
+    previous: usize,       // label index in the previous state, usize::MAX at the start
+    previous_node: usize,
+    detour: RefuelDetour,
+}
+
+fn push_label(labels: &mut Vec<Label>, label: Label) { // keeps only labels no other label beats on both cost and fuel
+    if labels.iter().any(|other| other.cost <= label.cost + EPSILON && other.fuel >= label.fuel - EPSILON) {
+        return;
+    }
+    labels.retain(|other| !(label.cost <= other.cost && label.fuel >= other.fuel));
+    labels.push(label);
+}
+
+impl<'a> FuelNetwork<'a> {
+    fn new(matrix: &'a [Vec<f64>], limits: &'a FuelLimits) -> Self {
+        let n = matrix.len();
+        let mut can_refuel = vec![false; n];
+        for &node in limits.refuel_nodes.iter().filter(|&&node| node < n) {
+            can_refuel[node] = true;
+        }
+        let refuel: Vec<usize> = (0..n).filter(|&node| can_refuel[node]).collect();
+        let reach = limits.max_leg_cost.min(limits.endurance); // longest leg flown on a full tank
+
+        // Floyd-Warshall on the refuel airports, every hop starts with a full tank
+        let mut hop_cost = vec![vec![f64::INFINITY; n]; n];
+        let mut hop_next = vec![vec![usize::MAX; n]; n];
+        for &from in &refuel {
+            hop_cost[from][from] = 0.0;
+            hop_next[from][from] = from;
+            for &to in &refuel {
+                if from != to && matrix[from][to] <= reach {
+                    hop_cost[from][to] = matrix[from][to];
+                    hop_next[from][to] = to;
+                }
+            }
+        }
+        for &via in &refuel {
+            for &from in &refuel {
+                for &to in &refuel {
+                    if hop_cost[from][via] + hop_cost[via][to] < hop_cost[from][to] {
+                        hop_cost[from][to] = hop_cost[from][via] + hop_cost[via][to];
+                        hop_next[from][to] = hop_next[from][via];
+                    }
+                }
+            }
+        }
+
+        let mut detours: Vec<Vec<Vec<Detour>>> = (0..n).map(|_| (0..n).map(|_| Vec::new()).collect()).collect();
+        for from in 0..n {
+            for to in 0..n {
+                if from == to {
+                    continue;
+                }
+                let mut options: Vec<Detour> = Vec::new();
+                for &last in refuel.iter().filter(|&&last| last != from && last != to && matrix[last][to] <= reach) {
+                    let arrival_fuel = if can_refuel[to] { limits.endurance } else { limits.endurance - matrix[last][to] };
+                    let mut candidates: Vec<(f64, f64, usize)> = refuel.iter()
+                        .filter(|&&first| first != from && first != to && matrix[from][first] <= limits.max_leg_cost && hop_cost[first][last].is_finite())
+                        .map(|&first| (matrix[from][first], matrix[from][first] + hop_cost[first][last] + matrix[last][to], first))
+                        .collect();
+                    candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+                    let mut cheapest = f64::INFINITY;
+                    for (first_hop, total, first) in candidates { // cheaper first hops only help if the total is lower too
+                        if total < cheapest - EPSILON {
+                            cheapest = total;
+                            options.push(Detour { total, first_hop, arrival_fuel, first, last });
+                        }
+                    }
+                }
+                detours[from][to] = options;
+            }
+        }
+        FuelNetwork { matrix, limits, can_refuel, hop_next, detours }
+    }
+
+    fn leg_options(&self, from: usize, fuel: f64, to: usize) -> Vec<(f64, f64, RefuelDetour)> {
+        // (cost, fuel on arrival, refuel detour) for every way to fly from -> to starting with this much fuel
+        let mut options = Vec::new();
+        let direct = self.matrix[from][to];
+        if direct <= self.limits.max_leg_cost && direct <= fuel + EPSILON {
+            let arrival = if self.can_refuel[to] { self.limits.endurance } else { fuel - direct };
+            options.push((direct, arrival, None));
+        }
+        for detour in self.detours[from][to].iter().filter(|detour| detour.first_hop <= fuel + EPSILON) {
+            options.push((detour.total, detour.arrival_fuel, Some((detour.first, detour.last))));
+        }
+        options
+    }
+
+    fn start_fuel(&self) -> f64 {
+        self.limits.endurance
+    }
+
+    fn expand(&self, visits: &[(usize, f64, RefuelDetour)]) -> FuelRoute {
+        // visits are (node, fuel on arrival, detour before it), turned into every landing in order
+        let mut stops = Vec::new();
+        for &(node, fuel, detour) in visits {
+            if let Some((first, last)) = detour {
+                let mut hop = first;
+                loop {
+                    stops.push(FuelStop { node: hop, refuel_stop: true, fuel: self.limits.endurance });
+                    if hop == last {
+                        break;
+                    }
+                    hop = self.hop_next[hop][last];
+                }
+            }
+            stops.push(FuelStop { node, refuel_stop: false, fuel });
+        }
+        let cost = stops.windows(2).map(|pair| self.matrix[pair[0].node][pair[1].node]).sum();
+        FuelRoute { cost, visit_order: visits.iter().map(|visit| visit.0).collect(), stops }
+    }
+}
+
+fn failed_route() -> FuelRoute {
+    FuelRoute { cost: f64::INFINITY, visit_order: Vec::new(), stops: Vec::new() }
+}
+
+pub fn fit_refuel_stops(distance_matrix: &[Vec<f64>], limits: &FuelLimits, visit_order: &[usize]) -> FuelRoute {
+    // cheapest refuel stops for a fixed visit order, exact: a label DP along the order
+    let network = FuelNetwork::new(distance_matrix, limits);
+    let Some(&start) = visit_order.first() else {
+        return failed_route();
+    };
+    let mut layers: Vec<Vec<Label>> = vec![vec![Label { cost: 0.0, fuel: network.start_fuel(), previous: usize::MAX, previous_node: start, detour: None }]];
+    for pair in visit_order.windows(2) {
+        let mut next = Vec::new();
+        for (index, label) in layers.last().unwrap().iter().enumerate() {
+            for (cost, fuel, detour) in network.leg_options(pair[0], label.fuel, pair[1]) {
+                push_label(&mut next, Label { cost: label.cost + cost, fuel, previous: index, previous_node: pair[0], detour });
+            }
+        }
+        if next.is_empty() {
+            return failed_route();
+        }
+        layers.push(next);
+    }
+    let last_layer = layers.last().unwrap();
+    let mut index = (0..last_layer.len()).min_by(|&a, &b| last_layer[a].cost.total_cmp(&last_layer[b].cost)).unwrap();
+    let mut visits = Vec::new();
+    for (position, layer) in layers.iter().enumerate().rev() {
+        let label = layer[index];
+        visits.push((visit_order[position], label.fuel, label.detour));
+        index = label.previous;
+    }
+    visits.reverse();
+    network.expand(&visits)
+}
+
+pub fn solve_with_refuel_stops<F>(distance_matrix: &[Vec<f64>], limits: &FuelLimits, solver: F) -> FuelRoute
+where
+    F: FnOnce(&[Vec<f64>]) -> (f64, Vec<usize>),
+{
+    // runs any matrix solver on the world where every arc is priced as flown with a full tank (the arc itself if it is in
+    // range, otherwise the cheapest chain of refuel stops), then fits the real refuel stops to its visit order
+    let network = FuelNetwork::new(distance_matrix, limits);
+    let reach = limits.max_leg_cost.min(limits.endurance);
+    let in_range: Vec<Vec<f64>> = distance_matrix.iter().enumerate().map(|(from, row)| row.iter().enumerate().map(|(to, &cost)| {
+        if from == to {
+            f64::INFINITY
+        } else if cost <= reach {
+            cost
+        } else {
+            network.detours[from][to].iter().map(|detour| detour.total).fold(f64::INFINITY, f64::min)
+        }
+    }).collect()).collect();
+    let (cost, visit_order) = solver(&in_range);
+    if cost == f64::INFINITY || visit_order.is_empty() {
+        return failed_route();
+    }
+    fit_refuel_stops(distance_matrix, limits, &visit_order)
+}
+
+pub fn fuel_held_karp(distance_matrix: &[Vec<f64>], limits: &FuelLimits) -> Result<FuelRoute, HeldKarpError> {
+    // resource constrained Held-Karp for the best open path: every (mask, last) state keeps its Pareto labels of
+    // (cost, fuel left) instead of one cost, any start with a full tank
+    let n = distance_matrix.len();
+    if n > FUEL_HELD_KARP_MAX_NODES {
+        return Err(HeldKarpError::TooManyNodes { nodes: n, max: FUEL_HELD_KARP_MAX_NODES });
+    }
+    if n == 0 {
+        return Ok(failed_route());
+    }
+    let network = FuelNetwork::new(distance_matrix, limits);
+    let mut states: Vec<Vec<Label>> = vec![Vec::new(); n << n]; // index mask * n + last
+    for start in 0..n {
+        states[(1 << start) * n + start].push(Label { cost: 0.0, fuel: network.start_fuel(), previous: usize::MAX, previous_node: start, detour: None });
+    }
+    for mask in 1..(1usize << n) {
+        for last in 0..n {
+            if states[mask * n + last].is_empty() {
+                continue;
+            }
+            for next in (0..n).filter(|&next| mask & (1 << next) == 0) {
+                let target = (mask | (1 << next)) * n + next;
+                let mut labels = std::mem::take(&mut states[target]);
+                for (index, label) in states[mask * n + last].iter().enumerate() {
+                    for (cost, fuel, detour) in network.leg_options(last, label.fuel, next) {
+                        push_label(&mut labels, Label { cost: label.cost + cost, fuel, previous: index, previous_node: last, detour });
+                    }
+                }
+                states[target] = labels;
+            }
+        }
+    }
+    let full = (1usize << n) - 1;
+    let best = (0..n).flat_map(|last| states[full * n + last].iter().enumerate().map(move |(index, label)| (label.cost, last, index)))
+        .min_by(|a, b| a.0.total_cmp(&b.0));
+    let Some((_, mut last, mut index)) = best else {
+        return Ok(failed_route());
+    };
+    let mut mask = full;
+    let mut visits = Vec::new();
+    loop {
+        let label = states[mask * n + last][index];
+        visits.push((last, label.fuel, label.detour));
+        if label.previous == usize::MAX {
+            break;
+        }
+        mask &= !(1 << last);
+        last = label.previous_node;
+        index = label.previous;
+    }
+    visits.reverse();
+    Ok(network.expand(&visits))
+}
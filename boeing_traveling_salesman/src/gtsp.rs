@@ -0,0 +1,272 @@
+// Generalized TSP. Locations are grouped in clusters, like the airports of one city, and the route visits exactly one
+// location from every cluster and none of the others. The exact DP is Held-Karp's over (visited clusters, last
+// location), so a cluster of one location is the plain TSP. The heuristic is a multi-start nearest neighbor to the
+// cheapest location of a cluster not visited yet, then a local search that moves clusters to other positions (with any
+// of their locations) and picks the best location of every cluster for the order found, until neither improves.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+const GTSP_HELD_KARP_MAX_CLUSTERS: usize = 24; // masks are u32, memory runs out well before this on large worlds
+const EPSILON: f64 = 1e-9;
+
+pub struct GtspConfig {
+    pub starts: usize,             // nearest neighbor starts spread over the locations, 0 starts from every location
+    pub memory_limit_bytes: usize, // the DP refuses instead of allocating more than this
+}
+
+impl Default for GtspConfig {
+    fn default() -> Self {
+        GtspConfig {
+            starts: 8,
+            memory_limit_bytes: 4 << 30,
+        }
+    }
+}
+
+pub struct GtspRoute {
+    pub cost: f64,          // infinity if no route visits every cluster
+    pub path: Vec<usize>,   // one location per cluster in visit order
+    pub chosen: Vec<usize>, // location picked for every cluster, in cluster_lists order
+}
+
+#[derive(Debug)]
+pub enum GtspError {
+    TooManyClusters { clusters: usize, max: usize },
+    OutOfMemory { required_bytes: usize, limit_bytes: usize },
+}
+
+impl fmt::Display for GtspError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GtspError::TooManyClusters { clusters, max } => write!(f, "{} clusters is more than the exact DP's {}", clusters, max),
+            GtspError::OutOfMemory { required_bytes, limit_bytes } =>
+                write!(f, "the DP tables need {} MiB but the limit is {} MiB", required_bytes >> 20, limit_bytes >> 20),
+        }
+    }
+}
+
+impl std::error::Error for GtspError {}
+
+fn failed_route() -> GtspRoute {
+    GtspRoute { cost: f64::INFINITY, path: Vec::new(), chosen: Vec::new() }
+}
+
+pub fn cluster_lists(locations: usize, cluster_of: &[usize]) -> Vec<Vec<usize>> {
+    // the locations of every cluster ordered by cluster id, ids don't have to be contiguous. Locations past the end of
+    // cluster_of are clusters of their own
+    let mut by_id: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (node, &id) in cluster_of.iter().enumerate().take(locations) {
+        by_id.entry(id).or_default().push(node);
+    }
+    let mut clusters: Vec<Vec<usize>> = by_id.into_values().collect();
+    clusters.extend((cluster_of.len()..locations).map(|node| vec![node]));
+    clusters
+}
+
+fn node_clusters(locations: usize, clusters: &[Vec<usize>]) -> Vec<usize> { // index in clusters of every location
+    let mut cluster_index = vec![usize::MAX; locations];
+    for (c, members) in clusters.iter().enumerate() {
+        for &node in members {
+            cluster_index[node] = c;
+        }
+    }
+    cluster_index
+}
+
+fn finish_route(distance_matrix: &[Vec<f64>], clusters: &[Vec<usize>], path: Vec<usize>) -> GtspRoute {
+    let cluster_index = node_clusters(distance_matrix.len(), clusters);
+    let mut chosen = vec![usize::MAX; clusters.len()];
+    for &node in &path {
+        chosen[cluster_index[node]] = node;
+    }
+    let cost = path.windows(2).map(|arc| distance_matrix[arc[0]][arc[1]]).sum();
+    GtspRoute { cost, path, chosen }
+}
+
+pub fn gtsp_memory_estimate(locations: usize, clusters: usize) -> usize {
+    // bytes for the cost (f64) and parent (u32) tables, one slot per (cluster mask, location)
+    let slots = (locations as u128) << clusters;
+    usize::try_from(slots * (std::mem::size_of::<f64>() + std::mem::size_of::<u32>()) as u128).unwrap_or(usize::MAX)
+}
+
+pub fn gtsp_held_karp(distance_matrix: &[Vec<f64>], cluster_of: &[usize], config: &GtspConfig) -> Result<GtspRoute, GtspError> {
+    // Held-Karp's DP with clusters in the masks: a state is (visited clusters, last location) and only steps to the
+    // locations of clusters not in the mask. Any location may start, the cheapest state with every cluster wins
+    let n = distance_matrix.len();
+    let clusters = cluster_lists(n, cluster_of);
+    let k = clusters.len();
+    if k > GTSP_HELD_KARP_MAX_CLUSTERS {
+        return Err(GtspError::TooManyClusters { clusters: k, max: GTSP_HELD_KARP_MAX_CLUSTERS });
+    }
+    let required_bytes = gtsp_memory_estimate(n, k);
+    if required_bytes > config.memory_limit_bytes {
+        return Err(GtspError::OutOfMemory { required_bytes, limit_bytes: config.memory_limit_bytes });
+    }
+    if k == 0 {
+        return Ok(failed_route());
+    }
+    let mut costs = vec![f64::INFINITY; n << k]; // index mask * n + last
+    let mut parents = vec![u32::MAX; n << k];
+    for (c, members) in clusters.iter().enumerate() {
+        for &node in members {
+            costs[(1 << c) * n + node] = 0.0;
+        }
+    }
+    for mask in 1..(1usize << k) {
+        for last_cluster in (0..k).filter(|&c| mask & (1 << c) != 0) {
+            for &last in &clusters[last_cluster] {
+                let cost = costs[mask * n + last];
+                if cost == f64::INFINITY {
+                    continue;
+                }
+                for next_cluster in (0..k).filter(|&c| mask & (1 << c) == 0) {
+                    let next_mask = mask | (1 << next_cluster);
+                    for &next in &clusters[next_cluster] {
+                        let next_cost = cost + distance_matrix[last][next];
+                        if next_cost < costs[next_mask * n + next] {
+                            costs[next_mask * n + next] = next_cost;
+                            parents[next_mask * n + next] = last as u32;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let full = (1usize << k) - 1;
+    let Some(mut last) = (0..n).filter(|&node| costs[full * n + node].is_finite()).min_by(|&a, &b| costs[full * n + a].total_cmp(&costs[full * n + b])) else {
+        return Ok(failed_route());
+    };
+    let cluster_index = node_clusters(n, &clusters);
+    let mut mask = full;
+    let mut path = vec![last];
+    while parents[mask * n + last] != u32::MAX {
+        let parent = parents[mask * n + last] as usize;
+        mask &= !(1 << cluster_index[last]);
+        last = parent;
+        path.push(last);
+    }
+    path.reverse();
+    Ok(finish_route(distance_matrix, &clusters, path))
+}
+
+fn nearest_neighbor(distance_matrix: &[Vec<f64>], clusters: &[Vec<usize>], cluster_index: &[usize], start: usize) -> Option<Vec<usize>> {
+    // always flies the cheapest arc to a location whose cluster isn't visited yet, None if that dead ends
+    let mut visited = vec![false; clusters.len()];
+    visited[cluster_index[start]] = true;
+    let mut path = vec![start];
+    while path.len() < clusters.len() {
+        let last = *path.last().unwrap();
+        let next = (0..distance_matrix.len())
+            .filter(|&node| !visited[cluster_index[node]] && distance_matrix[last][node].is_finite())
+            .min_by(|&a, &b| distance_matrix[last][a].total_cmp(&distance_matrix[last][b]))?;
+        visited[cluster_index[next]] = true;
+        path.push(next);
+    }
+    Some(path)
+}
+
+fn best_locations(distance_matrix: &[Vec<f64>], clusters: &[Vec<usize>], cluster_index: &[usize], path: &[usize]) -> (f64, Vec<usize>) {
+    // the cheapest location of every cluster for the cluster order of path, a shortest path through the layers
+    let order: Vec<usize> = path.iter().map(|&node| cluster_index[node]).collect();
+    let mut layers: Vec<Vec<(f64, usize)>> = vec![clusters[order[0]].iter().map(|_| (0.0, usize::MAX)).collect()]; // cost, index in previous layer
+    for pair in order.windows(2) {
+        let previous = layers.last().unwrap();
+        let layer = clusters[pair[1]].iter().map(|&node| {
+            (0..previous.len()).map(|p| (previous[p].0 + distance_matrix[clusters[pair[0]][p]][node], p))
+                .min_by(|a, b| a.0.total_cmp(&b.0)).unwrap()
+        }).collect();
+        layers.push(layer);
+    }
+    let last_layer = layers.last().unwrap();
+    let mut index = (0..last_layer.len()).min_by(|&a, &b| last_layer[a].0.total_cmp(&last_layer[b].0)).unwrap();
+    let cost = last_layer[index].0;
+    let mut best = vec![0; order.len()];
+    for position in (0..order.len()).rev() {
+        best[position] = clusters[order[position]][index];
+        index = layers[position][index].1;
+    }
+    (cost, best)
+}
+
+fn relocate_cluster(distance_matrix: &[Vec<f64>], clusters: &[Vec<usize>], cluster_index: &[usize], path: &mut Vec<usize>, cost: &mut f64) -> bool {
+    // takes one cluster out and puts it back at the cheapest position with the cheapest of its locations there, the
+    // first move that saves anything is applied
+    let arc = |from: usize, to: usize| distance_matrix[from][to];
+    for i in 0..path.len() {
+        let removed = path[i];
+        let before = if i > 0 { Some(path[i - 1]) } else { None };
+        let after = path.get(i + 1).copied();
+        let bridge = match (before, after) {
+            (Some(a), Some(b)) => arc(a, b),
+            _ => 0.0,
+        };
+        if bridge == f64::INFINITY {
+            continue;
+        }
+        let gain = before.map_or(0.0, |a| arc(a, removed)) + after.map_or(0.0, |b| arc(removed, b)) - bridge;
+        let mut rest = path.clone();
+        rest.remove(i);
+        for j in 0..=rest.len() {
+            let previous = if j > 0 { Some(rest[j - 1]) } else { None };
+            let next = rest.get(j).copied();
+            let old = match (previous, next) {
+                (Some(a), Some(b)) => arc(a, b),
+                _ => 0.0,
+            };
+            for &node in &clusters[cluster_index[removed]] {
+                let added = previous.map_or(0.0, |a| arc(a, node)) + next.map_or(0.0, |b| arc(node, b)) - old;
+                if added.is_finite() && added - gain < -EPSILON {
+                    rest.insert(j, node);
+                    *path = rest;
+                    *cost += added - gain;
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn local_search(distance_matrix: &[Vec<f64>], clusters: &[Vec<usize>], cluster_index: &[usize], path: &mut Vec<usize>, cost: &mut f64) {
+    loop {
+        while relocate_cluster(distance_matrix, clusters, cluster_index, path, cost) {}
+        let (best_cost, best) = best_locations(distance_matrix, clusters, cluster_index, path);
+        if best_cost >= *cost - EPSILON {
+            return;
+        }
+        *path = best;
+        *cost = best_cost;
+    }
+}
+
+pub fn gtsp_heuristic(distance_matrix: &[Vec<f64>], cluster_of: &[usize], config: &GtspConfig) -> GtspRoute {
+    // nearest neighbor and the local search from config.starts locations spread over the world, the cheapest route wins.
+    // Sparse worlds can dead end from every start, run it on the metric closure there
+    let n = distance_matrix.len();
+    let clusters = cluster_lists(n, cluster_of);
+    if clusters.is_empty() {
+        return failed_route();
+    }
+    let cluster_index = node_clusters(n, &clusters);
+    let starts: Vec<usize> = if config.starts == 0 || config.starts >= n {
+        (0..n).collect()
+    } else {
+        (0..config.starts).map(|k| k * n / config.starts).collect()
+    };
+    let mut best: Option<(f64, Vec<usize>)> = None;
+    for start in starts {
+        let Some(mut path) = nearest_neighbor(distance_matrix, &clusters, &cluster_index, start) else {
+            continue;
+        };
+        let mut cost = path.windows(2).map(|arc| distance_matrix[arc[0]][arc[1]]).sum();
+        local_search(distance_matrix, &clusters, &cluster_index, &mut path, &mut cost);
+        if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost - EPSILON) {
+            best = Some((cost, path));
+        }
+    }
+    match best {
+        Some((_, path)) => finish_route(distance_matrix, &clusters, path),
+        None => failed_route(),
+    }
+}
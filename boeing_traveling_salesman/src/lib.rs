@@ -33,3 +33,9 @@ impl Graph {
         self.graph.node_count()
     }
 }
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
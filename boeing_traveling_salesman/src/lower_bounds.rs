@@ -0,0 +1,298 @@
+// Lower bounds on the cost of the best open path, used to prune exact searches and to judge heuristics.
+// An open path over n nodes is the same as a closed tour over n + 1 nodes where the extra dummy node
+// has free arcs to and from everyone, so the classic tour bounds are computed on that closed version.
+
+pub fn assignment_problem(cost_matrix: &[Vec<f64>]) -> (f64, Vec<usize>) {
+    // Hungarian algorithm (O(n^3) with potentials), assigns every row a different column at minimum total cost
+    // infinite entries are forbidden, returns infinity if every assignment needs one
+    let n = cost_matrix.len();
+    if n == 0 {
+        return (0.0, Vec::new());
+    }
+    // forbidden entries get a finite cost larger than any assignment of allowed entries so the potentials stay well behaved
+    let largest = cost_matrix.iter().flatten().filter(|cost| cost.is_finite()).fold(0.0, |a: f64, &b| a.max(b.abs()));
+    let forbidden = (largest + 1.0) * (n as f64 + 1.0);
+    let cost = |row: usize, column: usize| {
+        let cost = cost_matrix[row][column];
+        if cost.is_finite() { cost } else { forbidden }
+    };
+
+    // 1-indexed, index 0 is the virtual row/column used while growing each augmenting path
+    let mut row_potential = vec![0.0; n + 1];
+    let mut column_potential = vec![0.0; n + 1];
+    let mut column_owner = vec![0usize; n + 1]; // row assigned to each column
+    let mut way = vec![0usize; n + 1];
+    for row in 1..=n {
+        column_owner[0] = row;
+        let mut column = 0;
+        let mut min_reduced = vec![f64::INFINITY; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[column] = true;
+            let current_row = column_owner[column];
+            let mut delta = f64::INFINITY;
+            let mut next_column = 0;
+            for j in 1..=n {
+                if !used[j] {
+                    let reduced = cost(current_row - 1, j - 1) - row_potential[current_row] - column_potential[j];
+                    if reduced < min_reduced[j] {
+                        min_reduced[j] = reduced;
+                        way[j] = column;
+                    }
+                    if min_reduced[j] < delta {
+                        delta = min_reduced[j];
+                        next_column = j;
+                    }
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    row_potential[column_owner[j]] += delta;
+                    column_potential[j] -= delta;
+                } else {
+                    min_reduced[j] -= delta;
+                }
+            }
+            column = next_column;
+            if column_owner[column] == 0 {
+                break;
+            }
+        }
+        loop { // flip the augmenting path
+            let previous_column = way[column];
+            column_owner[column] = column_owner[previous_column];
+            column = previous_column;
+            if column == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for column in 1..=n {
+        assignment[column_owner[column] - 1] = column - 1;
+    }
+    let total = assignment.iter().enumerate().map(|(row, &column)| cost_matrix[row][column]).sum();
+    (total, assignment) // a forbidden entry makes the sum infinite
+}
+
+fn dummy_closed_matrix(distance_matrix: &[Vec<f64>]) -> Vec<Vec<f64>> { // adds the dummy node n with free arcs both ways
+    let n = distance_matrix.len();
+    let mut matrix: Vec<Vec<f64>> = distance_matrix.iter().enumerate()
+        .map(|(i, row)| row.iter().enumerate().map(|(j, &cost)| if i == j { f64::INFINITY } else { cost }).chain(std::iter::once(0.0)).collect())
+        .collect();
+    let mut dummy_row = vec![0.0; n + 1];
+    dummy_row[n] = f64::INFINITY;
+    matrix.push(dummy_row);
+    matrix
+}
+
+pub fn assignment_lower_bound(distance_matrix: &[Vec<f64>]) -> f64 {
+    // every node of the closed tour gets exactly one successor, dropping the "one cycle" requirement
+    if distance_matrix.is_empty() {
+        return 0.0;
+    }
+    assignment_problem(&dummy_closed_matrix(distance_matrix)).0
+}
+
+fn minimum_arborescence(nodes: usize, root: usize, arcs: &[(usize, usize, f64)]) -> Option<Vec<usize>> {
+    // Chu-Liu/Edmonds, O(V E). Returns the indexes of the chosen arcs, None if some node can't be reached from the root.
+    // Every node takes its cheapest incoming arc, cycles are contracted into one node with the arcs into them repriced
+    // by the cycle arc they would replace, then the smaller problem is solved and the cycles are expanded again
+    let mut cheapest_in: Vec<Option<usize>> = vec![None; nodes];
+    for (index, &(from, to, cost)) in arcs.iter().enumerate() {
+        if from != to && to != root && cheapest_in[to].is_none_or(|best| cost < arcs[best].2) {
+            cheapest_in[to] = Some(index);
+        }
+    }
+    if (0..nodes).any(|node| node != root && cheapest_in[node].is_none()) {
+        return None;
+    }
+
+    // find the cycles made by the cheapest incoming arcs
+    let mut cycle_of = vec![usize::MAX; nodes];
+    let mut cycles = 0;
+    let mut visited_by = vec![usize::MAX; nodes];
+    for start in 0..nodes {
+        let mut node = start;
+        while node != root && visited_by[node] == usize::MAX && cycle_of[node] == usize::MAX {
+            visited_by[node] = start;
+            node = arcs[cheapest_in[node].unwrap()].0;
+        }
+        if node != root && visited_by[node] == start && cycle_of[node] == usize::MAX {
+            let mut member = node;
+            loop {
+                cycle_of[member] = cycles;
+                member = arcs[cheapest_in[member].unwrap()].0;
+                if member == node {
+                    break;
+                }
+            }
+            cycles += 1;
+        }
+    }
+    if cycles == 0 {
+        return Some((0..nodes).filter(|&node| node != root).map(|node| cheapest_in[node].unwrap()).collect());
+    }
+
+    // contract every cycle into one node, the other nodes get the ids after the cycles
+    let mut contracted = vec![0; nodes];
+    let mut next_id = cycles;
+    for node in 0..nodes {
+        contracted[node] = if cycle_of[node] != usize::MAX {
+            cycle_of[node]
+        } else {
+            next_id += 1;
+            next_id - 1
+        };
+    }
+    let mut smaller_arcs = Vec::new();
+    let mut original_index = Vec::new();
+    for (index, &(from, to, cost)) in arcs.iter().enumerate() {
+        let (small_from, small_to) = (contracted[from], contracted[to]);
+        if small_from != small_to {
+            let replaced = if cycle_of[to] != usize::MAX { arcs[cheapest_in[to].unwrap()].2 } else { 0.0 };
+            smaller_arcs.push((small_from, small_to, cost - replaced));
+            original_index.push(index);
+        }
+    }
+    let chosen = minimum_arborescence(next_id, contracted[root], &smaller_arcs)?;
+
+    // expand, the arc entering a cycle replaces the cycle arc into the node it enters
+    let mut result: Vec<usize> = chosen.iter().map(|&small| original_index[small]).collect();
+    let mut entered = vec![false; nodes];
+    for &index in &result {
+        entered[arcs[index].1] = true;
+    }
+    for node in 0..nodes {
+        if cycle_of[node] != usize::MAX && !entered[node] {
+            result.push(cheapest_in[node].unwrap());
+        }
+    }
+    Some(result)
+}
+
+fn one_arborescence(matrix: &[Vec<f64>], penalties: &[f64]) -> Option<(f64, Vec<usize>)> {
+    // min 1-arborescence of the dummy closed instance with arc (i, j) priced cost + penalties[i]: a spanning
+    // arborescence rooted at the dummy plus the cheapest arc back into it. Returns (penalized cost, out-degrees)
+    let root = matrix.len() - 1;
+    let arcs: Vec<(usize, usize, f64)> = matrix.iter().enumerate()
+        .flat_map(|(from, row)| row.iter().enumerate().filter(move |&(to, cost)| to != root && to != from && cost.is_finite())
+            .map(move |(to, &cost)| (from, to, cost + penalties[from])))
+        .collect();
+    let chosen = minimum_arborescence(matrix.len(), root, &arcs)?;
+    let mut out_degree = vec![0; matrix.len()];
+    let mut cost = 0.0;
+    for &index in &chosen {
+        cost += arcs[index].2;
+        out_degree[arcs[index].0] += 1;
+    }
+    let back = (0..root).filter(|&node| matrix[node][root].is_finite()).min_by(|&a, &b| penalties[a].total_cmp(&penalties[b]))?;
+    cost += matrix[back][root] + penalties[back];
+    out_degree[back] += 1;
+    Some((cost, out_degree))
+}
+
+pub struct LowerBoundConfig {
+    pub subgradient_iterations: usize,
+    pub initial_step_scale: f64, // Polyak step scale, halved whenever the bound stalls
+}
+
+impl Default for LowerBoundConfig {
+    fn default() -> Self {
+        LowerBoundConfig {
+            subgradient_iterations: 300,
+            initial_step_scale: 2.0,
+        }
+    }
+}
+
+pub struct LowerBounds {
+    pub assignment: f64,
+    pub arborescence: f64, // min 1-arborescence with the best single penalty on leaving the dummy
+    pub held_karp: f64,    // Lagrangian subgradient ascent on every out-degree
+}
+
+impl LowerBounds {
+    pub fn best(&self) -> f64 {
+        self.assignment.max(self.arborescence).max(self.held_karp)
+    }
+}
+
+pub fn arborescence_lower_bound(distance_matrix: &[Vec<f64>]) -> f64 {
+    // without a penalty every node hangs straight off the free dummy arcs and the bound is 0, so the dummy's arcs out
+    // are charged lambda and lambda is taken back once (the path starts once). The bound is concave in lambda
+    if distance_matrix.is_empty() {
+        return 0.0;
+    }
+    let matrix = dummy_closed_matrix(distance_matrix);
+    let root = matrix.len() - 1;
+    let bound_at = |lambda: f64| {
+        let mut penalties = vec![0.0; matrix.len()];
+        penalties[root] = lambda;
+        one_arborescence(&matrix, &penalties).map_or(f64::INFINITY, |(cost, _)| cost - lambda)
+    };
+    let largest = distance_matrix.iter().flatten().filter(|cost| cost.is_finite()).fold(0.0, |a: f64, &b| a.max(b));
+    let (mut low, mut high) = (0.0, largest * 2.0 + 1.0);
+    for _ in 0..40 { // ternary search on the concave bound
+        let (first, second) = (low + (high - low) / 3.0, high - (high - low) / 3.0);
+        if bound_at(first) < bound_at(second) {
+            low = first;
+        } else {
+            high = second;
+        }
+    }
+    bound_at(low).max(bound_at(0.0))
+}
+
+pub fn held_karp_lower_bound(distance_matrix: &[Vec<f64>], upper_bound: f64, config: &LowerBoundConfig) -> f64 {
+    // Held-Karp style Lagrangian relaxation of the "one arc out of every node" constraints on the 1-arborescence,
+    // subgradient ascent with Polyak steps towards the upper bound (the best path a solver found)
+    if distance_matrix.is_empty() {
+        return 0.0;
+    }
+    let matrix = dummy_closed_matrix(distance_matrix);
+    let mut penalties = vec![0.0; matrix.len()];
+    let mut best = f64::NEG_INFINITY;
+    let mut step_scale = config.initial_step_scale;
+    let mut stalled = 0;
+    for _ in 0..config.subgradient_iterations {
+        let Some((cost, out_degree)) = one_arborescence(&matrix, &penalties) else {
+            return f64::INFINITY; // some node can't be reached at all
+        };
+        let bound = cost - penalties.iter().sum::<f64>();
+        if bound > best + 1e-9 {
+            best = bound;
+            stalled = 0;
+        } else {
+            stalled += 1;
+            if stalled >= 20 {
+                step_scale /= 2.0;
+                stalled = 0;
+            }
+        }
+        let subgradient: Vec<f64> = out_degree.iter().map(|&degree| degree as f64 - 1.0).collect();
+        let norm: f64 = subgradient.iter().map(|g| g * g).sum();
+        if norm == 0.0 {
+            break; // the 1-arborescence is a tour, the bound is optimal
+        }
+        // without a finite upper bound aim a little above the current bound
+        let target = if upper_bound.is_finite() { upper_bound } else { bound.abs() * 1.05 + 1.0 };
+        let step = step_scale * (target - bound).max(1e-6) / norm;
+        for (penalty, g) in penalties.iter_mut().zip(&subgradient) {
+            *penalty += step * g;
+        }
+        if step_scale < 1e-6 {
+            break;
+        }
+    }
+    best
+}
+
+pub fn lower_bounds(distance_matrix: &[Vec<f64>], upper_bound: f64, config: &LowerBoundConfig) -> LowerBounds {
+    LowerBounds {
+        assignment: assignment_lower_bound(distance_matrix),
+        arborescence: arborescence_lower_bound(distance_matrix),
+        held_karp: held_karp_lower_bound(distance_matrix, upper_bound, config),
+    }
+}
@@ -4,6 +4,22 @@ mod tests;
 mod algorithms;
 mod analyzer;
 mod csv_readers;
+mod cvrp;
+mod feasibility;
+mod fleet;
+mod fuel;
+mod gtsp;
+mod lower_bounds;
+mod metric_closure;
+mod milp;
+mod orienteering;
+mod precedence;
+mod rng;
+mod steiner;
+mod time_windows;
+mod transformations;
+
+use crate::algorithms::{HeldKarpConfig, HeldKarpMode};
 //use std::error::Error;
 //use std::io;
 //use std::fs;
@@ -24,17 +40,118 @@ mod csv_readers;
 fn main() {
      
     let filename = "full_world.csv";
+    // the second csvs with required airports, precedences, windows, airframes, demands, prizes and clusters are the
+    // samples in test-csv-sidecars, written for 15 locations so any world at least that big can use them
+    let export_milp = std::env::args().any(|arg| arg == "--export-milp"); // the MILP files for an outside solver are only written on request
+    match crate::csv_readers::read_csv_as_hashmap(filename) {
+        Ok(distances) => {
+            let report = crate::feasibility::check(&distances);
+            println!("Feasibility check: {}", report);
+            println!("Sources {:?}, sinks {:?}, forced arcs {:?}", report.zero_in_degree, report.zero_out_degree, report.forced_arcs);
+        }
+        Err(e) => eprintln!("Error reading file: {}", e),
+    }
+
     match crate::csv_readers::read_csv_as_matrix(filename) {
         Ok(distance_matrix) => {
-            // Check if the matrix is 20x20 or smaller
-            if distance_matrix.len() <= 20 && distance_matrix.iter().all(|row| row.len() <= 20) {
-                let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix);
-                println!("Held_Karps_Optimal solution algorithm-");
-                println!("Minimum Cost: {}", cost);
-                println!("Optimal Path: {:?}", path);
-            } else {
-                eprintln!("Matrix is too large. Only 20 locations or less are allowed.");
+            // the tables are sized up front, too many locations for the memory limit comes back as an error
+            let config = HeldKarpConfig::default();
+            println!("Held-Karp tables need {} MiB", crate::algorithms::held_karp_memory_estimate(distance_matrix.len(), HeldKarpMode::BestOpenPath) >> 20);
+            match crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &config) {
+                Ok((cost, path)) => {
+                    println!("Held_Karps_Optimal solution algorithm-");
+                    println!("Minimum Cost: {}", cost);
+                    println!("Optimal Path: {:?}", path);
+                    // round trip through the symmetric 2n location version of the instance
+                    let symmetric = crate::transformations::asymmetric_to_symmetric(&distance_matrix, true);
+                    let symmetric_tour = symmetric.symmetric_tour(&path);
+                    let symmetric_cost: f64 = (0..symmetric_tour.len()).map(|k| symmetric.matrix[symmetric_tour[k]][symmetric_tour[(k + 1) % symmetric_tour.len()]]).sum();
+                    println!("Symmetric version: {} locations, directed cost {} from tour cost {}, path back {:?}",
+                        symmetric.matrix.len(), symmetric.directed_cost(symmetric_cost), symmetric_cost, symmetric.directed_tour(&symmetric_tour));
+                    let last = distance_matrix.len() - 1;
+                    for mode in [HeldKarpMode::FixedStart(0), HeldKarpMode::FixedStartEnd(0, last), HeldKarpMode::ClosedTour(0)] {
+                        match crate::algorithms::held_karp_algorithm(&distance_matrix, mode, &config) {
+                            Ok((cost, path)) => println!("{:?} Minimum Cost: {} Path: {:?}", mode, cost, path),
+                            Err(e) => eprintln!("Held-Karp {:?} failed: {}", mode, e),
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Held-Karp failed: {}", e),
+            }
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            // sparse worlds may have no path that visits every airport once, flying through visited airports fixes that
+            let route = crate::metric_closure::solve_with_ferry_legs(&distance_matrix, |closure| {
+                crate::algorithms::held_karp_algorithm(closure, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap_or((f64::INFINITY, Vec::new()))
+            });
+            println!("Held-Karp with ferry legs-");
+            println!("Minimum Cost: {}", route.cost);
+            println!("Visit order: {:?}", route.visit_order);
+            let stops: Vec<String> = route.stops.iter().map(|stop| if stop.pass_through { format!("({})", stop.node) } else { stop.node.to_string() }).collect();
+            println!("Flown (pass-throughs in brackets): {}", stops.join(" -> "));
+            // only the required airports from a second csv have to be visited, the optional ones are transit points
+            match crate::csv_readers::read_required_csv("test-csv-sidecars/required.csv", distance_matrix.len()) {
+                Ok(required) => {
+                    let route = crate::steiner::solve_steiner(&distance_matrix, &required, |closure| {
+                        crate::algorithms::held_karp_algorithm(closure, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap_or_else(|_| {
+                            let result = crate::algorithms::simulated_annealing(closure, &crate::algorithms::AnnealingConfig { seed: 42, ..Default::default() });
+                            (result.cost, result.path)
+                        })
+                    });
+                    println!("Required airports only- ({} of {} required)", crate::steiner::required_nodes(&distance_matrix, &required).len(), distance_matrix.len());
+                    println!("Minimum Cost: {}", route.cost);
+                    println!("Visit order: {:?}", route.visit_order);
+                    let stops: Vec<String> = route.stops.iter().map(|stop| if stop.pass_through { format!("({})", stop.node) } else { stop.node.to_string() }).collect();
+                    println!("Flown (pass-throughs in brackets): {}", stops.join(" -> "));
+                    println!("Optional airports flown through: {:?}", route.optional_flown);
+                }
+                Err(e) => eprintln!("Error reading required locations: {}", e),
+            }
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            let symmetry = crate::algorithms::detect_symmetry(&distance_matrix, 1e-9);
+            let result = crate::algorithms::christofides(&distance_matrix, &symmetry);
+            println!("Christofides solution algorithm-");
+            println!("Minimum Cost: {}", result.cost);
+            println!("Path: {:?}", result.path);
+            println!("Closed tour cost {}, matching used: {}, exact: {}, guaranteed ratio: {:?}", result.tour_cost, result.used_matching, result.exact_matching, result.approximation_ratio);
+            println!("Symmetric: {}, metric: {}, one way pairs: {}, largest asymmetry: {}", result.symmetry.symmetric, result.symmetry.metric, result.symmetry.one_way_pairs, result.symmetry.max_asymmetry);
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            let result = crate::algorithms::branch_and_bound(&distance_matrix, &crate::algorithms::BranchAndBoundConfig::default());
+            println!("Branch and bound solution algorithm-");
+            println!("Minimum Cost: {}", result.cost);
+            println!("Optimal Path: {:?}", result.path);
+            println!("Proved optimal: {}, lower bound: {}, search nodes: {}", result.proved_optimal, result.lower_bound, result.nodes_explored);
+            // the same world as a MILP, files for an outside solver with --export-milp and the in-process solve with lazy subtour cuts
+            if export_milp {
+                for (formulation, format, file_path) in [(crate::milp::Formulation::Mtz, crate::milp::ExportFormat::Lp, "full_world_mtz.lp"),
+                    (crate::milp::Formulation::SingleCommodityFlow, crate::milp::ExportFormat::Mps, "full_world_flow.mps")] {
+                    match crate::milp::export(&distance_matrix, formulation, format, file_path) {
+                        Ok(()) => println!("Wrote {}", file_path),
+                        Err(e) => eprintln!("Error writing {}: {}", file_path, e),
+                    }
+                }
             }
+            match crate::milp::solve_with_subtour_cuts(&distance_matrix, &crate::milp::MilpConfig::default()) {
+                Ok(result) => println!("MILP with subtour cuts: cost {}, path {:?}, {} solves, {} cuts", result.cost, result.path, result.cut_rounds, result.cuts),
+                Err(e) => eprintln!("MILP skipped: {}", e),
+            }
+            let bounds = crate::lower_bounds::lower_bounds(&distance_matrix, result.cost, &crate::lower_bounds::LowerBoundConfig::default());
+            println!("Lower bounds: assignment {}, 1-arborescence {}, Held-Karp {}, gap {:.2}%", bounds.assignment, bounds.arborescence,
+                bounds.held_karp, 100.0 * (result.cost - bounds.best()) / bounds.best());
         }
         Err(e) => eprintln!("Error reading CSV: {}", e),
     }
@@ -53,21 +170,266 @@ fn main() {
         }    
     }
 
-    if filename == filename {
-        match crate::csv_readers::read_csv_as_hashmap(filename) {
-            Ok(distances) => {  
-                let (cost, path) = crate::algorithms::nearest_neighbor_sparse( &distances);
-                println!("Nearest Neighbor sparse world solution algorithm-");
-                println!("Minimum Cost: {}", cost);
-                println!("Optimal Path: {:?}", path);
+    match crate::csv_readers::read_csv_as_hashmap(filename) {
+        Ok(distances) => {  
+            let (cost, path) = crate::algorithms::nearest_neighbor_sparse( &distances);
+            println!("Nearest Neighbor sparse world solution algorithm-");
+            println!("Minimum Cost: {}", cost);
+            println!("Optimal Path: {:?}", path);
+        }
+        Err(e) => {  // Handle potential errors
+            eprintln!("Error reading file: {}", e);
+        }
+    }    
+
+    match crate::csv_readers::read_csv_as_hashmap(filename) {
+        Ok(distances) => {
+            let config = crate::algorithms::MultiStartConfig { seed: 42, starts: Some(distances.len().min(32)), ..Default::default() };
+            let (cost, path) = crate::algorithms::multi_start_nearest_neighbor(&distances, &config);
+            println!("Multi-start nearest neighbor solution algorithm- (seed {}, {:?} starts)", config.seed, config.starts);
+            println!("Minimum Cost: {}", cost);
+            println!("Optimal Path: {:?}", path);
+        }
+        Err(e) => eprintln!("Error reading file: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            // visit in order pairs from a second csv, exact when the world is small enough and nearest neighbor + Or-opt
+            // and annealing that keep every pair otherwise
+            match crate::csv_readers::read_precedence_csv("test-csv-sidecars/precedence.csv", distance_matrix.len()) {
+                Ok(precedences) => {
+                    let config = HeldKarpConfig { precedences: precedences.clone(), ..Default::default() };
+                    match crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &config) {
+                        Ok((cost, path)) => println!("Held-Karp with precedence: cost {}, path {:?}", cost, path),
+                        Err(e) => eprintln!("Held-Karp with precedence skipped: {}", e),
+                    }
+                    let (cost, path) = crate::precedence::precedence_nearest_neighbor(&distance_matrix, &precedences);
+                    let (cost, path) = if cost.is_finite() { crate::precedence::precedence_or_opt(&distance_matrix, &precedences, &path) } else { (cost, path) };
+                    println!("Nearest neighbor + Or-opt with precedence: cost {}, path {:?}", cost, path);
+                    let config = crate::algorithms::AnnealingConfig { seed: 42, precedences: precedences.clone(), ..Default::default() };
+                    let result = crate::algorithms::simulated_annealing(&distance_matrix, &config);
+                    println!("Annealing with precedence: cost {}, broken pairs {:?}", result.cost, crate::precedence::broken_pairs(&result.path, &precedences));
+                }
+                Err(e) => eprintln!("Error reading precedence: {}", e),
             }
-            Err(e) => {  // Handle potential errors
-                eprintln!("Error reading file: {}", e);
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            // arc costs are flight times here, windows come from the sample csvs in test-csv-sidecars
+            match crate::csv_readers::read_time_windows_csv("test-csv-sidecars/windows.csv", distance_matrix.len()) {
+                Ok(windows) => {
+                    for strict in [false, true] {
+                        let config = crate::time_windows::TimeWindowConfig { strict, ..Default::default() };
+                        match crate::time_windows::time_window_route(&distance_matrix, &windows, &config) {
+                            Ok(schedule) => {
+                                println!("Time window route- (strict {})", strict);
+                                println!("Flight time: {}, waiting: {}, lateness: {}, done at {}", schedule.cost, schedule.total_wait, schedule.total_lateness, schedule.completion_time);
+                                for stop in &schedule.stops {
+                                    println!("  {} arrive {:.2} depart {:.2} (wait {:.2}, late {:.2})", stop.node, stop.arrival, stop.departure, stop.wait, stop.lateness);
+                                }
+                            }
+                            Err(crate::time_windows::TimeWindowError::WindowsViolated { best, .. }) if strict => {
+                                println!("Strict time windows rejected route {:?}", best.path);
+                            }
+                            Err(e) => eprintln!("Time window route failed: {}", e),
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Error reading time windows: {}", e),
             }
-        }    
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            // limited range, fuel at every third airport. Exact for small worlds, otherwise annealing with refuel stops fitted after
+            let limits = crate::fuel::FuelLimits { max_leg_cost: 8.0, endurance: 12.0, refuel_nodes: (0..distance_matrix.len()).step_by(3).collect() };
+            let route = match crate::fuel::fuel_held_karp(&distance_matrix, &limits) {
+                Ok(route) => route,
+                Err(e) => {
+                    eprintln!("Fuel Held-Karp skipped: {}", e);
+                    crate::fuel::solve_with_refuel_stops(&distance_matrix, &limits, |in_range| {
+                        let result = crate::algorithms::simulated_annealing(in_range, &crate::algorithms::AnnealingConfig { seed: 42, ..Default::default() });
+                        (result.cost, result.path)
+                    })
+                }
+            };
+            println!("Fuel limited route- (max leg {}, endurance {}, refuel at {:?})", limits.max_leg_cost, limits.endurance, limits.refuel_nodes);
+            println!("Minimum Cost: {}", route.cost);
+            println!("Visit order: {:?}", route.visit_order);
+            let stops: Vec<String> = route.stops.iter().map(|stop| if stop.refuel_stop { format!("[{} refuel]", stop.node) } else { format!("{} ({:.1} left)", stop.node, stop.fuel) }).collect();
+            println!("Stops: {}", stops.join(" "));
+            println!("Same order with refuel stops fitted again: {}", crate::fuel::fit_refuel_stops(&distance_matrix, &limits, &route.visit_order).cost);
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_hashmap(filename) {
+        Ok(distances) => {
+            // three aircraft, all out of location 0 and back keeping the longest route short, or one depot each for the lowest total
+            let configs = [
+                crate::fleet::FleetConfig { aircraft: 3, objective: crate::fleet::FleetObjective::MinMax, return_to_depot: true, ..Default::default() },
+                crate::fleet::FleetConfig { aircraft: 3, depots: crate::fleet::Depots::Separate(vec![0, 1, 2]), ..Default::default() },
+            ];
+            for config in configs {
+                match crate::fleet::plan_fleet(&distances, &config) {
+                    Ok(plan) => {
+                        println!("Fleet plan- ({} aircraft, {:?}, {:?})", config.aircraft, config.depots, config.objective);
+                        for (aircraft, route) in plan.routes.iter().enumerate() {
+                            println!("Aircraft {} from {}: cost {}, path {:?}", aircraft, route.depot, route.cost, route.path);
+                        }
+                        println!("Total Cost: {}, longest route: {}", plan.total_cost, plan.max_route_cost);
+                    }
+                    Err(e) => eprintln!("Fleet planner: {}", e),
+                }
+            }
+        }
+        Err(e) => eprintln!("Error reading file: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_hashmap(filename) {
+        Ok(distances) => {
+            // a mixed fleet of three out of location 0, the airframes and their own cost tables come from the sample csvs in test-csv-sidecars
+            let locations = crate::feasibility::node_count(&distances);
+            let aircraft_types = crate::csv_readers::read_aircraft_types_csv("test-csv-sidecars/aircraft.csv", 3, locations)
+                .and_then(|mut types| crate::csv_readers::read_aircraft_costs_csv("test-csv-sidecars/aircraft_costs.csv", &mut types, locations).map(|_| types));
+            match aircraft_types {
+                Ok(aircraft_types) => {
+                    let config = crate::fleet::FleetConfig { aircraft: 3, aircraft_types, ..Default::default() };
+                    match crate::fleet::plan_fleet(&distances, &config) {
+                        Ok(plan) => {
+                            println!("Mixed fleet plan- ({} aircraft)", config.aircraft);
+                            for ((aircraft, route), aircraft_type) in plan.routes.iter().enumerate().zip(&config.aircraft_types) {
+                                println!("Aircraft {} (x{}, {} airports and {} arcs prohibited): cost {}, path {:?}", aircraft, aircraft_type.cost_multiplier,
+                                    aircraft_type.prohibited_nodes.len(), aircraft_type.prohibited_arcs.len(), route.cost, route.path);
+                            }
+                            println!("Total Cost: {}", plan.total_cost);
+                        }
+                        Err(e) => eprintln!("Fleet planner: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("Error reading aircraft types: {}", e),
+            }
+        }
+        Err(e) => eprintln!("Error reading file: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_hashmap(filename) {
+        Ok(distances) => {
+            // cargo deliveries out of location 0, demands come from the sample csvs in test-csv-sidecars
+            match crate::csv_readers::read_demands_csv("test-csv-sidecars/demands.csv", crate::feasibility::node_count(&distances)) {
+                Ok(demands) => {
+                    let config = crate::cvrp::CvrpConfig { capacity: 30.0, ferry_legs: true, ..Default::default() };
+                    match crate::cvrp::solve_cvrp(&distances, &demands, &config) {
+                        Ok(solution) => {
+                            println!("Capacitated routes- (capacity {})", config.capacity);
+                            for (aircraft, route) in solution.routes.iter().enumerate() {
+                                println!("Aircraft {}: cost {}, load {}, deliveries {:?}", aircraft, route.cost, route.load, route.path);
+                                let stops: Vec<String> = route.stops.iter().zip(&route.load_profile)
+                                    .map(|(stop, on_board)| if stop.pass_through { format!("({})", stop.node) } else { format!("{} [{}]", stop.node, on_board) }).collect();
+                                println!("  Flown (pass-throughs in brackets, cargo on board after): {}", stops.join(" -> "));
+                            }
+                            println!("Total Cost: {}", solution.total_cost);
+                        }
+                        Err(e) => eprintln!("CVRP: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("Error reading demands: {}", e),
+            }
+        }
+        Err(e) => eprintln!("Error reading file: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            // the most valuable locations a route out of location 0 reaches for a cost of 50, prizes come from a second csv
+            match crate::csv_readers::read_prizes_csv("test-csv-sidecars/prizes.csv", distance_matrix.len()) {
+                Ok(prizes) => {
+                    let config = crate::orienteering::OrienteeringConfig { budget: 50.0, ..Default::default() };
+                    let mut routes = Vec::new();
+                    for (name, route) in [("heuristic", crate::orienteering::orienteering_heuristic(&distance_matrix, &prizes, &config)),
+                        ("exact DP", crate::orienteering::orienteering_held_karp(&distance_matrix, &prizes, &config))] {
+                        match route {
+                            Ok(route) => routes.push((name, route)),
+                            Err(e) => eprintln!("Orienteering {}: {}", name, e),
+                        }
+                    }
+                    for (name, route) in routes {
+                        println!("Orienteering {}- (budget {})", name, config.budget);
+                        println!("Prize: {}, cost: {}", route.prize, route.cost);
+                        println!("Path: {:?}", route.path);
+                        println!("Visited: {:?}", route.visited);
+                        println!("Skipped: {:?}", route.skipped);
+                    }
+                }
+                Err(e) => eprintln!("Error reading prizes: {}", e),
+            }
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            // one airport from every cluster, clusters come from a second csv. Both solvers run on the metric closure so a
+            // leg may fly through airports that weren't picked
+            match crate::csv_readers::read_clusters_csv("test-csv-sidecars/clusters.csv", distance_matrix.len()) {
+                Ok(cluster_of) => {
+                    let closure = crate::metric_closure::metric_closure(&distance_matrix);
+                    let config = crate::gtsp::GtspConfig::default();
+                    let mut routes = vec![("heuristic", crate::gtsp::gtsp_heuristic(&closure.distances, &cluster_of, &config))];
+                    match crate::gtsp::gtsp_held_karp(&closure.distances, &cluster_of, &config) {
+                        Ok(route) => routes.push(("exact DP", route)),
+                        Err(e) => eprintln!("Generalized TSP DP: {}", e),
+                    }
+                    for (name, route) in routes {
+                        let flown = closure.expand(&distance_matrix, &route.path);
+                        println!("Generalized TSP {}- ({} clusters)", name, crate::gtsp::cluster_lists(distance_matrix.len(), &cluster_of).len());
+                        println!("Minimum Cost: {}", flown.cost);
+                        println!("Path: {:?}", route.path);
+                        println!("Chosen per cluster: {:?}", route.chosen);
+                    }
+                }
+                Err(e) => eprintln!("Error reading clusters: {}", e),
+            }
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_hashmap(filename) {
+        Ok(distances) => {
+            let config = crate::algorithms::AntColonyConfig { seed: 42, ..Default::default() };
+            let result = crate::algorithms::ant_colony_system(&distances, &config);
+            println!("Ant colony system solution algorithm- (seed {})", config.seed);
+            println!("Minimum Cost: {}", result.cost);
+            println!("Optimal Path: {:?}", result.path);
+            println!("Failed ants: {} of {}", result.failed_ants, result.total_ants);
+        }
+        Err(e) => eprintln!("Error reading file: {}", e),
+    }
+
+    match crate::csv_readers::read_csv_as_matrix(filename) {
+        Ok(distance_matrix) => {
+            let config = crate::algorithms::AnnealingConfig { seed: 42, ..Default::default() };
+            let result = crate::algorithms::simulated_annealing(&distance_matrix, &config);
+            println!("Simulated annealing solution algorithm- (seed {})", config.seed);
+            println!("Minimum Cost: {}", result.cost);
+            println!("Optimal Path: {:?}", result.path);
+            let trace_step = (result.trace.len() / 10).max(1); // print about 10 points of the cooling curve
+            for point in result.trace.iter().step_by(trace_step) {
+                println!("  iteration {:>8} temperature {:>10.4} current {:>12.4} best {:>12.4}", point.iteration, point.temperature, point.current_cost, point.best_cost);
+            }
+            println!("Iterations: {}", result.iterations);
+        }
+        Err(e) => eprintln!("Error reading CSV: {}", e),
     }
-    println!("");
+    println!();
     crate::analyzer::algorithm_analyzer("Sparse graphs with 15 to 20 locations".to_string(), "test-csv-sparse".to_string(), 15,20); // needs table name, folder name for csv files, location lower bound, location upper bound
+    crate::analyzer::algorithm_analyzer("Sparse graphs with 21 to 60 locations".to_string(), "test-csv-sparse".to_string(), 21,60);
     crate::analyzer::algorithm_analyzer("Sparse graphs with 100 to 100 locations".to_string(), "test-csv-sparse".to_string(), 100,100);
     crate::analyzer::algorithm_analyzer("Full graphs with 100 to 100 locations".to_string(), "test-csv-full-world".to_string(), 100,100);
     crate::analyzer::algorithm_analyzer("Sparse graphs with 1000 to 1000 locations".to_string(), "test-csv-sparse".to_string(), 1000,1000);
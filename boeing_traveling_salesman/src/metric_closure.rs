@@ -0,0 +1,156 @@
+// Metric closure of a directed world, the cheapest cost between every pair of airports allowing stops in between.
+// Sparse worlds often have no Hamiltonian path, but an aircraft is allowed to fly through an airport it already
+// visited, so the solvers can run on the completed graph and every leg is expanded back into the arcs really flown.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+const FLOYD_WARSHALL_MAX_NODES: usize = 200; // above this n^3 loses to n Dijkstra runs on sparse worlds
+
+pub struct MetricClosure {
+    pub distances: Vec<Vec<f64>>, // shortest leg cost, infinity if unreachable (and on the diagonal like the csv matrix)
+    next_hop: Vec<Vec<usize>>,    // first airport after `from` on the shortest leg to `to`, usize::MAX if unreachable
+}
+
+pub struct RouteStop {
+    pub node: usize,
+    pub pass_through: bool, // flown through on a ferry leg, the planned visit to this airport is somewhere else
+}
+
+pub struct FerryRoute {
+    pub cost: f64,
+    pub visit_order: Vec<usize>, // the solver's path over the completed graph
+    pub stops: Vec<RouteStop>,   // every airport landed at in order, visits and pass-throughs
+}
+
+pub fn floyd_warshall(distance_matrix: &[Vec<f64>]) -> MetricClosure {
+    let n = distance_matrix.len();
+    let mut distances = distance_matrix.to_vec();
+    let mut next_hop = vec![vec![usize::MAX; n]; n];
+    for i in 0..n {
+        distances[i][i] = 0.0;
+        for j in 0..n {
+            if i != j && distances[i][j].is_finite() {
+                next_hop[i][j] = j;
+            }
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            if distances[i][k] == f64::INFINITY {
+                continue;
+            }
+            for j in 0..n {
+                let through_k = distances[i][k] + distances[k][j];
+                if through_k < distances[i][j] {
+                    distances[i][j] = through_k;
+                    next_hop[i][j] = next_hop[i][k];
+                }
+            }
+        }
+    }
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[i] = f64::INFINITY;
+    }
+    MetricClosure { distances, next_hop }
+}
+
+#[derive(PartialEq)]
+struct HeapEntry(f64, usize); // (cost so far, node), ordered so BinaryHeap pops the cheapest first
+
+impl Eq for HeapEntry {}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0).then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub fn dijkstra_all_pairs(distance_matrix: &[Vec<f64>]) -> MetricClosure {
+    // Dijkstra from every airport over adjacency lists, O(n E log n) instead of n^3 when the world is sparse
+    let n = distance_matrix.len();
+    let adjacency: Vec<Vec<(usize, f64)>> = distance_matrix.iter().enumerate()
+        .map(|(from, row)| row.iter().enumerate().filter(|&(to, cost)| to != from && cost.is_finite()).map(|(to, &cost)| (to, cost)).collect())
+        .collect();
+    let mut distances = vec![vec![f64::INFINITY; n]; n];
+    let mut next_hop = vec![vec![usize::MAX; n]; n];
+    for source in 0..n {
+        let row = &mut distances[source];
+        let hops = &mut next_hop[source];
+        row[source] = 0.0;
+        let mut heap = BinaryHeap::new();
+        heap.push(HeapEntry(0.0, source));
+        while let Some(HeapEntry(cost, node)) = heap.pop() {
+            if cost > row[node] {
+                continue; // stale entry
+            }
+            for &(to, arc_cost) in &adjacency[node] {
+                let new_cost = cost + arc_cost;
+                if new_cost < row[to] {
+                    row[to] = new_cost;
+                    hops[to] = if node == source { to } else { hops[node] };
+                    heap.push(HeapEntry(new_cost, to));
+                }
+            }
+        }
+        row[source] = f64::INFINITY;
+    }
+    MetricClosure { distances, next_hop }
+}
+
+pub fn metric_closure(distance_matrix: &[Vec<f64>]) -> MetricClosure {
+    if distance_matrix.len() <= FLOYD_WARSHALL_MAX_NODES {
+        floyd_warshall(distance_matrix)
+    } else {
+        dijkstra_all_pairs(distance_matrix)
+    }
+}
+
+impl MetricClosure {
+    pub fn leg(&self, from: usize, to: usize) -> Vec<usize> { // airports flown through from `from` to `to`, both ends included
+        let mut leg = vec![from];
+        let mut node = from;
+        while node != to {
+            node = self.next_hop[node][to];
+            if node == usize::MAX {
+                return Vec::new(); // unreachable
+            }
+            leg.push(node);
+        }
+        leg
+    }
+
+    pub fn expand(&self, distance_matrix: &[Vec<f64>], visit_order: &[usize]) -> FerryRoute {
+        // replaces every leg of a path over the completed graph with the real arcs, intermediate airports are pass-throughs
+        let mut stops: Vec<RouteStop> = visit_order.first().map(|&node| RouteStop { node, pass_through: false }).into_iter().collect();
+        for pair in visit_order.windows(2) {
+            let leg = self.leg(pair[0], pair[1]);
+            if leg.is_empty() {
+                return FerryRoute { cost: f64::INFINITY, visit_order: Vec::new(), stops: Vec::new() };
+            }
+            let last = leg.len() - 1;
+            stops.extend(leg.iter().enumerate().skip(1).map(|(k, &node)| RouteStop { node, pass_through: k != last }));
+        }
+        let cost = stops.windows(2).map(|arc| distance_matrix[arc[0].node][arc[1].node]).sum();
+        FerryRoute { cost, visit_order: visit_order.to_vec(), stops }
+    }
+}
+
+pub fn solve_with_ferry_legs<F>(distance_matrix: &[Vec<f64>], solver: F) -> FerryRoute
+where
+    F: FnOnce(&[Vec<f64>]) -> (f64, Vec<usize>),
+{
+    // runs any matrix solver on the metric closure and expands its path into the arcs really flown
+    let closure = metric_closure(distance_matrix);
+    let (cost, visit_order) = solver(&closure.distances);
+    if cost == f64::INFINITY || visit_order.is_empty() {
+        return FerryRoute { cost: f64::INFINITY, visit_order: Vec::new(), stops: Vec::new() };
+    }
+    closure.expand(distance_matrix, &visit_order)
+}
@@ -0,0 +1,347 @@
+// Mixed integer formulations of the open path problem, written out as LP or MPS files for an outside solver and
+// solved in-process with microlp for small worlds.
+// The path is turned into a closed tour with a dummy node (the last index) that has free arcs to and from every
+// location, the dummy's successor is the start and its predecessor the end. Only arcs that exist get an x variable.
+
+use std::fmt;
+use std::fs;
+use microlp::{ComparisonOp, OptimizationDirection, Problem};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Formulation {
+    Mtz,                   // Miller-Tucker-Zemlin order variables u
+    SingleCommodityFlow,   // the dummy ships one unit of flow to every location along the chosen arcs
+    SubtourCuts,           // degree constraints only, subtour elimination rows are added lazily while solving
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Lp,
+    Mps,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RowSense {
+    Le,
+    Eq,
+}
+
+pub struct MilpVariable {
+    pub name: String,
+    pub cost: f64,
+    pub lower: f64,
+    pub upper: f64,
+    pub integer: bool,
+}
+
+pub struct MilpRow {
+    pub name: String,
+    pub terms: Vec<(usize, f64)>, // variable index, coefficient
+    pub sense: RowSense,
+    pub rhs: f64,
+}
+
+pub struct MilpModel {
+    pub name: String,
+    pub nodes: usize,             // locations plus the dummy
+    pub arcs: Vec<(usize, usize)>, // arc of x variable k is arcs[k], the x variables come first
+    pub variables: Vec<MilpVariable>,
+    pub rows: Vec<MilpRow>,
+}
+
+pub struct MilpConfig {
+    pub max_nodes: usize, // the test worlds up to 100 locations solve in seconds, dense worlds need many more cut rounds
+    pub max_cut_rounds: usize,
+}
+
+impl Default for MilpConfig {
+    fn default() -> Self {
+        MilpConfig {
+            max_nodes: 120,
+            max_cut_rounds: 500,
+        }
+    }
+}
+
+pub struct MilpResult {
+    pub cost: f64,
+    pub path: Vec<usize>,
+    pub cut_rounds: usize, // solves needed until the arcs formed one tour
+    pub cuts: usize,       // subtour elimination rows added
+}
+
+#[derive(Debug)]
+pub enum MilpError {
+    TooManyNodes { nodes: usize, max: usize },
+    CutLimit { rounds: usize },
+    Solver(String),
+}
+
+impl fmt::Display for MilpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MilpError::TooManyNodes { nodes, max } => write!(f, "{} locations is more than the {} the MILP solve supports", nodes, max),
+            MilpError::CutLimit { rounds } => write!(f, "still had subtours after {} rounds of cuts", rounds),
+            MilpError::Solver(message) => write!(f, "MILP solver failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for MilpError {}
+
+pub fn build_model(distance_matrix: &[Vec<f64>], formulation: Formulation) -> MilpModel {
+    let n = distance_matrix.len();
+    let dummy = n;
+    let nodes = n + 1;
+    let mut arcs = Vec::new();
+    let mut variables = Vec::new();
+    for from in 0..nodes {
+        for to in 0..nodes {
+            let cost = distance_matrix.get(from).and_then(|row| row.get(to)).copied().unwrap_or(0.0); // dummy arcs are free
+            if from != to && cost.is_finite() {
+                arcs.push((from, to));
+                variables.push(MilpVariable { name: format!("x_{}_{}", from, to), cost, lower: 0.0, upper: 1.0, integer: true });
+            }
+        }
+    }
+    let mut rows = Vec::new();
+    for node in 0..nodes { // one arc out and one arc in everywhere
+        let out_terms = arcs.iter().enumerate().filter(|(_, arc)| arc.0 == node).map(|(k, _)| (k, 1.0)).collect();
+        rows.push(MilpRow { name: format!("out_{}", node), terms: out_terms, sense: RowSense::Eq, rhs: 1.0 });
+        let in_terms = arcs.iter().enumerate().filter(|(_, arc)| arc.1 == node).map(|(k, _)| (k, 1.0)).collect();
+        rows.push(MilpRow { name: format!("in_{}", node), terms: in_terms, sense: RowSense::Eq, rhs: 1.0 });
+    }
+    let big = (nodes - 1) as f64;
+    match formulation {
+        Formulation::Mtz => {
+            // u is the position after the dummy, u_i - u_j + (N - 1) x_ij <= N - 2 forces u_j >= u_i + 1 on used arcs
+            let order_start = variables.len();
+            for node in 0..n {
+                variables.push(MilpVariable { name: format!("u_{}", node), cost: 0.0, lower: 1.0, upper: big, integer: false });
+            }
+            for (k, &(from, to)) in arcs.iter().enumerate() {
+                if from != dummy && to != dummy {
+                    rows.push(MilpRow {
+                        name: format!("mtz_{}_{}", from, to),
+                        terms: vec![(order_start + from, 1.0), (order_start + to, -1.0), (k, big)],
+                        sense: RowSense::Le,
+                        rhs: big - 1.0,
+                    });
+                }
+            }
+        }
+        Formulation::SingleCommodityFlow => {
+            // N - 1 units leave the dummy, every location keeps one, flow only moves on chosen arcs
+            let flow_start = variables.len();
+            for &(from, to) in &arcs {
+                variables.push(MilpVariable { name: format!("f_{}_{}", from, to), cost: 0.0, lower: 0.0, upper: big, integer: false });
+            }
+            for (k, &(from, to)) in arcs.iter().enumerate() {
+                rows.push(MilpRow { name: format!("cap_{}_{}", from, to), terms: vec![(flow_start + k, 1.0), (k, -big)], sense: RowSense::Le, rhs: 0.0 });
+            }
+            for node in 0..nodes {
+                let mut terms: Vec<(usize, f64)> = Vec::new();
+                for (k, &(from, to)) in arcs.iter().enumerate() {
+                    if from == node {
+                        terms.push((flow_start + k, 1.0));
+                    } else if to == node {
+                        terms.push((flow_start + k, -1.0));
+                    }
+                }
+                let rhs = if node == dummy { big } else { -1.0 }; // out minus in
+                rows.push(MilpRow { name: format!("flow_{}", node), terms, sense: RowSense::Eq, rhs });
+            }
+        }
+        Formulation::SubtourCuts => {}
+    }
+    MilpModel { name: format!("tsp_{}", n), nodes, arcs, variables, rows }
+}
+
+fn format_terms(model: &MilpModel, terms: &[(usize, f64)]) -> String {
+    // LP files should keep lines short, so long sums are broken every few terms
+    let mut text = String::new();
+    for (i, &(variable, coefficient)) in terms.iter().enumerate() {
+        if i > 0 && i % 8 == 0 {
+            text.push_str("\n   ");
+        }
+        let sign = if coefficient < 0.0 { "-" } else if i > 0 { "+" } else { "" };
+        text.push_str(&format!(" {} {} {}", sign, coefficient.abs(), model.variables[variable].name));
+    }
+    if terms.is_empty() {
+        text.push_str(" 0");
+    }
+    text
+}
+
+pub fn write_lp(model: &MilpModel) -> String {
+    let mut text = format!("\\ {}: open path over {} locations, node {} is the dummy start and end\n", model.name, model.nodes - 1, model.nodes - 1);
+    let objective: Vec<(usize, f64)> = model.variables.iter().enumerate().filter(|(_, v)| v.cost != 0.0).map(|(k, v)| (k, v.cost)).collect();
+    text.push_str(&format!("Minimize\n obj:{}\nSubject To\n", format_terms(model, &objective)));
+    for row in &model.rows {
+        let sense = match row.sense {
+            RowSense::Le => "<=",
+            RowSense::Eq => "=",
+        };
+        text.push_str(&format!(" {}:{} {} {}\n", row.name, format_terms(model, &row.terms), sense, row.rhs));
+    }
+    text.push_str("Bounds\n");
+    for variable in model.variables.iter().filter(|v| !v.integer) {
+        text.push_str(&format!(" {} <= {} <= {}\n", variable.lower, variable.name, variable.upper));
+    }
+    text.push_str("Binaries\n");
+    for variable in model.variables.iter().filter(|v| v.integer) {
+        text.push_str(&format!(" {}\n", variable.name));
+    }
+    text.push_str("End\n");
+    text
+}
+
+pub fn write_mps(model: &MilpModel) -> String {
+    // free MPS, binaries are marked with BV bounds
+    let mut text = format!("NAME {}\nROWS\n N obj\n", model.name);
+    for row in &model.rows {
+        let sense = match row.sense {
+            RowSense::Le => "L",
+            RowSense::Eq => "E",
+        };
+        text.push_str(&format!(" {} {}\n", sense, row.name));
+    }
+    let mut column_entries: Vec<Vec<(&str, f64)>> = vec![Vec::new(); model.variables.len()];
+    for row in &model.rows {
+        for &(variable, coefficient) in &row.terms {
+            column_entries[variable].push((&row.name, coefficient));
+        }
+    }
+    text.push_str("COLUMNS\n");
+    for (variable, entries) in model.variables.iter().zip(&column_entries) {
+        if variable.cost != 0.0 {
+            text.push_str(&format!(" {} obj {}\n", variable.name, variable.cost));
+        }
+        for (row, coefficient) in entries {
+            text.push_str(&format!(" {} {} {}\n", variable.name, row, coefficient));
+        }
+    }
+    text.push_str("RHS\n");
+    for row in model.rows.iter().filter(|row| row.rhs != 0.0) {
+        text.push_str(&format!(" RHS {} {}\n", row.name, row.rhs));
+    }
+    text.push_str("BOUNDS\n");
+    for variable in &model.variables {
+        if variable.integer {
+            text.push_str(&format!(" BV BND {}\n", variable.name));
+        } else {
+            text.push_str(&format!(" LO BND {} {}\n UP BND {} {}\n", variable.name, variable.lower, variable.name, variable.upper));
+        }
+    }
+    text.push_str("ENDATA\n");
+    text
+}
+
+pub fn export(distance_matrix: &[Vec<f64>], formulation: Formulation, format: ExportFormat, file_path: &str) -> std::io::Result<()> {
+    let model = build_model(distance_matrix, formulation);
+    let text = match format {
+        ExportFormat::Lp => write_lp(&model),
+        ExportFormat::Mps => write_mps(&model),
+    };
+    fs::write(file_path, text)
+}
+
+pub fn solve_model(model: &MilpModel) -> Result<Option<Vec<f64>>, MilpError> {
+    // values of every variable at the optimum, None when the model is infeasible
+    let mut problem = Problem::new(OptimizationDirection::Minimize);
+    let handles: Vec<microlp::Variable> = model.variables.iter().map(|variable| {
+        if variable.integer && variable.lower == 0.0 && variable.upper == 1.0 {
+            problem.add_binary_var(variable.cost)
+        } else if variable.integer {
+            problem.add_integer_var(variable.cost, (variable.lower as i32, variable.upper as i32))
+        } else {
+            problem.add_var(variable.cost, (variable.lower, variable.upper))
+        }
+    }).collect();
+    for row in &model.rows {
+        let sense = match row.sense {
+            RowSense::Le => ComparisonOp::Le,
+            RowSense::Eq => ComparisonOp::Eq,
+        };
+        let terms: Vec<(microlp::Variable, f64)> = row.terms.iter().map(|&(variable, coefficient)| (handles[variable], coefficient)).collect();
+        problem.add_constraint(terms.as_slice(), sense, row.rhs);
+    }
+    match problem.solve() {
+        Ok(solution) => Ok(Some(handles.iter().map(|&handle| *solution.var_value(handle)).collect())),
+        Err(microlp::Error::Infeasible) => Ok(None),
+        Err(e) => Err(MilpError::Solver(e.to_string())),
+    }
+}
+
+fn successor_cycles(model: &MilpModel, values: &[f64]) -> Vec<Vec<usize>> {
+    // the chosen arcs form a permutation, split it into its cycles
+    let mut successor = vec![usize::MAX; model.nodes];
+    for (k, &(from, to)) in model.arcs.iter().enumerate() {
+        if values[k] > 0.5 {
+            successor[from] = to;
+        }
+    }
+    let mut seen = vec![false; model.nodes];
+    let mut cycles = Vec::new();
+    for start in 0..model.nodes {
+        if seen[start] {
+            continue;
+        }
+        let mut cycle = Vec::new();
+        let mut node = start;
+        while !seen[node] {
+            seen[node] = true;
+            cycle.push(node);
+            node = successor[node];
+        }
+        cycles.push(cycle);
+    }
+    cycles
+}
+
+pub fn path_from_solution(model: &MilpModel, values: &[f64]) -> Vec<usize> {
+    // walk the tour from the dummy, the dummy itself is left out. Empty if the arcs are not one tour
+    let cycles = successor_cycles(model, values);
+    if cycles.len() != 1 {
+        return Vec::new();
+    }
+    let tour = &cycles[0];
+    let dummy = model.nodes - 1;
+    let at = tour.iter().position(|&node| node == dummy).unwrap();
+    tour[at + 1..].iter().chain(&tour[..at]).copied().collect()
+}
+
+pub fn solve_with_subtour_cuts(distance_matrix: &[Vec<f64>], config: &MilpConfig) -> Result<MilpResult, MilpError> {
+    // Dantzig-Fulkerson-Johnson with lazy cuts: solve with only the degree rows, and every cycle that isn't the whole
+    // tour gets a row saying its locations can use at most |S| - 1 arcs among themselves, then solve again
+    let n = distance_matrix.len();
+    if n > config.max_nodes {
+        return Err(MilpError::TooManyNodes { nodes: n, max: config.max_nodes });
+    }
+    if n == 0 {
+        return Ok(MilpResult { cost: 0.0, path: Vec::new(), cut_rounds: 0, cuts: 0 });
+    }
+    let mut model = build_model(distance_matrix, Formulation::SubtourCuts);
+    let mut cuts = 0;
+    for round in 1..=config.max_cut_rounds {
+        let Some(values) = solve_model(&model)? else {
+            return Ok(MilpResult { cost: f64::INFINITY, path: Vec::new(), cut_rounds: round, cuts });
+        };
+        let cycles = successor_cycles(&model, &values);
+        if cycles.len() == 1 {
+            let path = path_from_solution(&model, &values);
+            let cost = path.windows(2).map(|pair| distance_matrix[pair[0]][pair[1]]).sum();
+            return Ok(MilpResult { cost, path, cut_rounds: round, cuts });
+        }
+        for cycle in cycles {
+            let mut inside = vec![false; model.nodes];
+            for &node in &cycle {
+                inside[node] = true;
+            }
+            let terms = model.arcs.iter().enumerate().filter(|(_, &(from, to))| inside[from] && inside[to]).map(|(k, _)| (k, 1.0)).collect();
+            model.rows.push(MilpRow { name: format!("subtour_{}", cuts), terms, sense: RowSense::Le, rhs: cycle.len() as f64 - 1.0 });
+            cuts += 1;
+        }
+    }
+    Err(MilpError::CutLimit { rounds: config.max_cut_rounds })
+}
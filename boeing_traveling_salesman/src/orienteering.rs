@@ -0,0 +1,300 @@
+// Prize-collecting routes (orienteering). Every location has a prize and the route may spend at most a cost budget, so
+// instead of visiting everything it picks the locations worth the most that it can reach in budget and skips the rest.
+// Useful when a full tour is impossible or too costly. The heuristic grows the route by the best prize per added cost
+// and then trades visited locations for better ones, the exact DP is Held-Karp's subset DP keeping every subset that
+// stays in budget and taking the one with the largest prize.
+
+use std::fmt;
+
+const ORIENTEERING_MAX_NODES: usize = 20; // (mask, last) costs are f64 so budget checks aren't off by f32 rounding
+const MAX_REMOVED: usize = 3; // the removal move takes out runs of up to this many stops
+const EPSILON: f64 = 1e-9;
+
+pub struct OrienteeringConfig {
+    pub depot: usize,
+    pub budget: f64,           // total cost the route may spend
+    pub return_to_depot: bool, // the leg back to the depot counts against the budget too
+}
+
+impl Default for OrienteeringConfig {
+    fn default() -> Self {
+        OrienteeringConfig {
+            depot: 0,
+            budget: f64::INFINITY, // collects everything it can reach
+            return_to_depot: false,
+        }
+    }
+}
+
+pub struct OrienteeringRoute {
+    pub path: Vec<usize>,    // depot first, and last too when returning
+    pub cost: f64,
+    pub prize: f64,          // prizes of the visited locations, the depot's included
+    pub visited: Vec<usize>, // sorted
+    pub skipped: Vec<usize>, // sorted
+}
+
+#[derive(Debug)]
+pub enum OrienteeringError {
+    DepotOutOfRange { depot: usize, nodes: usize },
+    NegativeBudget { budget: f64 }, // not even staying at the depot fits
+    TooManyNodes { nodes: usize, max: usize }, // exact DP only
+}
+
+impl fmt::Display for OrienteeringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrienteeringError::DepotOutOfRange { depot, nodes } => write!(f, "depot {} is outside a world of {} locations", depot, nodes),
+            OrienteeringError::NegativeBudget { budget } => write!(f, "budget {} doesn't even cover staying at the depot", budget),
+            OrienteeringError::TooManyNodes { nodes, max } => write!(f, "{} locations is more than the exact DP's {}", nodes, max),
+        }
+    }
+}
+
+impl std::error::Error for OrienteeringError {}
+
+fn check_config(locations: usize, config: &OrienteeringConfig) -> Result<(), OrienteeringError> {
+    if config.depot >= locations {
+        return Err(OrienteeringError::DepotOutOfRange { depot: config.depot, nodes: locations });
+    }
+    if config.budget < 0.0 {
+        return Err(OrienteeringError::NegativeBudget { budget: config.budget });
+    }
+    Ok(())
+}
+
+fn prize_of(prizes: &[f64], node: usize) -> f64 { // locations without a prize are worth nothing
+    prizes.get(node).copied().unwrap_or(0.0)
+}
+
+fn route_cost(distance_matrix: &[Vec<f64>], config: &OrienteeringConfig, route: &[usize]) -> f64 {
+    // route holds only the stops after the depot, the depot legs are added here
+    let mut last = config.depot;
+    let mut cost = 0.0;
+    for &node in route {
+        cost += distance_matrix[last][node];
+        last = node;
+    }
+    if config.return_to_depot && !route.is_empty() {
+        cost += distance_matrix[last][config.depot];
+    }
+    cost
+}
+
+fn finish_route(distance_matrix: &[Vec<f64>], prizes: &[f64], config: &OrienteeringConfig, route: &[usize]) -> OrienteeringRoute {
+    let n = distance_matrix.len();
+    let mut path = vec![config.depot];
+    path.extend_from_slice(route);
+    if config.return_to_depot && !route.is_empty() {
+        path.push(config.depot);
+    }
+    let mut on_route = vec![false; n];
+    for &node in &path {
+        on_route[node] = true;
+    }
+    let (visited, skipped): (Vec<usize>, Vec<usize>) = (0..n).partition(|&node| on_route[node]);
+    let prize = visited.iter().map(|&node| prize_of(prizes, node)).sum();
+    OrienteeringRoute { path, cost: route_cost(distance_matrix, config, route), prize, visited, skipped }
+}
+
+fn insertion_delta(distance_matrix: &[Vec<f64>], config: &OrienteeringConfig, route: &[usize], node: usize, j: usize) -> f64 {
+    // added cost of putting node in front of route[j] (or at the end), infinity if an arc is missing
+    let previous = if j == 0 { config.depot } else { route[j - 1] };
+    match route.get(j) {
+        Some(&next) => distance_matrix[previous][node] + distance_matrix[node][next] - distance_matrix[previous][next],
+        None if config.return_to_depot => {
+            let old_home = if route.is_empty() { 0.0 } else { distance_matrix[previous][config.depot] };
+            distance_matrix[previous][node] + distance_matrix[node][config.depot] - old_home
+        }
+        None => distance_matrix[previous][node],
+    }
+}
+
+fn cheapest_insertion(distance_matrix: &[Vec<f64>], config: &OrienteeringConfig, route: &[usize], cost: f64, node: usize) -> Option<(usize, f64)> {
+    // position and added cost of the cheapest spot for node that stays in budget
+    (0..=route.len())
+        .map(|j| (j, insertion_delta(distance_matrix, config, route, node, j)))
+        .filter(|&(_, delta)| delta.is_finite() && cost + delta <= config.budget + EPSILON)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+fn fill_route(distance_matrix: &[Vec<f64>], prizes: &[f64], config: &OrienteeringConfig, route: &mut Vec<usize>, cost: &mut f64, left_out: &[usize]) {
+    // keeps inserting the location with the best prize per added cost that still fits the budget. left_out is never
+    // inserted, those are the stops the removal move just took out
+    let n = distance_matrix.len();
+    let mut on_route = vec![false; n];
+    on_route[config.depot] = true;
+    for &node in route.iter() {
+        on_route[node] = true;
+    }
+    loop {
+        let mut best: Option<(f64, usize, usize, f64)> = None; // ratio, node, position, added cost
+        for node in (0..n).filter(|&node| !on_route[node] && !left_out.contains(&node) && prize_of(prizes, node) > 0.0) {
+            if let Some((j, delta)) = cheapest_insertion(distance_matrix, config, route, *cost, node) {
+                let ratio = prize_of(prizes, node) / delta.max(EPSILON);
+                if best.is_none_or(|(best_ratio, ..)| ratio > best_ratio) {
+                    best = Some((ratio, node, j, delta));
+                }
+            }
+        }
+        let Some((_, node, j, delta)) = best else {
+            return;
+        };
+        route.insert(j, node);
+        on_route[node] = true;
+        *cost += delta;
+    }
+}
+
+fn shorten_route(distance_matrix: &[Vec<f64>], config: &OrienteeringConfig, route: &mut Vec<usize>, cost: &mut f64) {
+    // moves single stops to the cheapest other spot in the route until none gets cheaper, leaving budget for more prizes
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..route.len() {
+            let mut rest = route.clone();
+            let node = rest.remove(i);
+            for j in (0..=rest.len()).filter(|&j| j != i) {
+                let mut candidate = rest.clone();
+                candidate.insert(j, node);
+                let candidate_cost = route_cost(distance_matrix, config, &candidate);
+                if candidate_cost < *cost - EPSILON {
+                    *route = candidate;
+                    *cost = candidate_cost;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn route_prize(prizes: &[f64], route: &[usize]) -> f64 {
+    route.iter().map(|&node| prize_of(prizes, node)).sum()
+}
+
+fn local_search(distance_matrix: &[Vec<f64>], prizes: &[f64], config: &OrienteeringConfig, route: &mut Vec<usize>, cost: &mut f64) {
+    // shorten the route, insert whatever fits now, and try taking out each run of 1 to MAX_REMOVED stops and filling the
+    // route again without them, once by prize per cost and once starting from each skipped location in turn. A refill
+    // is kept if the prize grows or stays the same for less cost
+    let n = distance_matrix.len();
+    'search: loop {
+        shorten_route(distance_matrix, config, route, cost);
+        let before = route.len();
+        fill_route(distance_matrix, prizes, config, route, cost, &[]);
+        if route.len() > before {
+            continue;
+        }
+        let prize = route_prize(prizes, route);
+        let mut on_route = vec![false; n];
+        for &node in route.iter().chain([config.depot].iter()) {
+            on_route[node] = true;
+        }
+        for removed_count in 1..=MAX_REMOVED.min(route.len()) {
+            for i in 0..=route.len() - removed_count {
+                let mut shortened = route.clone();
+                let removed: Vec<usize> = shortened.drain(i..i + removed_count).collect();
+                let shortened_cost = route_cost(distance_matrix, config, &shortened);
+                if !shortened_cost.is_finite() { // no arc to bridge the gap
+                    continue;
+                }
+                let forced = (0..n).filter(|&node| !on_route[node] && prize_of(prizes, node) > 0.0);
+                for first in [None].into_iter().chain(forced.map(Some)) {
+                    let mut candidate = shortened.clone();
+                    let mut candidate_cost = shortened_cost;
+                    if let Some(node) = first {
+                        let Some((j, delta)) = cheapest_insertion(distance_matrix, config, &candidate, candidate_cost, node) else {
+                            continue;
+                        };
+                        candidate.insert(j, node);
+                        candidate_cost += delta;
+                    }
+                    fill_route(distance_matrix, prizes, config, &mut candidate, &mut candidate_cost, &removed);
+                    let candidate_prize = route_prize(prizes, &candidate);
+                    if candidate_prize > prize + EPSILON || (candidate_prize > prize - EPSILON && candidate_cost < *cost - EPSILON) {
+                        *route = candidate;
+                        *cost = candidate_cost;
+                        continue 'search;
+                    }
+                }
+            }
+        }
+        return;
+    }
+}
+
+pub fn orienteering_heuristic(distance_matrix: &[Vec<f64>], prizes: &[f64], config: &OrienteeringConfig) -> Result<OrienteeringRoute, OrienteeringError> {
+    // insertion by prize per added cost and the local search, once from the depot alone and once more from every first
+    // leg out of it that fits the budget since sparse worlds often only open up from the right first stop
+    let n = distance_matrix.len();
+    check_config(n, config)?;
+    let first_legs = (0..n).filter(|&node| node != config.depot && route_cost(distance_matrix, config, &[node]) <= config.budget + EPSILON);
+    let mut best: (f64, f64, Vec<usize>) = (f64::NEG_INFINITY, f64::INFINITY, Vec::new()); // prize, cost, route
+    for start in [Vec::new()].into_iter().chain(first_legs.map(|node| vec![node])) {
+        let mut route = start;
+        let mut cost = route_cost(distance_matrix, config, &route);
+        fill_route(distance_matrix, prizes, config, &mut route, &mut cost, &[]);
+        local_search(distance_matrix, prizes, config, &mut route, &mut cost);
+        let prize = route_prize(prizes, &route);
+        if prize > best.0 + EPSILON || (prize > best.0 - EPSILON && cost < best.1 - EPSILON) {
+            best = (prize, cost, route);
+        }
+    }
+    Ok(finish_route(distance_matrix, prizes, config, &best.2))
+}
+
+pub fn orienteering_held_karp(distance_matrix: &[Vec<f64>], prizes: &[f64], config: &OrienteeringConfig) -> Result<OrienteeringRoute, OrienteeringError> {
+    // Held-Karp's (visited set, last node) DP from the depot, which stays out of the masks. States over budget are never
+    // extended since costs only grow, and every state within budget (with the leg home if returning) is a candidate
+    // route: the most prize wins, ties go to the cheaper one
+    let n = distance_matrix.len();
+    check_config(n, config)?;
+    if n > ORIENTEERING_MAX_NODES {
+        return Err(OrienteeringError::TooManyNodes { nodes: n, max: ORIENTEERING_MAX_NODES });
+    }
+    let free: Vec<usize> = (0..n).filter(|&node| node != config.depot).collect();
+    let m = free.len();
+    let mut costs = vec![f64::INFINITY; m << m]; // index mask * m + last
+    let mut parents = vec![u8::MAX; m << m];
+    for (i, &node) in free.iter().enumerate() {
+        costs[(1 << i) * m + i] = distance_matrix[config.depot][node];
+    }
+    let mut mask_prizes = vec![0.0; 1 << m];
+    for mask in 1..(1usize << m) {
+        let lowest = mask.trailing_zeros() as usize;
+        mask_prizes[mask] = mask_prizes[mask & (mask - 1)] + prize_of(prizes, free[lowest]);
+    }
+    let home = |last: usize| if config.return_to_depot { distance_matrix[free[last]][config.depot] } else { 0.0 };
+    let mut best = (prize_of(prizes, config.depot), 0.0, 0usize, 0usize); // prize, cost, mask, last. The empty route
+    for mask in 1..(1usize << m) {
+        for last in (0..m).filter(|&last| mask & (1 << last) != 0) {
+            let cost = costs[mask * m + last];
+            if cost > config.budget + EPSILON {
+                continue;
+            }
+            let total = cost + home(last);
+            let prize = prize_of(prizes, config.depot) + mask_prizes[mask];
+            if total <= config.budget + EPSILON && (prize > best.0 + EPSILON || (prize > best.0 - EPSILON && total < best.1 - EPSILON)) {
+                best = (prize, total, mask, last);
+            }
+            for next in (0..m).filter(|&next| mask & (1 << next) == 0) {
+                let target = (mask | (1 << next)) * m + next;
+                let next_cost = cost + distance_matrix[free[last]][free[next]];
+                if next_cost < costs[target] {
+                    costs[target] = next_cost;
+                    parents[target] = last as u8;
+                }
+            }
+        }
+    }
+    let (_, _, mut mask, mut last) = best;
+    let mut route = Vec::new();
+    while mask != 0 {
+        route.push(free[last]);
+        let parent = parents[mask * m + last];
+        mask &= !(1 << last);
+        last = parent as usize;
+    }
+    route.reverse();
+    Ok(finish_route(distance_matrix, prizes, config, &route))
+}
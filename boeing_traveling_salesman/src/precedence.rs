@@ -0,0 +1,93 @@
+// Precedence constraints. A pair (before, after) of location indexes means before has to be visited ahead of after, like
+// pickup before delivery or survey before inspection. Held-Karp and simulated annealing take the pairs in their configs,
+// this file has the check and a nearest neighbor plus Or-opt that never break a pair
+
+const MAX_SEGMENT: usize = 3; // Or-opt moves chains of up to this many locations
+
+pub fn broken_pairs(path: &[usize], precedences: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // pairs the path visits out of order, a pair with a location the path doesn't visit isn't counted here
+    let mut position = vec![usize::MAX; path.iter().max().map_or(0, |&node| node + 1)];
+    for (k, &node) in path.iter().enumerate() {
+        position[node] = k;
+    }
+    let position_of = |node: usize| position.get(node).copied().unwrap_or(usize::MAX);
+    precedences.iter().copied()
+        .filter(|&(before, after)| position_of(before) != usize::MAX && position_of(after) != usize::MAX && position_of(before) >= position_of(after))
+        .collect()
+}
+
+fn path_cost(distance_matrix: &[Vec<f64>], path: &[usize]) -> f64 {
+    path.windows(2).map(|arc| distance_matrix[arc[0]][arc[1]]).sum()
+}
+
+pub fn precedence_nearest_neighbor(distance_matrix: &[Vec<f64>], precedences: &[(usize, usize)]) -> (f64, Vec<usize>) {
+    // nearest neighbor from every start that has no predecessor, only ever flying to a location whose predecessors have
+    // all been visited. A start that dead ends is dropped, infinity and an empty path if every start does
+    let n = distance_matrix.len();
+    let mut waiting_on = vec![0usize; n]; // predecessors not visited yet
+    let mut successors = vec![Vec::new(); n];
+    for &(before, after) in precedences.iter().filter(|&&(before, after)| before < n && after < n) {
+        waiting_on[after] += 1;
+        successors[before].push(after);
+    }
+    let mut best = (f64::INFINITY, Vec::new());
+    for start in (0..n).filter(|&start| waiting_on[start] == 0) {
+        let mut waiting = waiting_on.clone();
+        let mut visited = vec![false; n];
+        let mut path: Vec<usize> = Vec::with_capacity(n);
+        let mut cost = 0.0;
+        let mut next = Some(start);
+        while let Some(node) = next {
+            if let Some(&last) = path.last() {
+                cost += distance_matrix[last][node];
+            }
+            visited[node] = true;
+            path.push(node);
+            for &after in &successors[node] {
+                waiting[after] -= 1;
+            }
+            next = (0..n)
+                .filter(|&to| !visited[to] && waiting[to] == 0 && distance_matrix[node][to].is_finite())
+                .min_by(|&a, &b| distance_matrix[node][a].total_cmp(&distance_matrix[node][b]));
+        }
+        if path.len() == n && cost < best.0 {
+            best = (cost, path);
+        }
+    }
+    best
+}
+
+pub fn precedence_or_opt(distance_matrix: &[Vec<f64>], precedences: &[(usize, usize)], path: &[usize]) -> (f64, Vec<usize>) {
+    // moves every chain of 1 to MAX_SEGMENT locations to its cheapest place that keeps every pair, passes until no chain
+    // moves. An empty path comes back as infinity
+    if path.is_empty() {
+        return (f64::INFINITY, Vec::new());
+    }
+    let mut path = path.to_vec();
+    let mut cost = path_cost(distance_matrix, &path);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for length in 1..=MAX_SEGMENT.min(path.len() - 1) {
+            for start in 0..=path.len() - length {
+                let mut rest = path.clone();
+                let segment: Vec<usize> = rest.drain(start..start + length).collect();
+                let mut best: Option<(f64, Vec<usize>)> = None;
+                for insert_at in (0..=rest.len()).filter(|&insert_at| insert_at != start) {
+                    let mut candidate = rest.clone();
+                    candidate.splice(insert_at..insert_at, segment.iter().copied());
+                    let candidate_cost = path_cost(distance_matrix, &candidate);
+                    if candidate_cost < best.as_ref().map_or(cost, |(best_cost, _)| *best_cost) - 1e-9 && broken_pairs(&candidate, precedences).is_empty() {
+                        best = Some((candidate_cost, candidate));
+                    }
+                }
+                if let Some((best_cost, best_path)) = best {
+                    path = best_path;
+                    cost = best_cost;
+                    improved = true;
+                }
+            }
+        }
+    }
+    (cost, path)
+}
@@ -0,0 +1,54 @@
+// Mandatory and optional locations (Steiner TSP). Only the required airports have to be visited, the optional ones are
+// transit points a route may fly through or leave out. Any matrix solver runs on the metric closure between the required
+// airports alone and every leg is expanded back into the arcs really flown, so optional airports show up as
+// pass-throughs. An exact solver on the closure stays exact, the cheapest walk covering the required airports is a
+// cheapest order of them with a shortest leg between each pair.
+
+use crate::metric_closure::{metric_closure, RouteStop};
+
+pub struct SteinerRoute {
+    pub cost: f64,
+    pub visit_order: Vec<usize>,    // the required airports in the order the solver visits them
+    pub stops: Vec<RouteStop>,      // every airport landed at in order, optional ones and revisits are pass-throughs
+    pub optional_flown: Vec<usize>, // sorted, optional airports the route flies through
+}
+
+fn failed_route() -> SteinerRoute {
+    SteinerRoute { cost: f64::INFINITY, visit_order: Vec::new(), stops: Vec::new(), optional_flown: Vec::new() }
+}
+
+pub fn is_required(required: &[bool], node: usize) -> bool { // locations without a flag have to be visited, like a plain world
+    required.get(node).copied().unwrap_or(true)
+}
+
+pub fn required_nodes(distance_matrix: &[Vec<f64>], required: &[bool]) -> Vec<usize> {
+    (0..distance_matrix.len()).filter(|&node| is_required(required, node)).collect()
+}
+
+pub fn solve_steiner<F>(distance_matrix: &[Vec<f64>], required: &[bool], solver: F) -> SteinerRoute
+where
+    F: FnOnce(&[Vec<f64>]) -> (f64, Vec<usize>),
+{
+    // the solver sees a world of the required airports only (indexed in order), priced by the shortest legs through
+    // the whole world. Fewer than two required airports need no solver
+    let nodes = required_nodes(distance_matrix, required);
+    let closure = metric_closure(distance_matrix);
+    let visit_order = if nodes.len() < 2 {
+        nodes
+    } else {
+        let reduced: Vec<Vec<f64>> = nodes.iter().map(|&from| nodes.iter().map(|&to| closure.distances[from][to]).collect()).collect();
+        let (cost, path) = solver(&reduced);
+        if cost == f64::INFINITY || path.len() != nodes.len() {
+            return failed_route();
+        }
+        path.iter().map(|&k| nodes[k]).collect()
+    };
+    let route = closure.expand(distance_matrix, &visit_order);
+    if route.cost == f64::INFINITY {
+        return failed_route();
+    }
+    let mut optional_flown: Vec<usize> = route.stops.iter().map(|stop| stop.node).filter(|&node| !is_required(required, node)).collect();
+    optional_flown.sort_unstable();
+    optional_flown.dedup();
+    SteinerRoute { cost: route.cost, visit_order: route.visit_order, stops: route.stops, optional_flown }
+}
@@ -1,17 +1,21 @@
 
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests{   
 
-    use crate::*;
     use std::fs;
+    use std::collections::HashMap;
     use bitvec::prelude::*;
+    use crate::algorithms::{HeldKarpConfig, HeldKarpError, HeldKarpMode};
+
+    type Distances = HashMap<usize, HashMap<usize, f64>>;
     //use std::path::Path;
     //use std::error::Error;
 
-    fn is_valid_route(distance_matrix: &Vec<Vec<f64>>, cost: &f64, path: &Vec<usize>) -> String {
-        if path.len() == 0 {
-            return format!("No path found, path = []");
+    fn is_valid_route(distance_matrix: &[Vec<f64>], cost: &f64, path: &[usize]) -> String {
+        if path.is_empty() {
+            return "No path found, path = []".to_string();
         }
         let mut visited = bitvec![0; distance_matrix.len()]; // keeps track of which nodes are visited
         visited.set(path[0], true); //visit start
@@ -37,7 +41,355 @@ mod tests{
             return format!("Cost was {} but algorithm returned {}", cost_check, cost);
         }
 
-        return format!("valid"); //  returns valid if path had no issues
+        "valid".to_string() //  returns valid if path had no issues
+    }
+    fn is_valid_precedence_route(distance_matrix: &[Vec<f64>], cost: &f64, path: &[usize], precedences: &[(usize, usize)]) -> String {
+        // a valid route over every location that also visits the first location of each pair ahead of the second
+        let check = is_valid_route(distance_matrix, cost, path);
+        if check != "valid" {
+            return check;
+        }
+        if path.len() != distance_matrix.len() {
+            return format!("Path {:?} misses locations", path);
+        }
+        for &(before, after) in precedences {
+            let position = |node: usize| path.iter().position(|&visit| visit == node).unwrap();
+            if position(before) > position(after) {
+                return format!("Visited {} before {} but the precedence says {} first, path {:?}", after, before, before, path);
+            }
+        }
+        "valid".to_string()
+    }
+    fn check_arcs(distance_matrix: &[Vec<f64>], path: &[usize], cost: f64) -> String {
+        // every arc of the path exists and the arcs add up to the reported cost
+        let mut cost_check = 0.0;
+        for arc in path.windows(2) {
+            if distance_matrix[arc[0]][arc[1]] == f64::INFINITY {
+                return format!("No arc from {} to {}, in path {:?}", arc[0], arc[1], path);
+            }
+            cost_check += distance_matrix[arc[0]][arc[1]];
+        }
+        if (cost_check - cost).abs() > 1e-6 {
+            return format!("Path {:?} cost was {} but the algorithm returned {}", path, cost_check, cost);
+        }
+        "valid".to_string()
+    }
+    fn check_visited_once(visits: &[usize], expected: impl IntoIterator<Item = usize>) -> String {
+        // the visits are exactly the expected locations, each one once
+        let mut sorted = visits.to_vec();
+        sorted.sort_unstable();
+        let mut expected: Vec<usize> = expected.into_iter().collect();
+        expected.sort_unstable();
+        if sorted != expected {
+            return format!("Visits {:?} should cover {:?} exactly once", visits, expected);
+        }
+        "valid".to_string()
+    }
+    fn is_valid_fleet(distance_matrix: &[Vec<f64>], plan: &crate::fleet::FleetPlan, depots: &[usize], return_to_depot: bool) -> String {
+        is_valid_mixed_fleet(&vec![distance_matrix.to_vec(); depots.len()], plan, depots, return_to_depot)
+    }
+    fn is_valid_mixed_fleet(aircraft_matrices: &[Vec<Vec<f64>>], plan: &crate::fleet::FleetPlan, depots: &[usize], return_to_depot: bool) -> String {
+        // every route starts at its depot and flies at its own aircraft's costs, across the fleet every location that
+        // isn't a depot is a stop exactly once and the plan totals match the routes
+        if plan.routes.len() != depots.len() {
+            return format!("{} routes for {} aircraft", plan.routes.len(), depots.len());
+        }
+        let mut stops = Vec::new();
+        for ((route, &depot), distance_matrix) in plan.routes.iter().zip(depots).zip(aircraft_matrices) {
+            if route.path.first() != Some(&depot) || route.depot != depot {
+                return format!("Route {:?} does not start at depot {}", route.path, depot);
+            }
+            if return_to_depot && route.path.len() > 1 {
+                if route.path.last() != Some(&depot) {
+                    return format!("Route {:?} does not return to depot {}", route.path, depot);
+                }
+                stops.extend_from_slice(&route.path[1..route.path.len() - 1]);
+            } else {
+                stops.extend_from_slice(&route.path[1..]);
+            }
+            let check = check_arcs(distance_matrix, &route.path, route.cost);
+            if check != "valid" {
+                return check;
+            }
+        }
+        let check = check_visited_once(&stops, (0..aircraft_matrices[0].len()).filter(|node| !depots.contains(node)));
+        if check != "valid" {
+            return check;
+        }
+        let total: f64 = plan.routes.iter().map(|route| route.cost).sum();
+        let longest = plan.routes.iter().fold(0.0, |a: f64, route| a.max(route.cost));
+        if (total - plan.total_cost).abs() > 1e-6 || (longest - plan.max_route_cost).abs() > 1e-6 {
+            return format!("Plan totals {} and {} do not match the routes {} and {}", plan.total_cost, plan.max_route_cost, total, longest);
+        }
+        "valid".to_string()
+    }
+    fn is_valid_orienteering(distance_matrix: &[Vec<f64>], prizes: &[f64], config: &crate::orienteering::OrienteeringConfig, route: &crate::orienteering::OrienteeringRoute) -> String {
+        // from the depot (back to it if asked) within budget, with the reported prize, and visited plus skipped
+        // splitting the world between them
+        let path = &route.path;
+        if path.first() != Some(&config.depot) {
+            return format!("Route {:?} does not start at depot {}", path, config.depot);
+        }
+        let stops = if config.return_to_depot && path.len() > 1 {
+            if path.last() != Some(&config.depot) {
+                return format!("Route {:?} does not return to depot {}", path, config.depot);
+            }
+            &path[..path.len() - 1]
+        } else {
+            &path[..]
+        };
+        for check in [check_arcs(distance_matrix, path, route.cost), check_visited_once(stops, route.visited.iter().copied())] {
+            if check != "valid" {
+                return check;
+            }
+        }
+        if route.cost > config.budget + 1e-6 {
+            return format!("Route {:?} costs {} over the budget {}", path, route.cost, config.budget);
+        }
+        let prize_check: f64 = route.visited.iter().map(|&node| prizes[node]).sum();
+        if (prize_check - route.prize).abs() > 1e-6 {
+            return format!("Route {:?} prize was {} but the route says {}", path, prize_check, route.prize);
+        }
+        let skipped: Vec<usize> = (0..distance_matrix.len()).filter(|node| !route.visited.contains(node)).collect();
+        if route.skipped != skipped {
+            return format!("Skipped {:?} should be {:?}", route.skipped, skipped);
+        }
+        "valid".to_string()
+    }
+    fn is_fuel_feasible(distance_matrix: &[Vec<f64>], limits: &crate::fuel::FuelLimits, route: &crate::fuel::FuelRoute) -> String {
+        // flies the route with a full tank from the first stop: no leg above the max leg cost, the tank never below empty,
+        // refills only at refuel airports, and the planned visits cover every location once
+        if route.stops.is_empty() {
+            return "No route found, stops = []".to_string();
+        }
+        let visits: Vec<usize> = route.stops.iter().filter(|stop| !stop.refuel_stop).map(|stop| stop.node).collect();
+        if visits != route.visit_order {
+            return format!("Visits {:?} do not match the visit order {:?}", visits, route.visit_order);
+        }
+        for check in [check_arcs(distance_matrix, &route.stops.iter().map(|stop| stop.node).collect::<Vec<_>>(), route.cost), check_visited_once(&visits, 0..distance_matrix.len())] {
+            if check != "valid" {
+                return check;
+            }
+        }
+        let mut fuel = limits.endurance;
+        for (k, stop) in route.stops.iter().enumerate() {
+            if k > 0 {
+                let from = route.stops[k - 1].node;
+                let leg = distance_matrix[from][stop.node];
+                if leg > limits.max_leg_cost {
+                    return format!("Leg {} to {} costs {} but the max leg is {}", from, stop.node, leg, limits.max_leg_cost);
+                }
+                fuel -= leg;
+                if fuel < -1e-9 {
+                    return format!("Out of fuel flying {} to {}, {} short", from, stop.node, -fuel);
+                }
+            }
+            let refuels = limits.refuel_nodes.contains(&stop.node);
+            if stop.refuel_stop && !refuels {
+                return format!("Refuel stop at {} which has no fuel", stop.node);
+            }
+            if refuels {
+                fuel = limits.endurance;
+            }
+            if (fuel - stop.fuel).abs() > 1e-6 {
+                return format!("Fuel at {} is {} but the route says {}", stop.node, fuel, stop.fuel);
+            }
+        }
+        "valid".to_string()
+    }
+    fn is_valid_schedule(distance_matrix: &[Vec<f64>], windows: &[crate::time_windows::TimeWindow], schedule: &crate::time_windows::Schedule, strict: bool) -> String {
+        // flies the path from time 0 as early as possible and checks every stop's times, the totals, the cost and coverage.
+        // Strict schedules can't arrive after any window
+        let check = is_valid_route(distance_matrix, &schedule.cost, &schedule.path);
+        if check != "valid" {
+            return check;
+        }
+        if schedule.stops.iter().map(|stop| stop.node).collect::<Vec<_>>() != schedule.path {
+            return format!("Stops do not follow the path {:?}", schedule.path);
+        }
+        let mut time = 0.0;
+        let (mut total_wait, mut total_lateness) = (0.0, 0.0);
+        for (k, stop) in schedule.stops.iter().enumerate() {
+            let window = &windows[stop.node];
+            let arrival = if k == 0 { window.earliest.max(0.0) } else { time + distance_matrix[schedule.stops[k - 1].node][stop.node] };
+            let wait = (window.earliest - arrival).max(0.0);
+            let lateness = (arrival - window.latest).max(0.0);
+            let departure = arrival + wait + window.service;
+            if (arrival - stop.arrival).abs() > 1e-6 || (departure - stop.departure).abs() > 1e-6 {
+                return format!("Stop {} arrives {} and departs {} but the schedule says {} and {}", stop.node, arrival, departure, stop.arrival, stop.departure);
+            }
+            if (wait - stop.wait).abs() > 1e-6 || (lateness - stop.lateness).abs() > 1e-6 {
+                return format!("Stop {} waits {} and is {} late but the schedule says {} and {}", stop.node, wait, lateness, stop.wait, stop.lateness);
+            }
+            if strict && lateness > 0.0 {
+                return format!("Strict schedule arrives at {} {} late", stop.node, lateness);
+            }
+            total_wait += wait;
+            total_lateness += lateness;
+            time = departure;
+        }
+        if (total_wait - schedule.total_wait).abs() > 1e-6 || (total_lateness - schedule.total_lateness).abs() > 1e-6 || (time - schedule.completion_time).abs() > 1e-6 {
+            return format!("Totals {} waiting, {} late, done at {} but the schedule says {}, {}, {}", total_wait, total_lateness, time, schedule.total_wait, schedule.total_lateness, schedule.completion_time);
+        }
+        "valid".to_string()
+    }
+    fn is_valid_cvrp(distance_matrix: &[Vec<f64>], demands: &[f64], config: &crate::cvrp::CvrpConfig, solution: &crate::cvrp::CvrpSolution) -> String {
+        // every route flies its deliveries out of and back to the depot within capacity, with a load profile that drops
+        // by each delivery, and the deliveries cover every location but the depot exactly once
+        let mut delivered = Vec::new();
+        for route in &solution.routes {
+            let visits: Vec<usize> = route.stops.iter().filter(|stop| !stop.pass_through).map(|stop| stop.node).collect();
+            if visits != route.path || route.path.first() != Some(&config.depot) || route.path.last() != Some(&config.depot) || route.path.len() < 3 {
+                return format!("Route {:?} doesn't fly its deliveries {:?} out of and back to the depot", visits, route.path);
+            }
+            let check = check_arcs(distance_matrix, &route.stops.iter().map(|stop| stop.node).collect::<Vec<_>>(), route.cost);
+            if check != "valid" {
+                return check;
+            }
+            let deliveries = &route.path[1..route.path.len() - 1];
+            let load: f64 = deliveries.iter().map(|&node| demands[node]).sum();
+            if (load - route.load).abs() > 1e-6 || load > config.capacity + 1e-9 {
+                return format!("Route {:?} carries {} (says {}) with capacity {}", route.path, load, route.load, config.capacity);
+            }
+            let mut on_board = load;
+            for (k, (stop, &profile)) in route.stops.iter().zip(&route.load_profile).enumerate() {
+                if k > 0 && k < route.stops.len() - 1 && !stop.pass_through {
+                    on_board -= demands[stop.node];
+                }
+                if (on_board - profile).abs() > 1e-6 {
+                    return format!("Leaving {} with {} on board but the profile says {}", stop.node, on_board, profile);
+                }
+            }
+            delivered.extend_from_slice(deliveries);
+        }
+        let check = check_visited_once(&delivered, (0..distance_matrix.len()).filter(|&node| node != config.depot));
+        if check != "valid" {
+            return check;
+        }
+        let total: f64 = solution.routes.iter().map(|route| route.cost).sum();
+        if (total - solution.total_cost).abs() > 1e-6 {
+            return format!("Total was {} but the solution says {}", total, solution.total_cost);
+        }
+        "valid".to_string()
+    }
+    fn closed_tour_cost(distance_matrix: &[Vec<f64>], tour: &[usize]) -> f64 {
+        (0..tour.len()).map(|k| distance_matrix[tour[k]][tour[(k + 1) % tour.len()]]).sum()
+    }
+    fn check_pass_through_route(distance_matrix: &[Vec<f64>], cost: f64, visit_order: &[usize], stops: &[crate::metric_closure::RouteStop], expected: &[usize]) -> String {
+        // ferry and required airport routes: the stops that aren't pass-throughs follow the visit order and are exactly
+        // the expected airports
+        let visits: Vec<usize> = stops.iter().filter(|stop| !stop.pass_through).map(|stop| stop.node).collect();
+        if visits != visit_order {
+            return "visits don't follow the visit order".to_string();
+        }
+        for check in [check_arcs(distance_matrix, &stops.iter().map(|stop| stop.node).collect::<Vec<_>>(), cost), check_visited_once(&visits, expected.iter().copied())] {
+            if check != "valid" {
+                return check;
+            }
+        }
+        "valid".to_string()
+    }
+    fn check_ferry_route(distance_matrix: &[Vec<f64>], route: &crate::metric_closure::FerryRoute) -> String {
+        // every airport is visited, the other stops are pass-throughs
+        check_pass_through_route(distance_matrix, route.cost, &route.visit_order, &route.stops, &(0..distance_matrix.len()).collect::<Vec<usize>>())
+    }
+    fn check_steiner_route(distance_matrix: &[Vec<f64>], required: &[bool], route: &crate::steiner::SteinerRoute) -> String {
+        // every required airport is visited, optional airports are only flown through and optional_flown lists them
+        let check = check_pass_through_route(distance_matrix, route.cost, &route.visit_order, &route.stops, &crate::steiner::required_nodes(distance_matrix, required));
+        if check != "valid" {
+            return check;
+        }
+        let mut optional: Vec<usize> = route.stops.iter().map(|stop| stop.node).filter(|&node| !crate::steiner::is_required(required, node)).collect();
+        optional.sort();
+        optional.dedup();
+        if optional != route.optional_flown {
+            return format!("optional airports flown {:?} but the route says {:?}", optional, route.optional_flown);
+        }
+        "valid".to_string()
+    }
+    fn is_valid_gtsp(distance_matrix: &[Vec<f64>], cluster_of: &[usize], route: &crate::gtsp::GtspRoute) -> String {
+        // one location of every cluster, with the reported chosen locations
+        let check = check_arcs(distance_matrix, &route.path, route.cost);
+        if check != "valid" {
+            return check;
+        }
+        let clusters = crate::gtsp::cluster_lists(distance_matrix.len(), cluster_of);
+        let mut chosen = vec![usize::MAX; clusters.len()];
+        for &node in &route.path {
+            let Some(c) = clusters.iter().position(|members| members.contains(&node)) else {
+                return format!("Location {} is in no cluster, in route {:?}", node, route.path);
+            };
+            if chosen[c] != usize::MAX {
+                return format!("Cluster {} visited twice, in route {:?}", c, route.path);
+            }
+            chosen[c] = node;
+        }
+        if chosen.contains(&usize::MAX) || chosen != route.chosen {
+            return format!("Route {:?} chose {:?} but the route says {:?}", route.path, chosen, route.chosen);
+        }
+        "valid".to_string()
+    }
+    fn brute_force_precedence(distance_matrix: &[Vec<f64>], precedences: &[(usize, usize)]) -> f64 {
+        // cheapest open path keeping every pair, tries every order (small worlds only)
+        fn search(distance_matrix: &[Vec<f64>], precedences: &[(usize, usize)], path: &mut Vec<usize>, cost: f64, best: &mut f64) {
+            let n = distance_matrix.len();
+            if path.len() == n {
+                *best = best.min(cost);
+                return;
+            }
+            for node in 0..n {
+                let released = precedences.iter().all(|&(before, after)| after != node || path.contains(&before));
+                if path.contains(&node) || !released {
+                    continue;
+                }
+                let arc = path.last().map_or(0.0, |&last| distance_matrix[last][node]);
+                path.push(node);
+                search(distance_matrix, precedences, path, cost + arc, best);
+                path.pop();
+            }
+        }
+        let mut best = f64::INFINITY;
+        search(distance_matrix, precedences, &mut Vec::new(), 0.0, &mut best);
+        best
+    }
+    fn brute_force_prize(distance_matrix: &[Vec<f64>], prizes: &[f64], config: &crate::orienteering::OrienteeringConfig) -> f64 {
+        // largest prize of any route from the depot within budget, tries every simple path (small worlds only)
+        fn search(distance_matrix: &[Vec<f64>], prizes: &[f64], config: &crate::orienteering::OrienteeringConfig, path: &mut Vec<usize>, cost: f64, best: &mut f64) {
+            let last = *path.last().unwrap();
+            let home = if config.return_to_depot && path.len() > 1 { distance_matrix[last][config.depot] } else { 0.0 };
+            if cost + home <= config.budget + 1e-9 {
+                *best = best.max(path.iter().map(|&node| prizes[node]).sum());
+            }
+            for node in 0..distance_matrix.len() {
+                let arc = distance_matrix[last][node];
+                if path.contains(&node) || cost + arc > config.budget + 1e-9 {
+                    continue;
+                }
+                path.push(node);
+                search(distance_matrix, prizes, config, path, cost + arc, best);
+                path.pop();
+            }
+        }
+        let mut best = f64::NEG_INFINITY;
+        search(distance_matrix, prizes, config, &mut vec![config.depot], 0.0, &mut best);
+        best
+    }
+
+    fn test_worlds(folder_path: &str) -> Vec<(String, Distances, Vec<Vec<f64>>)> {
+        // reads every csv in a folder as (file name, hashmap, distance matrix), sorted so failures are reproducible
+        let mut worlds = Vec::new();
+        let mut file_paths: Vec<_> = fs::read_dir(folder_path).expect("Failed to read directory")
+            .map(|entry| entry.expect("Failed to read entry").path())
+            .filter(|file_path| file_path.is_file() && file_path.extension().is_some_and(|ext| ext == "csv"))
+            .collect();
+        file_paths.sort();
+        for file_path in file_paths {
+            let file_name = file_path.display().to_string();
+            let distances = crate::csv_readers::read_csv_as_hashmap(&file_name).expect("Failed to read csv as hashmap");
+            let distance_matrix = crate::csv_readers::read_csv_as_matrix(&file_name).expect("Failed to read csv as matrix");
+            worlds.push((file_name, distances, distance_matrix));
+        }
+        worlds
     }
     
 
@@ -50,7 +402,7 @@ mod tests{
             let file_path = entry.path();
     
             // Ensure it's a file and has a .csv extension
-            if file_path.is_file() && file_path.extension().map_or(false, |ext| ext == "csv") {
+            if file_path.is_file() && file_path.extension().is_some_and(|ext| ext == "csv") {
                 println!("Testing file: {:?}", file_path.display());
                 let distance_matrix = match crate::csv_readers::read_csv_as_matrix(file_path.to_str().unwrap()) {
                     Ok(matrix) => matrix,
@@ -62,7 +414,7 @@ mod tests{
     
                 let size = distance_matrix.len();
                 if size <= 20 {
-                    let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix);
+                    let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
     
                     // Test validity of the route
                     let result = is_valid_route(&distance_matrix, &cost, &path);
@@ -83,7 +435,7 @@ mod tests{
             let file_path = entry.path();
 
             // Ensure it's a file and has a .csv extension
-            if file_path.is_file() && file_path.extension().map_or(false, |ext| ext == "csv") {
+            if file_path.is_file() && file_path.extension().is_some_and(|ext| ext == "csv") {
                 println!("Testing file: {:?}", file_path.display());
                 let distances = match crate::csv_readers::read_csv_as_hashmap(file_path.to_str().unwrap()) {   // gets hashmap for route algorithm
                     Ok(distances) => distances,
@@ -118,7 +470,7 @@ mod tests{
             let file_path = entry.path();
 
             // Ensure it's a file and has a .csv extension
-            if file_path.is_file() && file_path.extension().map_or(false, |ext| ext == "csv") {
+            if file_path.is_file() && file_path.extension().is_some_and(|ext| ext == "csv") {
                 println!("Testing file: {:?}", file_path.display());
                 let distances = match crate::csv_readers::read_csv_as_hashmap(file_path.to_str().unwrap()) {   // gets hashmap for route algorithm
                     Ok(distances) => distances,
@@ -146,6 +498,39 @@ mod tests{
         }
     }
     #[test]
+    fn test_nearest_neighbor_sparse_patches_stuck_fragments() {
+        // the splices leave several fragments on these two, both have a path (Held-Karp finds 48.32 and 45.42)
+        // and the old fragment merging found 63.31 and 63.65
+        for (file_name, baseline) in [("test-csv-sparse/sparse_15_world8.csv", 63.31), ("test-csv-sparse/sparse_15_world9.csv", 63.65)] {
+            let distances = crate::csv_readers::read_csv_as_hashmap(file_name).expect("Failed to read csv");
+            let distance_matrix = crate::csv_readers::read_csv_as_matrix(file_name).expect("Failed to read csv");
+            let (cost, path) = crate::algorithms::nearest_neighbor_sparse(&distances);
+            assert!(cost.is_finite(), "No route for file {}", file_name);
+            assert_eq!(is_valid_route(&distance_matrix, &cost, &path), "valid");
+            assert!(cost <= baseline, "Worse than the old fragment merging for file {}: {}", file_name, cost);
+        }
+    }
+    #[test]
+    fn test_nearest_neighbor_sparse_terminates_without_path() {
+        // 2 and 3 both have no way out, so no path can visit everything. The old fragment merging looped forever here
+        let mut distances: Distances = HashMap::new();
+        for (from, to, cost) in [(0, 1, 1.0), (1, 0, 1.0), (0, 2, 2.0), (1, 3, 2.0)] {
+            distances.entry(from).or_default().insert(to, cost);
+        }
+        distances.entry(2).or_default();
+        distances.entry(3).or_default();
+        assert_eq!(crate::algorithms::nearest_neighbor_sparse(&distances), (f64::INFINITY, Vec::new()));
+        assert_eq!(crate::algorithms::nearest_neighbor_full_graph(&distances), (f64::INFINITY, Vec::new())); // dead ends at 3 instead of exiting
+
+        // the cheapest arcs make 0 -> 1 and 2 -> 3, only splicing 2 -> 3 into the middle connects them
+        let mut distances: Distances = HashMap::new();
+        for (from, to, cost) in [(0, 1, 1.0), (2, 3, 1.0), (0, 2, 5.0), (3, 1, 5.0)] {
+            distances.entry(from).or_default().insert(to, cost);
+        }
+        distances.entry(1).or_default();
+        assert_eq!(crate::algorithms::nearest_neighbor_sparse(&distances), (11.0, vec![0, 2, 3, 1]));
+    }
+    #[test]
     fn test_invalid_arc() {
         // Create a distance matrix where there's no arc between nodes 0 and 3 (represented by `f64::INFINITY`)
         let distance_matrix = vec![
@@ -200,5 +585,1179 @@ mod tests{
         let result = is_valid_route(&distance_matrix, &cost, &path);
         assert!(result.contains("Cost was"), "Test failed with result: {}", result);
     }
-    
-}
\ No newline at end of file
+    #[test]
+    fn test_two_opt_sparse() {
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            let (start_cost, start_path) = crate::algorithms::nearest_neighbor_sparse(&distances);
+            let (cost, path) = crate::algorithms::two_opt(&distances, &start_path);
+
+            let result = is_valid_route(&distance_matrix, &cost, &path);
+            assert_eq!(result, "valid", "The route is invalid for file {}: {}", file_name, result);
+            assert_eq!(path.len(), start_path.len(), "2-opt dropped locations for file {}", file_name);
+            assert!(cost <= start_cost + 1e-6, "2-opt made {} worse: {} -> {}", file_name, start_cost, cost);
+        }
+    }
+    #[test]
+    fn test_two_opt_full_graph() {
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-full-world") {
+            let (start_cost, start_path) = crate::algorithms::nearest_neighbor_full_graph(&distances);
+            let (cost, path) = crate::algorithms::two_opt(&distances, &start_path);
+
+            let result = is_valid_route(&distance_matrix, &cost, &path);
+            assert_eq!(result, "valid", "The route is invalid for file {}: {}", file_name, result);
+            assert!(cost <= start_cost + 1e-6, "2-opt made {} worse: {} -> {}", file_name, start_cost, cost);
+        }
+    }
+    #[test]
+    fn test_two_opt_asymmetric_reversal() {
+        // 0 -> 1 -> 2 -> 3 is expensive in the middle, reversing [1, 2] is cheaper but only because 2 -> 1 is cheap
+        let mut distances: Distances = HashMap::new();
+        for (from, to, cost) in [(0, 1, 1.0), (1, 2, 10.0), (2, 3, 1.0), (0, 2, 1.0), (2, 1, 1.0), (1, 3, 1.0)] {
+            distances.entry(from).or_default().insert(to, cost);
+        }
+        let (cost, path) = crate::algorithms::two_opt(&distances, &[0, 1, 2, 3]);
+        assert_eq!(path, vec![0, 2, 1, 3]);
+        assert!((cost - 3.0).abs() < 1e-9, "Expected cost 3 but got {}", cost);
+
+        // same instance without the reversed arc 2 -> 1, the move would create a missing arc so the path must stay put
+        distances.get_mut(&2).unwrap().remove(&1);
+        let (cost, path) = crate::algorithms::two_opt(&distances, &[0, 1, 2, 3]);
+        assert_eq!(path, vec![0, 1, 2, 3]);
+        assert!((cost - 12.0).abs() < 1e-9, "Expected cost 12 but got {}", cost);
+    }
+    #[test]
+    fn test_or_opt_and_segment_swap_improve_heuristics() {
+        // every existing heuristic output must stay valid and never get worse under either move
+        let mut starting_routes = Vec::new();
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            let (_, nearest_path) = crate::algorithms::nearest_neighbor_sparse(&distances);
+            let (_, two_opt_path) = crate::algorithms::two_opt(&distances, &nearest_path);
+            starting_routes.push((file_name.clone(), distances.clone(), distance_matrix.clone(), nearest_path));
+            starting_routes.push((file_name.clone(), distances.clone(), distance_matrix.clone(), two_opt_path));
+            if distance_matrix.len() <= 20 {
+                let (_, held_karp_path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+                starting_routes.push((file_name, distances, distance_matrix, held_karp_path));
+            }
+        }
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-full-world") {
+            let (_, nearest_path) = crate::algorithms::nearest_neighbor_full_graph(&distances);
+            starting_routes.push((file_name, distances, distance_matrix, nearest_path));
+        }
+
+        for (file_name, distances, distance_matrix, start_path) in starting_routes {
+            let start_cost = crate::algorithms::path_cost(&distances, &start_path);
+            for (cost, path) in [crate::algorithms::or_opt(&distances, &start_path), crate::algorithms::or_three_opt(&distances, &start_path)] {
+                let result = is_valid_route(&distance_matrix, &cost, &path);
+                assert_eq!(result, "valid", "The route is invalid for file {}: {}", file_name, result);
+                assert_eq!(path.len(), start_path.len(), "Locations were dropped for file {}", file_name);
+                assert!(cost <= start_cost + 1e-6, "Move made {} worse: {} -> {}", file_name, start_cost, cost);
+            }
+        }
+    }
+    #[test]
+    fn test_or_opt_relocates_chain_on_one_way_arcs() {
+        // one way chain 0 -> 1 -> 2 -> 3 -> 4 where node 3 is cheaper to visit first, no arc can be traversed backwards
+        let mut distances: Distances = HashMap::new();
+        for (from, to, cost) in [(0, 1, 1.0), (1, 2, 1.0), (2, 3, 10.0), (3, 4, 10.0), (2, 4, 1.0), (0, 3, 1.0), (3, 1, 1.0)] {
+            distances.entry(from).or_default().insert(to, cost);
+        }
+        let (cost, path) = crate::algorithms::or_opt(&distances, &[0, 1, 2, 3, 4]);
+        assert_eq!(path, vec![0, 3, 1, 2, 4]);
+        assert!((cost - 4.0).abs() < 1e-9, "Expected cost 4 but got {}", cost);
+
+        // the segment swap finds the same route by exchanging [1, 2] and [3]
+        let (cost, path) = crate::algorithms::or_three_opt(&distances, &[0, 1, 2, 3, 4]);
+        assert_eq!(path, vec![0, 3, 1, 2, 4]);
+        assert!((cost - 4.0).abs() < 1e-9, "Expected cost 4 but got {}", cost);
+    }
+    #[test]
+    fn test_simulated_annealing_route() {
+        for folder_path in ["test-csv-sparse", "test-csv-full-world"] {
+            for (file_name, distances, distance_matrix) in test_worlds(folder_path) {
+                // random start on the small worlds, nearest neighbor start on the large ones
+                let initial_path = if distance_matrix.len() > 20 {
+                    Some(crate::algorithms::nearest_neighbor_sparse(&distances).1)
+                } else {
+                    None
+                };
+                let config = crate::algorithms::AnnealingConfig {
+                    seed: 7,
+                    max_iterations: Some(50_000),
+                    time_budget: std::time::Duration::from_secs(60),
+                    initial_path,
+                    ..Default::default()
+                };
+                let result = crate::algorithms::simulated_annealing(&distance_matrix, &config);
+
+                let check = is_valid_route(&distance_matrix, &result.cost, &result.path);
+                assert_eq!(check, "valid", "The route is invalid for file {}: {}", file_name, check);
+                assert_eq!(result.path.len(), distance_matrix.len(), "Locations were dropped for file {}", file_name);
+                assert!(!result.trace.is_empty(), "No trace recorded for file {}", file_name);
+                if let Some(initial_path) = &config.initial_path {
+                    let initial_cost = crate::algorithms::path_cost(&distances, initial_path);
+                    assert!(result.cost <= initial_cost + 1e-6, "Annealing lost the starting route for file {}", file_name);
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_simulated_annealing_same_seed_same_result() {
+        let distance_matrix = crate::csv_readers::read_csv_as_matrix("test-csv-sparse/sparse_15_world1.csv").expect("Failed to read csv");
+        let run = |seed: u64| {
+            let config = crate::algorithms::AnnealingConfig {
+                seed,
+                max_iterations: Some(20_000),
+                time_budget: std::time::Duration::from_secs(60),
+                ..Default::default()
+            };
+            crate::algorithms::simulated_annealing(&distance_matrix, &config)
+        };
+        let first = run(3);
+        let second = run(3);
+        assert_eq!(first.path, second.path);
+        assert_eq!(first.cost, second.cost);
+        assert_eq!(first.iterations, second.iterations);
+        let first_trace: Vec<f64> = first.trace.iter().map(|point| point.current_cost).collect();
+        let second_trace: Vec<f64> = second.trace.iter().map(|point| point.current_cost).collect();
+        assert_eq!(first_trace, second_trace);
+    }
+    #[test]
+    fn test_genetic_algorithm_route() {
+        for folder_path in ["test-csv-sparse", "test-csv-full-world"] {
+            for (file_name, distances, distance_matrix) in test_worlds(folder_path) {
+                let small = distance_matrix.len() <= 20;
+                let config = crate::algorithms::GeneticConfig {
+                    seed: 11,
+                    population_size: if small { 40 } else { 16 },
+                    generations: if small { 100 } else { 10 },
+                    ..Default::default()
+                };
+                let (cost, path) = crate::algorithms::genetic_algorithm(&distances, &config);
+
+                let result = is_valid_route(&distance_matrix, &cost, &path);
+                assert_eq!(result, "valid", "The route is invalid for file {}: {}", file_name, result);
+                assert_eq!(path.len(), distance_matrix.len(), "Locations were dropped for file {}", file_name);
+                let (nearest_cost, _) = crate::algorithms::nearest_neighbor_sparse(&distances);
+                assert!(nearest_cost.is_finite(), "No nearest neighbor seed for file {}", file_name); // every test world has a path
+                assert!(cost <= nearest_cost + 1e-6, "Genetic algorithm lost its nearest neighbor seed for file {}", file_name);
+            }
+        }
+    }
+    #[test]
+    fn test_genetic_algorithm_random_start_same_seed_same_result() {
+        // no nearest neighbor seed, every individual starts random and has to be repaired onto existing arcs
+        let distances = crate::csv_readers::read_csv_as_hashmap("test-csv-sparse/sparse_15_world2.csv").expect("Failed to read csv");
+        let distance_matrix = crate::csv_readers::read_csv_as_matrix("test-csv-sparse/sparse_15_world2.csv").expect("Failed to read csv");
+        let config = crate::algorithms::GeneticConfig {
+            seed: 5,
+            generations: 100,
+            seed_with_nearest_neighbor: false,
+            ..Default::default()
+        };
+        let (cost, path) = crate::algorithms::genetic_algorithm(&distances, &config);
+        let result = is_valid_route(&distance_matrix, &cost, &path);
+        assert_eq!(result, "valid", "The route is invalid: {}", result);
+        assert_eq!(crate::algorithms::genetic_algorithm(&distances, &config), (cost, path));
+    }
+    #[test]
+    fn test_ant_colony_route() {
+        for folder_path in ["test-csv-sparse", "test-csv-full-world"] {
+            for (file_name, distances, distance_matrix) in test_worlds(folder_path) {
+                let config = crate::algorithms::AntColonyConfig {
+                    seed: 13,
+                    iterations: if distance_matrix.len() <= 20 { 100 } else { 20 },
+                    ..Default::default()
+                };
+                let result = crate::algorithms::ant_colony_system(&distances, &config);
+
+                let check = is_valid_route(&distance_matrix, &result.cost, &result.path);
+                assert_eq!(check, "valid", "The route is invalid for file {}: {}", file_name, check);
+                assert_eq!(result.path.len(), distance_matrix.len(), "Locations were dropped for file {}", file_name);
+                assert_eq!(result.total_ants, config.ants * config.iterations);
+                assert!(result.failed_ants < result.total_ants, "Every ant failed for file {}", file_name);
+            }
+        }
+    }
+    #[test]
+    fn test_ant_colony_dead_ends() {
+        // starting at 1 or 2 always dead ends, starting at 0 only works through 0 -> 1 -> 2
+        let mut distances: Distances = HashMap::new();
+        for (from, to, cost) in [(0, 1, 5.0), (1, 2, 5.0), (0, 2, 1.0)] {
+            distances.entry(from).or_default().insert(to, cost);
+        }
+        let config = crate::algorithms::AntColonyConfig { seed: 1, ants: 10, iterations: 10, ..Default::default() };
+        let result = crate::algorithms::ant_colony_system(&distances, &config);
+        assert_eq!(result.path, vec![0, 1, 2]);
+        assert!((result.cost - 10.0).abs() < 1e-9, "Expected cost 10 but got {}", result.cost);
+        assert!(result.failed_ants > 0, "Ants starting at 1 or 2 should fail");
+
+        // without backtracking the ant that greedily takes 0 -> 2 is stuck too
+        let config = crate::algorithms::AntColonyConfig { max_backtracks: 0, exploitation: 1.0, ..config };
+        let result = crate::algorithms::ant_colony_system(&distances, &config);
+        assert_eq!(result.failed_ants, result.total_ants);
+        assert!(result.path.is_empty());
+        assert_eq!(result.cost, f64::INFINITY);
+    }
+    #[test]
+    fn test_branch_and_bound_route() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 20 {
+                continue;
+            }
+            let result = crate::algorithms::branch_and_bound(&distance_matrix, &crate::algorithms::BranchAndBoundConfig { time_limit: std::time::Duration::from_secs(120) });
+
+            let check = is_valid_route(&distance_matrix, &result.cost, &result.path);
+            assert_eq!(check, "valid", "The route is invalid for file {}: {}", file_name, check);
+            assert!(result.proved_optimal, "No proof for file {}", file_name);
+            assert!((result.lower_bound - result.cost).abs() < 1e-6, "Bound and cost differ after a proof for file {}", file_name);
+            let (held_karp_cost, _) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+            assert!((result.cost - held_karp_cost).abs() < 1e-4, "Held-Karp found {} but branch and bound {} for file {}", held_karp_cost, result.cost, file_name);
+        }
+    }
+    #[test]
+    fn test_branch_and_bound_no_path() {
+        // nodes 1 and 2 both have no outgoing arcs, only one of them can end the path
+        let distance_matrix = vec![
+            vec![f64::INFINITY, 1.0, 1.0],
+            vec![f64::INFINITY, f64::INFINITY, f64::INFINITY],
+            vec![f64::INFINITY, f64::INFINITY, f64::INFINITY],
+        ];
+        let result = crate::algorithms::branch_and_bound(&distance_matrix, &crate::algorithms::BranchAndBoundConfig { time_limit: std::time::Duration::from_secs(10) });
+        assert!(result.proved_optimal);
+        assert!(result.path.is_empty());
+        assert_eq!(result.cost, f64::INFINITY);
+        assert_eq!(result.lower_bound, f64::INFINITY);
+    }
+    #[test]
+    fn test_lower_bounds() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 20 {
+                continue;
+            }
+            let (optimal_cost, _) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+            let bounds = crate::lower_bounds::lower_bounds(&distance_matrix, optimal_cost, &crate::lower_bounds::LowerBoundConfig::default());
+            for (name, bound) in [("assignment", bounds.assignment), ("1-arborescence", bounds.arborescence), ("Held-Karp", bounds.held_karp)] {
+                assert!(bound <= optimal_cost + 1e-4, "{} bound {} above the optimum {} for file {}", name, bound, optimal_cost, file_name);
+                assert!(bound > 0.0, "{} bound is not positive for file {}", name, file_name);
+            }
+            assert!(bounds.held_karp >= bounds.arborescence - 1e-6, "Subgradient ascent lost to its starting point for file {}", file_name);
+        }
+
+        // a one way chain is its own 1-arborescence, every bound is exact
+        let inf = f64::INFINITY;
+        let distance_matrix = vec![
+            vec![inf, 2.0, 9.0, 9.0],
+            vec![9.0, inf, 3.0, 9.0],
+            vec![9.0, 9.0, inf, 4.0],
+            vec![9.0, 9.0, 9.0, inf],
+        ];
+        let bounds = crate::lower_bounds::lower_bounds(&distance_matrix, 9.0, &crate::lower_bounds::LowerBoundConfig::default());
+        assert!((bounds.assignment - 9.0).abs() < 1e-9, "Assignment bound {}", bounds.assignment);
+        assert!((bounds.best() - 9.0).abs() < 1e-6, "Best bound {}", bounds.best());
+    }
+    #[test]
+    fn test_milp_matches_held_karp() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 20 {
+                continue;
+            }
+            let result = crate::milp::solve_with_subtour_cuts(&distance_matrix, &crate::milp::MilpConfig::default()).unwrap();
+            let (held_karp_cost, _) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+            if held_karp_cost.is_infinite() {
+                assert!(result.path.is_empty() && result.cost.is_infinite(), "MILP found a path Held-Karp could not for file {}", file_name);
+                continue;
+            }
+            let check = is_valid_route(&distance_matrix, &result.cost, &result.path);
+            assert_eq!(check, "valid", "The route is invalid for file {}: {}", file_name, check);
+            assert!((result.cost - held_karp_cost).abs() < 1e-4, "Held-Karp found {} but the MILP {} for file {}", held_karp_cost, result.cost, file_name);
+        }
+    }
+    #[test]
+    fn test_milp_formulations() {
+        // a 6 location world where the cheap arcs form two triangles, the path has to pay to cross between them
+        let inf = f64::INFINITY;
+        let mut distance_matrix = vec![vec![9.0; 6]; 6];
+        for (from, to) in [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)] {
+            distance_matrix[from][to] = 1.0;
+        }
+        for (i, row) in distance_matrix.iter_mut().enumerate() {
+            row[i] = inf;
+        }
+        distance_matrix[2][3] = 4.0;
+        let (held_karp_cost, _) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+        assert_eq!(held_karp_cost, 8.0);
+        for formulation in [crate::milp::Formulation::Mtz, crate::milp::Formulation::SingleCommodityFlow] {
+            let model = crate::milp::build_model(&distance_matrix, formulation);
+            let values = crate::milp::solve_model(&model).unwrap().unwrap();
+            let path = crate::milp::path_from_solution(&model, &values);
+            let cost: f64 = path.windows(2).map(|pair| distance_matrix[pair[0]][pair[1]]).sum();
+            let check = is_valid_route(&distance_matrix, &cost, &path);
+            assert_eq!(check, "valid", "The {:?} route is invalid: {}", formulation, check);
+            assert!((cost - held_karp_cost).abs() < 1e-6, "{:?} found {} instead of {}", formulation, cost, held_karp_cost);
+
+            let lp = crate::milp::write_lp(&model);
+            assert!(lp.starts_with("\\") && lp.contains("Subject To") && lp.trim_end().ends_with("End"));
+            assert_eq!(lp.matches(':').count(), model.rows.len() + 2, "{:?} LP file should have the objective and every row", formulation);
+            let mps = crate::milp::write_mps(&model);
+            assert!(mps.contains("\nCOLUMNS\n") && mps.trim_end().ends_with("ENDATA"));
+            assert_eq!(mps.matches(" BV BND ").count(), model.arcs.len());
+        }
+        let result = crate::milp::solve_with_subtour_cuts(&distance_matrix, &crate::milp::MilpConfig::default()).unwrap();
+        assert_eq!(result.cost, 8.0);
+        assert!(result.cuts > 0, "The two triangles should need at least one cut");
+    }
+    #[test]
+    fn test_fleet_plans() {
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 50 {
+                continue;
+            }
+            let configs = [
+                crate::fleet::FleetConfig { aircraft: 3, ..Default::default() },
+                crate::fleet::FleetConfig { aircraft: 3, objective: crate::fleet::FleetObjective::MinMax, ..Default::default() },
+                crate::fleet::FleetConfig { aircraft: 2, depots: crate::fleet::Depots::Separate(vec![0, 1]), return_to_depot: true, ..Default::default() },
+            ];
+            for config in configs {
+                let plan = crate::fleet::plan_fleet(&distances, &config).unwrap();
+                let depots = match &config.depots {
+                    crate::fleet::Depots::Shared(depot) => vec![*depot; config.aircraft],
+                    crate::fleet::Depots::Separate(depots) => depots.clone(),
+                };
+                if plan.total_cost.is_infinite() { // returning to a depot needs arcs back into it, sparse worlds may not have any
+                    assert!(config.return_to_depot, "No {:?} plan for file {}", config.objective, file_name);
+                    continue;
+                }
+                let check = is_valid_fleet(&distance_matrix, &plan, &depots, config.return_to_depot);
+                assert_eq!(check, "valid", "The {:?} plan is invalid for file {}: {}", config.objective, file_name, check);
+            }
+        }
+
+        // two spokes out of the depot joined at their ends, one aircraft each keeps the longest route short but one
+        // aircraft flying both spokes is cheaper in total
+        let inf = f64::INFINITY;
+        let mut distance_matrix = vec![vec![inf; 5]; 5];
+        let mut distances: Distances = HashMap::new();
+        for (from, to, cost) in [(0, 1, 1.0), (1, 2, 1.0), (0, 3, 2.0), (3, 4, 1.0), (2, 3, 1.0)] {
+            distance_matrix[from][to] = cost;
+            distances.entry(from).or_default().insert(to, cost);
+        }
+        let min_max = crate::fleet::plan_fleet(&distances, &crate::fleet::FleetConfig { objective: crate::fleet::FleetObjective::MinMax, ..Default::default() }).unwrap();
+        assert_eq!(is_valid_fleet(&distance_matrix, &min_max, &[0, 0], false), "valid");
+        assert_eq!((min_max.max_route_cost, min_max.total_cost), (3.0, 5.0));
+        let total = crate::fleet::plan_fleet(&distances, &crate::fleet::FleetConfig::default()).unwrap();
+        assert_eq!(is_valid_fleet(&distance_matrix, &total, &[0, 0], false), "valid");
+        assert_eq!((total.max_route_cost, total.total_cost), (4.0, 4.0));
+    }
+    #[test]
+    fn test_mixed_fleet() {
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            let n = distance_matrix.len();
+            if n > 50 {
+                continue;
+            }
+            // a cheap airframe kept out of a few airports next to one paying more and barred from a couple of arcs
+            let aircraft_types = vec![
+                crate::fleet::AircraftType { cost_multiplier: 0.5, prohibited_nodes: vec![n / 2, n - 1], ..Default::default() },
+                crate::fleet::AircraftType { cost_multiplier: 2.0, prohibited_arcs: vec![(0, 1), (1, 2)], ..Default::default() },
+            ];
+            let aircraft_matrices: Vec<Vec<Vec<f64>>> = aircraft_types.iter().map(|aircraft_type| {
+                let mut matrix: Vec<Vec<f64>> = distance_matrix.iter().map(|row| row.iter().map(|cost| cost * aircraft_type.cost_multiplier).collect()).collect();
+                for &(from, to) in &aircraft_type.prohibited_arcs {
+                    matrix[from][to] = f64::INFINITY;
+                }
+                for &node in &aircraft_type.prohibited_nodes {
+                    matrix[node].fill(f64::INFINITY);
+                    for row in matrix.iter_mut() {
+                        row[node] = f64::INFINITY;
+                    }
+                }
+                matrix
+            }).collect();
+            let plan = crate::fleet::plan_fleet(&distances, &crate::fleet::FleetConfig { aircraft_types, ..Default::default() }).unwrap();
+            if plan.total_cost.is_infinite() { // a prohibited airport may leave the other aircraft no way in
+                continue;
+            }
+            let check = is_valid_mixed_fleet(&aircraft_matrices, &plan, &[0, 0], false);
+            assert_eq!(check, "valid", "The mixed fleet plan is invalid for file {}: {}", file_name, check);
+        }
+
+        // a chain out of the depot both aircraft can fly, the cheap one twice over but it can't land at 2, so it can only
+        // take the first hop or nothing at all
+        let inf = f64::INFINITY;
+        let mut distances: Distances = HashMap::new();
+        for (from, to, cost) in [(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0), (0, 2, 3.0)] {
+            distances.entry(from).or_default().insert(to, cost);
+        }
+        let aircraft_types = vec![
+            crate::fleet::AircraftType { cost_multiplier: 0.5, prohibited_nodes: vec![2], ..Default::default() },
+            crate::fleet::AircraftType { cost_table: Some(distances.clone()), prohibited_arcs: vec![(0, 1)], ..Default::default() },
+        ];
+        let config = crate::fleet::FleetConfig { aircraft_types: aircraft_types.clone(), ..Default::default() };
+        let mut aircraft_matrices = crate::fleet::aircraft_matrices(&distances, &config);
+        assert_eq!((aircraft_matrices[0][0][1], aircraft_matrices[0][1][2], aircraft_matrices[1][0][1], aircraft_matrices[1][0][2]), (0.5, inf, inf, 3.0));
+        let plan = crate::fleet::plan_fleet(&distances, &config).unwrap();
+        assert_eq!(is_valid_mixed_fleet(&aircraft_matrices, &plan, &[0, 0], false), "valid");
+        assert_eq!((plan.routes[0].path.clone(), plan.routes[1].path.clone(), plan.total_cost), (vec![0, 1], vec![0, 2, 3], 4.5));
+
+        // scaling the second aircraft's own table makes the cheap one worth flying alone as far as it can
+        let mut pricey = aircraft_types;
+        pricey[1].cost_multiplier = 3.0;
+        pricey[0].prohibited_nodes.clear();
+        let config = crate::fleet::FleetConfig { aircraft_types: pricey, ..Default::default() };
+        aircraft_matrices = crate::fleet::aircraft_matrices(&distances, &config);
+        let plan = crate::fleet::plan_fleet(&distances, &config).unwrap();
+        assert_eq!(is_valid_mixed_fleet(&aircraft_matrices, &plan, &[0, 0], false), "valid");
+        assert_eq!((plan.routes[0].path.clone(), plan.routes[1].path.clone(), plan.total_cost), (vec![0, 1, 2, 3], vec![0], 1.5));
+
+        // an aircraft barred from its own depot or a type missing gets no plan
+        let barred = vec![crate::fleet::AircraftType { prohibited_nodes: vec![0], ..Default::default() }, crate::fleet::AircraftType::default()];
+        assert!(matches!(crate::fleet::plan_fleet(&distances, &crate::fleet::FleetConfig { aircraft_types: barred, ..Default::default() }),
+            Err(crate::fleet::FleetError::DepotProhibited { aircraft: 0, depot: 0 })));
+        let one_type = vec![crate::fleet::AircraftType::default()];
+        assert!(matches!(crate::fleet::plan_fleet(&distances, &crate::fleet::FleetConfig { aircraft_types: one_type, ..Default::default() }),
+            Err(crate::fleet::FleetError::AircraftTypeCount { types: 1, aircraft: 2 })));
+    }
+    #[test]
+    fn test_fuel_held_karp() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 15 {
+                continue;
+            }
+            // unlimited range is plain Held-Karp
+            let unlimited = crate::fuel::FuelLimits { max_leg_cost: f64::INFINITY, endurance: f64::INFINITY, refuel_nodes: Vec::new() };
+            let route = crate::fuel::fuel_held_karp(&distance_matrix, &unlimited).unwrap();
+            let (held_karp_cost, _) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+            assert!((route.cost - held_karp_cost).abs() < 1e-4, "Unlimited fuel found {} but Held-Karp {} for file {}", route.cost, held_karp_cost, file_name);
+
+            let limits = crate::fuel::FuelLimits { max_leg_cost: 8.0, endurance: 10.0, refuel_nodes: (0..distance_matrix.len()).step_by(3).collect() };
+            let route = crate::fuel::fuel_held_karp(&distance_matrix, &limits).unwrap();
+            if route.cost.is_infinite() {
+                continue;
+            }
+            let check = is_fuel_feasible(&distance_matrix, &limits, &route);
+            assert_eq!(check, "valid", "The fuel route is invalid for file {}: {}", file_name, check);
+            let refitted = crate::fuel::fit_refuel_stops(&distance_matrix, &limits, &route.visit_order);
+            assert!((refitted.cost - route.cost).abs() < 1e-6, "Refuel stops fitted to the same order cost {} instead of {} for file {}", refitted.cost, route.cost, file_name);
+            let annealed = crate::fuel::solve_with_refuel_stops(&distance_matrix, &limits, |in_range| {
+                let result = crate::algorithms::simulated_annealing(in_range, &crate::algorithms::AnnealingConfig { seed: 42, max_iterations: Some(20_000), ..Default::default() });
+                (result.cost, result.path)
+            });
+            if annealed.cost.is_finite() {
+                let check = is_fuel_feasible(&distance_matrix, &limits, &annealed);
+                assert_eq!(check, "valid", "The annealing fuel route is invalid for file {}: {}", file_name, check);
+                assert!(annealed.cost >= route.cost - 1e-4, "Annealing {} beat the exact {} for file {}", annealed.cost, route.cost, file_name);
+            }
+        }
+    }
+    #[test]
+    fn test_refuel_stop_inserted() {
+        // flying 3 0 1 2 the tank is nearly empty at 1, so the aircraft goes back through the refuel airport 3 to reach 2
+        let inf = f64::INFINITY;
+        let mut distance_matrix = vec![vec![inf; 4]; 4];
+        for (from, to, cost) in [(3, 0, 1.0), (0, 1, 5.0), (1, 2, 4.5), (1, 3, 1.0), (3, 2, 4.0)] {
+            distance_matrix[from][to] = cost;
+        }
+        let limits = crate::fuel::FuelLimits { max_leg_cost: 6.0, endurance: 7.0, refuel_nodes: vec![3] };
+        let route = crate::fuel::fit_refuel_stops(&distance_matrix, &limits, &[3, 0, 1, 2]);
+        assert_eq!(is_fuel_feasible(&distance_matrix, &limits, &route), "valid");
+        assert_eq!(route.stops.iter().map(|stop| (stop.node, stop.refuel_stop)).collect::<Vec<_>>(), vec![(3, false), (0, false), (1, false), (3, true), (2, false)]);
+        assert_eq!(route.cost, 11.0);
+        // visiting 3 between 1 and 2 needs no extra stop and is the optimum
+        let best = crate::fuel::fuel_held_karp(&distance_matrix, &limits).unwrap();
+        assert_eq!(is_fuel_feasible(&distance_matrix, &limits, &best), "valid");
+        assert_eq!((best.cost, best.visit_order), (10.0, vec![0, 1, 3, 2]));
+
+        // the same order without the refuel stop runs dry, the validator catches it
+        let path = vec![3, 0, 1, 2];
+        let stops = vec![(3, 7.0), (0, 6.0), (1, 1.0), (2, -3.5)].into_iter().map(|(node, fuel)| crate::fuel::FuelStop { node, refuel_stop: false, fuel }).collect();
+        let direct = crate::fuel::FuelRoute { cost: 10.5, visit_order: path, stops };
+        assert!(is_fuel_feasible(&distance_matrix, &limits, &direct).starts_with("Out of fuel"));
+    }
+    #[test]
+    fn test_time_window_route() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 15 {
+                continue;
+            }
+            // open windows: any route is on time
+            let open = vec![crate::time_windows::TimeWindow::default(); distance_matrix.len()];
+            let schedule = crate::time_windows::time_window_route(&distance_matrix, &open, &Default::default()).unwrap();
+            let check = is_valid_schedule(&distance_matrix, &open, &schedule, false);
+            assert_eq!(check, "valid", "The open window schedule is invalid for file {}: {}", file_name, check);
+            assert_eq!((schedule.total_wait, schedule.total_lateness), (0.0, 0.0));
+
+            // windows that are only the moment the Held-Karp path arrives, strict mode has to find a route that is never late
+            let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+            if cost.is_infinite() {
+                continue;
+            }
+            let reference = crate::time_windows::schedule_path(&distance_matrix, &open, &path, 0.0);
+            let mut windows = open.clone();
+            for stop in &reference.stops {
+                windows[stop.node] = crate::time_windows::TimeWindow { earliest: stop.arrival, latest: stop.arrival, service: 0.0 };
+            }
+            let strict = crate::time_windows::TimeWindowConfig { strict: true, ..Default::default() };
+            let schedule = crate::time_windows::time_window_route(&distance_matrix, &windows, &strict).unwrap();
+            let check = is_valid_schedule(&distance_matrix, &windows, &schedule, true);
+            assert_eq!(check, "valid", "The strict schedule is invalid for file {}: {}", file_name, check);
+            assert!(schedule.cost >= cost - 1e-4, "Time windows {} beat Held-Karp {} for file {}", schedule.cost, cost, file_name);
+        }
+    }
+    #[test]
+    fn test_time_window_wait_and_strict_rejection() {
+        // every flight takes 1: visiting 2 first is on time, then the aircraft waits 3 for 1 to open
+        let inf = f64::INFINITY;
+        let distance_matrix = vec![vec![inf, 1.0, 1.0], vec![1.0, inf, 1.0], vec![1.0, 1.0, inf]];
+        let window = |earliest, latest| crate::time_windows::TimeWindow { earliest, latest, service: 0.0 };
+        let windows = vec![window(0.0, 0.0), window(5.0, 6.0), window(1.0, 2.0)];
+        let strict = crate::time_windows::TimeWindowConfig { strict: true, ..Default::default() };
+        let schedule = crate::time_windows::time_window_route(&distance_matrix, &windows, &strict).unwrap();
+        assert_eq!(is_valid_schedule(&distance_matrix, &windows, &schedule, true), "valid");
+        assert_eq!(schedule.path, vec![0, 2, 1]);
+        assert_eq!(schedule.stops.iter().map(|stop| (stop.arrival, stop.departure)).collect::<Vec<_>>(), vec![(0.0, 0.0), (1.0, 1.0), (2.0, 5.0)]);
+        assert_eq!((schedule.cost, schedule.total_wait, schedule.completion_time), (2.0, 3.0, 5.0));
+
+        // 0 and 1 both close at time 0, one of them is always late
+        let windows = vec![window(0.0, 0.0), window(0.0, 0.0), window(0.0, inf)];
+        match crate::time_windows::time_window_route(&distance_matrix, &windows, &strict) {
+            Err(crate::time_windows::TimeWindowError::WindowsViolated { late_stops, best }) => {
+                assert_eq!(late_stops.len(), 1);
+                assert_eq!(best.total_lateness, 1.0);
+            }
+            other => panic!("Strict mode should reject the windows, got {:?}", other),
+        }
+        let relaxed = crate::time_windows::time_window_route(&distance_matrix, &windows, &Default::default()).unwrap();
+        assert_eq!(is_valid_schedule(&distance_matrix, &windows, &relaxed, false), "valid");
+        assert_eq!((relaxed.cost, relaxed.total_lateness), (2.0, 1.0));
+    }
+    #[test]
+    fn test_precedence_route() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 15 {
+                continue;
+            }
+            // every other location has to come after the one three places on, the pairs chain without a cycle
+            let n = distance_matrix.len();
+            let precedences: Vec<(usize, usize)> = (0..n - 3).step_by(2).map(|node| (node + 3, node)).collect();
+            let (free_cost, _) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+            let config = HeldKarpConfig { precedences: precedences.clone(), ..Default::default() };
+            let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &config).unwrap();
+            if cost.is_infinite() {
+                continue;
+            }
+            let check = is_valid_precedence_route(&distance_matrix, &cost, &path, &precedences);
+            assert_eq!(check, "valid", "Held-Karp broke a precedence for file {}: {}", file_name, check);
+            assert!(cost >= free_cost - 1e-4, "Precedence made the route cheaper for file {}", file_name);
+            let fixed = HeldKarpConfig { precedences: precedences.clone(), ..Default::default() };
+            if let Ok((fixed_cost, fixed_path)) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::FixedStart(path[0]), &fixed) {
+                assert_eq!(is_valid_precedence_route(&distance_matrix, &fixed_cost, &fixed_path, &precedences), "valid");
+                assert!((fixed_cost - cost).abs() < 1e-4, "Fixed start {} found {} but the best open path {} for file {}", path[0], fixed_cost, cost, file_name);
+            }
+
+            let (nearest_cost, nearest_path) = crate::precedence::precedence_nearest_neighbor(&distance_matrix, &precedences);
+            if nearest_cost.is_finite() {
+                let (or_opt_cost, or_opt_path) = crate::precedence::precedence_or_opt(&distance_matrix, &precedences, &nearest_path);
+                let check = is_valid_precedence_route(&distance_matrix, &or_opt_cost, &or_opt_path, &precedences);
+                assert_eq!(check, "valid", "Nearest + Or-opt broke a precedence for file {}: {}", file_name, check);
+                assert!(or_opt_cost <= nearest_cost + 1e-9 && or_opt_cost >= cost - 1e-4);
+            }
+            let annealing_config = crate::algorithms::AnnealingConfig { seed: 42, max_iterations: Some(200_000), precedences: precedences.clone(), ..Default::default() };
+            let result = crate::algorithms::simulated_annealing(&distance_matrix, &annealing_config);
+            if result.cost.is_finite() {
+                let check = is_valid_precedence_route(&distance_matrix, &result.cost, &result.path, &precedences);
+                assert_eq!(check, "valid", "Annealing broke a precedence for file {}: {}", file_name, check);
+                assert!(result.cost >= cost - 1e-4, "Annealing {} beat Held-Karp {} for file {}", result.cost, cost, file_name);
+                assert!(crate::precedence::broken_pairs(&result.path, &precedences).is_empty());
+            }
+        }
+    }
+    #[test]
+    fn test_precedence_small_worlds() {
+        // 0 1 2 3 4 is the cheap chain, the pairs push 3 ahead of 1 and 4 ahead of 0
+        let mut distance_matrix = vec![vec![5.0; 5]; 5];
+        for (node, row) in distance_matrix.iter_mut().enumerate() {
+            row[node] = f64::INFINITY;
+            if node < 4 {
+                row[node + 1] = 1.0;
+            }
+        }
+        distance_matrix[3][1] = 2.0;
+        let precedences = vec![(3, 1), (4, 0)];
+        assert!(!crate::precedence::broken_pairs(&[0, 1, 2, 3, 4], &precedences).is_empty());
+        let config = HeldKarpConfig { precedences: precedences.clone(), ..Default::default() };
+        let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &config).unwrap();
+        assert_eq!(is_valid_precedence_route(&distance_matrix, &cost, &path, &precedences), "valid");
+        assert_eq!(cost, brute_force_precedence(&distance_matrix, &precedences));
+        let (nearest_cost, nearest_path) = crate::precedence::precedence_nearest_neighbor(&distance_matrix, &precedences);
+        let (or_opt_cost, or_opt_path) = crate::precedence::precedence_or_opt(&distance_matrix, &precedences, &nearest_path);
+        assert!(nearest_cost.is_finite());
+        assert_eq!(is_valid_precedence_route(&distance_matrix, &or_opt_cost, &or_opt_path, &precedences), "valid");
+
+        // a depot can't come after anything, a cycle has no route at all and a pair past the last location is an error
+        let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::FixedStart(0), &config).unwrap();
+        assert_eq!((cost, path), (f64::INFINITY, vec![]));
+        let cycle = HeldKarpConfig { precedences: vec![(1, 2), (2, 3), (3, 1)], ..Default::default() };
+        assert_eq!(crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &cycle).unwrap().0, f64::INFINITY);
+        let annealing_config = crate::algorithms::AnnealingConfig { seed: 42, max_iterations: Some(20_000), precedences: cycle.precedences.clone(), ..Default::default() };
+        assert_eq!(crate::algorithms::simulated_annealing(&distance_matrix, &annealing_config).cost, f64::INFINITY);
+        assert_eq!(crate::precedence::precedence_nearest_neighbor(&distance_matrix, &cycle.precedences).0, f64::INFINITY);
+        let out_of_range = HeldKarpConfig { precedences: vec![(0, 5)], ..Default::default() };
+        assert_eq!(crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &out_of_range),
+            Err(HeldKarpError::PrecedenceOutOfRange { node: 5, nodes: 5 }));
+    }
+    #[test]
+    fn test_orienteering() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            let n = distance_matrix.len();
+            if n > 20 {
+                continue;
+            }
+            let prizes: Vec<f64> = (0..n).map(|node| if node == 0 { 0.0 } else { (node % 5 + 1) as f64 }).collect();
+            for (budget, return_to_depot) in [(6.0, false), (12.0, true)] {
+                let config = crate::orienteering::OrienteeringConfig { budget, return_to_depot, ..Default::default() };
+                let heuristic = crate::orienteering::orienteering_heuristic(&distance_matrix, &prizes, &config).unwrap();
+                let check = is_valid_orienteering(&distance_matrix, &prizes, &config, &heuristic);
+                assert_eq!(check, "valid", "The heuristic route is invalid for file {}: {}", file_name, check);
+                let exact = crate::orienteering::orienteering_held_karp(&distance_matrix, &prizes, &config).unwrap();
+                let check = is_valid_orienteering(&distance_matrix, &prizes, &config, &exact);
+                assert_eq!(check, "valid", "The DP route is invalid for file {}: {}", file_name, check);
+                assert!(heuristic.prize <= exact.prize + 1e-6, "Heuristic prize {} beats the DP {} for file {}", heuristic.prize, exact.prize, file_name);
+                if n <= 15 && budget <= 6.0 { // few enough arcs fit the budget to try every route
+                    assert!((exact.prize - brute_force_prize(&distance_matrix, &prizes, &config)).abs() < 1e-6, "DP prize is not the best for file {}", file_name);
+                }
+            }
+        }
+
+        // a star with a cheap worthless spoke, two valuable ones and a far prize. One loop out and back only flies one spoke
+        // and the far prize is over budget
+        let inf = f64::INFINITY;
+        let mut distance_matrix = vec![vec![inf; 5]; 5];
+        for (node, cost) in [(1, 1.0), (2, 2.0), (3, 2.0), (4, 5.0)] {
+            distance_matrix[0][node] = cost;
+            distance_matrix[node][0] = cost;
+        }
+        let prizes = vec![0.0, 0.0, 3.0, 4.0, 6.0];
+        let config = crate::orienteering::OrienteeringConfig { budget: 8.0, return_to_depot: true, ..Default::default() };
+        for route in [crate::orienteering::orienteering_heuristic(&distance_matrix, &prizes, &config).unwrap(),
+                      crate::orienteering::orienteering_held_karp(&distance_matrix, &prizes, &config).unwrap()] {
+            assert_eq!(is_valid_orienteering(&distance_matrix, &prizes, &config, &route), "valid");
+            assert_eq!((route.path.clone(), route.prize, route.cost, route.skipped.clone()), (vec![0, 3, 0], 4.0, 4.0, vec![1, 2, 4]));
+        }
+        // chaining the spokes lets an open path reach the far prize for less than the spoke to it
+        distance_matrix[2][3] = 1.0;
+        distance_matrix[3][4] = 1.0;
+        let config = crate::orienteering::OrienteeringConfig { budget: 4.0, ..Default::default() };
+        for route in [crate::orienteering::orienteering_heuristic(&distance_matrix, &prizes, &config).unwrap(),
+                      crate::orienteering::orienteering_held_karp(&distance_matrix, &prizes, &config).unwrap()] {
+            assert_eq!(is_valid_orienteering(&distance_matrix, &prizes, &config, &route), "valid");
+            assert_eq!((route.path.clone(), route.prize, route.skipped.clone()), (vec![0, 2, 3, 4], 13.0, vec![1]));
+        }
+        assert!(matches!(crate::orienteering::orienteering_held_karp(&vec![vec![1.0; 21]; 21], &prizes, &config), Err(crate::orienteering::OrienteeringError::TooManyNodes { .. })));
+        let config = crate::orienteering::OrienteeringConfig { budget: -1.0, ..Default::default() };
+        assert!(matches!(crate::orienteering::orienteering_heuristic(&distance_matrix, &prizes, &config), Err(crate::orienteering::OrienteeringError::NegativeBudget { .. })));
+    }
+    #[test]
+    fn test_cvrp_routes() {
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-sparse").into_iter().chain(test_worlds("test-csv-full-world")) {
+            let demands: Vec<f64> = (0..distance_matrix.len()).map(|node| if node == 0 { 0.0 } else { (node % 7 + 1) as f64 }).collect();
+            let savings_config = crate::cvrp::CvrpConfig { local_search: false, ferry_legs: true, ..Default::default() };
+            let savings = crate::cvrp::solve_cvrp(&distances, &demands, &savings_config).unwrap();
+            let check = is_valid_cvrp(&distance_matrix, &demands, &savings_config, &savings);
+            assert_eq!(check, "valid", "The savings routes are invalid for file {}: {}", file_name, check);
+            let config = crate::cvrp::CvrpConfig { ferry_legs: true, ..Default::default() };
+            let improved = crate::cvrp::solve_cvrp(&distances, &demands, &config).unwrap();
+            let check = is_valid_cvrp(&distance_matrix, &demands, &config, &improved);
+            assert_eq!(check, "valid", "The improved routes are invalid for file {}: {}", file_name, check);
+            assert!(improved.total_cost <= savings.total_cost + 1e-6, "Local search made {} worse than {} for file {}", improved.total_cost, savings.total_cost, file_name);
+            // without ferry legs the full worlds always have the arcs
+            if file_name.contains("full") {
+                let direct = crate::cvrp::solve_cvrp(&distances, &demands, &Default::default()).unwrap();
+                assert_eq!(is_valid_cvrp(&distance_matrix, &demands, &Default::default(), &direct), "valid");
+            }
+        }
+    }
+    #[test]
+    fn test_cvrp_capacity_splits_routes() {
+        // a star around depot 0 where 1 and 2 sit together and so do 3 and 4, each pair just fits one aircraft
+        let arcs = [(0, 1, 2.0), (1, 2, 1.0), (2, 0, 2.0), (0, 3, 2.0), (3, 4, 1.0), (4, 0, 2.0), (2, 3, 1.0), (1, 0, 2.0), (0, 2, 2.0), (3, 0, 2.0), (0, 4, 2.0)];
+        let mut distances: Distances = HashMap::new();
+        for (from, to, cost) in arcs {
+            distances.entry(from).or_default().insert(to, cost);
+        }
+        let mut distance_matrix = vec![vec![f64::INFINITY; 5]; 5];
+        for (from, to, cost) in arcs {
+            distance_matrix[from][to] = cost;
+        }
+        let demands = vec![0.0, 4.0, 5.0, 5.0, 4.0];
+        let config = crate::cvrp::CvrpConfig { capacity: 9.0, ..Default::default() };
+        let solution = crate::cvrp::solve_cvrp(&distances, &demands, &config).unwrap();
+        assert_eq!(is_valid_cvrp(&distance_matrix, &demands, &config, &solution), "valid");
+        let mut paths: Vec<Vec<usize>> = solution.routes.iter().map(|route| route.path.clone()).collect();
+        paths.sort();
+        assert_eq!(paths, vec![vec![0, 1, 2, 0], vec![0, 3, 4, 0]]);
+        assert_eq!(solution.total_cost, 10.0);
+        assert_eq!(solution.routes[0].load_profile, if solution.routes[0].path[1] == 1 { vec![9.0, 5.0, 0.0, 0.0] } else { vec![9.0, 4.0, 0.0, 0.0] });
+
+        // enough room for everything is one route through the 2 -> 3 arc, a location heavier than an aircraft can't be served
+        let config = crate::cvrp::CvrpConfig { capacity: 18.0, ..Default::default() };
+        let solution = crate::cvrp::solve_cvrp(&distances, &demands, &config).unwrap();
+        assert_eq!(is_valid_cvrp(&distance_matrix, &demands, &config, &solution), "valid");
+        assert_eq!((solution.routes.len(), solution.total_cost), (1, 7.0));
+        let config = crate::cvrp::CvrpConfig { capacity: 4.5, ..Default::default() };
+        assert!(matches!(crate::cvrp::solve_cvrp(&distances, &demands, &config), Err(crate::cvrp::CvrpError::DemandTooLarge { capacity: 4.5, .. })));
+    }
+    #[test]
+    fn test_assignment_problem() {
+        let cost_matrix = vec![
+            vec![4.0, 1.0, 3.0],
+            vec![2.0, 0.0, 5.0],
+            vec![3.0, 2.0, 2.0],
+        ];
+        let (cost, assignment) = crate::lower_bounds::assignment_problem(&cost_matrix);
+        assert_eq!(assignment, vec![1, 0, 2]);
+        assert!((cost - 5.0).abs() < 1e-9, "Expected cost 5 but got {}", cost);
+
+        // forbidding the cheap entries moves the optimum, forbidding a whole column makes it infeasible
+        let cost_matrix = vec![
+            vec![4.0, f64::INFINITY, 3.0],
+            vec![2.0, 0.0, 5.0],
+            vec![3.5, f64::INFINITY, 2.0],
+        ];
+        let (cost, assignment) = crate::lower_bounds::assignment_problem(&cost_matrix);
+        assert_eq!(assignment, vec![0, 1, 2]);
+        assert!((cost - 6.0).abs() < 1e-9, "Expected cost 6 but got {}", cost);
+        let cost_matrix = vec![
+            vec![4.0, f64::INFINITY],
+            vec![2.0, f64::INFINITY],
+        ];
+        assert_eq!(crate::lower_bounds::assignment_problem(&cost_matrix).0, f64::INFINITY);
+    }
+    #[test]
+    fn test_held_karp_modes() {
+        let worlds = test_worlds("test-csv-sparse");
+        let (file_name, _distances, distance_matrix) = worlds.iter().find(|(_, _, matrix)| matrix.len() <= 15).expect("No small sparse world");
+        let n = distance_matrix.len();
+        let (best_cost, best_path) = crate::algorithms::held_karp_algorithm(distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+        assert_eq!(is_valid_route(distance_matrix, &best_cost, &best_path), "valid", "Best open path invalid for file {}", file_name);
+
+        // the single pass has to agree with restarting from every depot
+        let mut cheapest_start = f64::INFINITY;
+        for start in 0..n {
+            let (cost, path) = crate::algorithms::held_karp_algorithm(distance_matrix, HeldKarpMode::FixedStart(start), &HeldKarpConfig::default()).unwrap();
+            if cost.is_finite() {
+                assert_eq!(path[0], start);
+                assert_eq!(is_valid_route(distance_matrix, &cost, &path), "valid", "Start {} invalid for file {}", start, file_name);
+            } else {
+                assert!(path.is_empty());
+            }
+            cheapest_start = cheapest_start.min(cost);
+        }
+        assert!((cheapest_start - best_cost).abs() < 1e-4, "Best open path {} but cheapest depot {} for file {}", best_cost, cheapest_start, file_name);
+
+        let (start, end) = (best_path[0], best_path[n - 1]);
+        let (cost, path) = crate::algorithms::held_karp_algorithm(distance_matrix, HeldKarpMode::FixedStartEnd(start, end), &HeldKarpConfig::default()).unwrap();
+        assert!((cost - best_cost).abs() < 1e-4);
+        assert_eq!((path[0], path[n - 1]), (start, end));
+        let (cost, path) = crate::algorithms::held_karp_algorithm(distance_matrix, HeldKarpMode::FixedStartEnd(start, start), &HeldKarpConfig::default()).unwrap();
+        assert_eq!(cost, f64::INFINITY);
+        assert!(path.is_empty());
+    }
+    #[test]
+    fn test_held_karp_closed_tour() {
+        // a one way ring 0 -> 1 -> 2 -> 3 -> 0 with cheap shortcuts that can't close the tour
+        let mut distance_matrix = vec![vec![f64::INFINITY; 4]; 4];
+        for i in 0..4 {
+            distance_matrix[i][(i + 1) % 4] = 2.0;
+        }
+        distance_matrix[0][2] = 1.0;
+        distance_matrix[2][1] = 1.0;
+        let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::ClosedTour(1), &HeldKarpConfig::default()).unwrap();
+        assert_eq!(path, vec![1, 2, 3, 0, 1]);
+        assert!((cost - 8.0).abs() < 1e-9);
+        let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+        assert_eq!(path, vec![3, 0, 2, 1]);
+        assert!((cost - 4.0).abs() < 1e-9);
+        let result = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::ClosedTour(4), &HeldKarpConfig::default());
+        assert_eq!(result, Err(HeldKarpError::DepotOutOfRange { depot: 4, nodes: 4 }));
+    }
+    #[test]
+    fn test_held_karp_threads_and_memory_limit() {
+        let worlds = test_worlds("test-csv-sparse");
+        let (file_name, _distances, distance_matrix) = worlds.iter().find(|(_, _, matrix)| matrix.len() <= 15).expect("No small sparse world");
+        let single = HeldKarpConfig { threads: 1, ..Default::default() };
+        let parallel = HeldKarpConfig { threads: 4, ..Default::default() };
+        for mode in [HeldKarpMode::BestOpenPath, HeldKarpMode::FixedStart(0), HeldKarpMode::ClosedTour(0)] {
+            let expected = crate::algorithms::held_karp_algorithm(distance_matrix, mode, &single).unwrap();
+            assert_eq!(crate::algorithms::held_karp_algorithm(distance_matrix, mode, &parallel).unwrap(), expected, "{:?} differs between thread counts for {}", mode, file_name);
+        }
+
+        // 25 locations with a depot is 24 * 2^23 slots of 5 bytes
+        assert_eq!(crate::algorithms::held_karp_memory_estimate(25, HeldKarpMode::FixedStart(0)), 24 * (1 << 23) * 5);
+        let tight = HeldKarpConfig { memory_limit_bytes: 1 << 20, ..Default::default() };
+        let required_bytes = crate::algorithms::held_karp_memory_estimate(distance_matrix.len(), HeldKarpMode::BestOpenPath);
+        assert_eq!(crate::algorithms::held_karp_algorithm(distance_matrix, HeldKarpMode::BestOpenPath, &tight), Err(HeldKarpError::OutOfMemory { required_bytes, limit_bytes: 1 << 20 }));
+        let too_many = vec![vec![1.0; 40]; 40];
+        assert_eq!(crate::algorithms::held_karp_algorithm(&too_many, HeldKarpMode::BestOpenPath, &Default::default()), Err(HeldKarpError::TooManyNodes { nodes: 40, max: 30 }));
+    }
+    #[test]
+    fn test_christofides_full_graph() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-full-world") {
+            let symmetry = crate::algorithms::detect_symmetry(&distance_matrix, 1e-9);
+            assert!(!symmetry.symmetric && symmetry.one_way_pairs == 0, "Full worlds are random in both directions, {}", file_name);
+            let christofides = crate::algorithms::christofides(&distance_matrix, &symmetry);
+            assert!(christofides.used_matching, "100 locations is too many odd nodes for the exact matching but the greedy one still runs, {}", file_name);
+            let double_tree = crate::algorithms::double_tree(&distance_matrix, &symmetry);
+            for result in [christofides, double_tree] {
+                let check = is_valid_route(&distance_matrix, &result.cost, &result.path);
+                assert_eq!(check, "valid", "The route is invalid for file {}: {}", file_name, check);
+                assert_eq!(result.approximation_ratio, None);
+            }
+        }
+    }
+    #[test]
+    fn test_christofides_bound_on_symmetric_metric() {
+        // euclidean points are symmetric and metric, so the closed tours must be within 1.5 and 2 of the optimal tour
+        let mut rng = crate::rng::SeededRng::new(9);
+        for _ in 0..5 {
+            let points: Vec<(f64, f64)> = (0..12).map(|_| (rng.next_f64() * 100.0, rng.next_f64() * 100.0)).collect();
+            let distance_matrix: Vec<Vec<f64>> = points.iter().enumerate().map(|(i, a)| points.iter().enumerate().map(|(j, b)|
+                if i == j { f64::INFINITY } else { ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt() }).collect()).collect();
+            let symmetry = crate::algorithms::detect_symmetry(&distance_matrix, 1e-9);
+            assert!(symmetry.symmetric && symmetry.metric);
+            let (optimal_tour, _) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::ClosedTour(0), &HeldKarpConfig::default()).unwrap();
+
+            let christofides = crate::algorithms::christofides(&distance_matrix, &symmetry);
+            assert!(christofides.used_matching && christofides.exact_matching);
+            assert_eq!(christofides.approximation_ratio, Some(1.5));
+            assert!(christofides.tour_cost <= 1.5 * optimal_tour + 1e-6, "Christofides tour {} against optimal {}", christofides.tour_cost, optimal_tour);
+            assert!(christofides.cost <= christofides.tour_cost + 1e-9);
+            assert_eq!(is_valid_route(&distance_matrix, &christofides.cost, &christofides.path), "valid");
+
+            let double_tree = crate::algorithms::double_tree(&distance_matrix, &symmetry);
+            assert_eq!(double_tree.approximation_ratio, Some(2.0));
+            assert!(double_tree.tour_cost <= 2.0 * optimal_tour + 1e-6, "Double tree tour {} against optimal {}", double_tree.tour_cost, optimal_tour);
+        }
+
+        // 60 euclidean points have more odd MST nodes than the exact matching takes, the greedy one keeps Christofides
+        // running (and no worse than the doubled tree it would otherwise fall back to)
+        let points: Vec<(f64, f64)> = (0..60).map(|_| (rng.next_f64() * 100.0, rng.next_f64() * 100.0)).collect();
+        let distance_matrix: Vec<Vec<f64>> = points.iter().enumerate().map(|(i, a)| points.iter().enumerate().map(|(j, b)|
+            if i == j { f64::INFINITY } else { ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt() }).collect()).collect();
+        let symmetry = crate::algorithms::detect_symmetry(&distance_matrix, 1e-9);
+        let christofides = crate::algorithms::christofides(&distance_matrix, &symmetry);
+        assert!(christofides.used_matching && !christofides.exact_matching);
+        assert_eq!(christofides.approximation_ratio, None);
+        assert_eq!(is_valid_route(&distance_matrix, &christofides.cost, &christofides.path), "valid");
+        let double_tree = crate::algorithms::double_tree(&distance_matrix, &symmetry);
+        assert!(christofides.tour_cost <= double_tree.tour_cost + 1e-6, "Greedy Christofides {} against double tree {}", christofides.tour_cost, double_tree.tour_cost);
+    }
+    #[test]
+    fn test_symmetric_transformation_round_trip() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 15 {
+                continue;
+            }
+            let (cost, path) = crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+            let symmetric = crate::transformations::asymmetric_to_symmetric(&distance_matrix, true);
+            let n = symmetric.matrix.len();
+            assert_eq!(n, 2 * (distance_matrix.len() + 1));
+            assert!((0..n).all(|i| (0..n).all(|j| symmetric.matrix[i][j] == symmetric.matrix[j][i])), "Not symmetric for {}", file_name);
+
+            let tour = symmetric.symmetric_tour(&path);
+            let directed_cost = symmetric.directed_cost(closed_tour_cost(&symmetric.matrix, &tour));
+            assert!((directed_cost - cost).abs() < 1e-6, "Held-Karp {} but {} after the round trip for {}", cost, directed_cost, file_name);
+            assert_eq!(symmetric.directed_tour(&tour), Some(path.clone()));
+            // the same tour walked backwards and closed with its start maps to the same path
+            let mut reversed: Vec<usize> = tour.iter().rev().copied().collect();
+            reversed.push(reversed[0]);
+            assert_eq!(symmetric.directed_tour(&reversed), Some(path));
+        }
+    }
+    #[test]
+    fn test_symmetric_transformation_solves_directed() {
+        // solving the symmetric version exactly gives the directed optimum, both for open paths and closed tours
+        let mut rng = crate::rng::SeededRng::new(5);
+        for _ in 0..4 {
+            let distance_matrix: Vec<Vec<f64>> = (0..5).map(|i| (0..5).map(|j|
+                if i == j || rng.next_f64() < 0.2 { f64::INFINITY } else { 1.0 + (rng.next_f64() * 9.0).round() }).collect()).collect();
+            for (open_path, mode) in [(true, HeldKarpMode::BestOpenPath), (false, HeldKarpMode::ClosedTour(0))] {
+                let (directed_cost, _) = crate::algorithms::held_karp_algorithm(&distance_matrix, mode, &HeldKarpConfig::default()).unwrap();
+                let symmetric = crate::transformations::asymmetric_to_symmetric(&distance_matrix, open_path);
+                let (symmetric_cost, symmetric_path) = crate::algorithms::held_karp_algorithm(&symmetric.matrix, HeldKarpMode::ClosedTour(0), &HeldKarpConfig::default()).unwrap();
+                if directed_cost == f64::INFINITY {
+                    // a finite symmetric tour has to skip a free edge, so it doesn't map back
+                    assert!(symmetric_cost == f64::INFINITY || symmetric.directed_tour(&symmetric_path).is_none());
+                    continue;
+                }
+                assert!((symmetric.directed_cost(symmetric_cost) - directed_cost).abs() < 1e-6);
+                let directed = symmetric.directed_tour(&symmetric_path).expect("Optimal symmetric tour uses every free edge");
+                let mapped_cost = if open_path {
+                    directed.windows(2).map(|arc| distance_matrix[arc[0]][arc[1]]).sum()
+                } else {
+                    closed_tour_cost(&distance_matrix, &directed)
+                };
+                assert!((mapped_cost - directed_cost).abs() < 1e-6, "Mapped tour costs {} instead of {}", mapped_cost, directed_cost);
+            }
+        }
+    }
+    #[test]
+    fn test_multi_start_nearest_neighbor() {
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-full-world") {
+            let config = crate::algorithms::MultiStartConfig { seed: 7, threads: 1, ..Default::default() };
+            let (cost, path) = crate::algorithms::multi_start_nearest_neighbor(&distances, &config);
+            let check = is_valid_route(&distance_matrix, &cost, &path);
+            assert_eq!(check, "valid", "The route is invalid for file {}: {}", file_name, check);
+
+            // same seed gives the same path whatever the thread count
+            let parallel = crate::algorithms::MultiStartConfig { threads: 4, ..config };
+            assert_eq!(crate::algorithms::multi_start_nearest_neighbor(&distances, &parallel), (cost, path.clone()), "Thread count changed the result for {}", file_name);
+
+            // a sample of the starts can't beat trying all of them
+            let sampled = crate::algorithms::MultiStartConfig { starts: Some(3), ..config };
+            let (sampled_cost, sampled_path) = crate::algorithms::multi_start_nearest_neighbor(&distances, &sampled);
+            assert_eq!(is_valid_route(&distance_matrix, &sampled_cost, &sampled_path), "valid");
+            assert!(sampled_cost >= cost - 1e-9);
+        }
+    }
+    #[test]
+    fn test_metric_closure_floyd_warshall_matches_dijkstra() {
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 50 {
+                continue;
+            }
+            let floyd = crate::metric_closure::floyd_warshall(&distance_matrix);
+            let dijkstra = crate::metric_closure::dijkstra_all_pairs(&distance_matrix);
+            let n = distance_matrix.len();
+            for from in 0..n {
+                for to in 0..n {
+                    let (a, b) = (floyd.distances[from][to], dijkstra.distances[from][to]);
+                    assert!(a == b || (a - b).abs() < 1e-9, "{} -> {} is {} and {} in {}", from, to, a, b, file_name);
+                    if from != to && a.is_finite() {
+                        assert!(a <= distance_matrix[from][to]);
+                        for closure in [&floyd, &dijkstra] {
+                            let leg = closure.leg(from, to);
+                            let leg_cost: f64 = leg.windows(2).map(|arc| distance_matrix[arc[0]][arc[1]]).sum();
+                            assert!((leg_cost - a).abs() < 1e-9, "Leg {:?} costs {} not {} in {}", leg, leg_cost, a, file_name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_ferry_legs_held_karp() {
+        let held_karp = |matrix: &[Vec<f64>]| crate::algorithms::held_karp_algorithm(matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 15 {
+                continue;
+            }
+            let route = crate::metric_closure::solve_with_ferry_legs(&distance_matrix, held_karp);
+            assert_eq!(check_ferry_route(&distance_matrix, &route), "valid", "Ferry route invalid for {}", file_name);
+            let (strict_cost, _) = held_karp(&distance_matrix);
+            assert!(route.cost <= strict_cost + 1e-4, "Ferry legs {} against {} without them for {}", route.cost, strict_cost, file_name);
+        }
+
+        // a hub with three spokes has no path that lands everywhere once, with ferry legs it flies back through the hub
+        let mut distance_matrix = vec![vec![f64::INFINITY; 4]; 4];
+        for (spoke, cost) in [(1, 1.0), (2, 2.0), (3, 3.0)] {
+            distance_matrix[0][spoke] = cost;
+            distance_matrix[spoke][0] = 1.0;
+        }
+        assert_eq!(held_karp(&distance_matrix).0, f64::INFINITY);
+        let route = crate::metric_closure::solve_with_ferry_legs(&distance_matrix, held_karp);
+        assert_eq!(check_ferry_route(&distance_matrix, &route), "valid");
+        assert_eq!(route.stops.iter().filter(|stop| stop.pass_through).count(), 1);
+        assert_eq!(route.visit_order[0], 3); // the expensive spoke is only worth flying out of
+        assert!((route.cost - 5.0).abs() < 1e-9, "Expected 5 but got {}", route.cost);
+    }
+    #[test]
+    fn test_steiner_route() {
+        let held_karp = |matrix: &[Vec<f64>]| crate::algorithms::held_karp_algorithm(matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap_or((f64::INFINITY, Vec::new()));
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            if distance_matrix.len() > 15 {
+                continue;
+            }
+            // without flags every airport is required and the route is the ferry leg route
+            let all = crate::steiner::solve_steiner(&distance_matrix, &[], held_karp);
+            assert_eq!(check_steiner_route(&distance_matrix, &[], &all), "valid", "Steiner route invalid for {}", file_name);
+            let ferry = crate::metric_closure::solve_with_ferry_legs(&distance_matrix, held_karp);
+            assert!((all.cost - ferry.cost).abs() < 1e-4, "All required {} against ferry legs {} for {}", all.cost, ferry.cost, file_name);
+            let required: Vec<bool> = (0..distance_matrix.len()).map(|node| node % 3 != 1).collect();
+            let route = crate::steiner::solve_steiner(&distance_matrix, &required, held_karp);
+            assert_eq!(check_steiner_route(&distance_matrix, &required, &route), "valid", "Steiner route invalid for {}", file_name);
+            assert!(route.cost <= all.cost + 1e-4, "Fewer required airports cost {} against {} for {}", route.cost, all.cost, file_name);
+        }
+
+        // airports 2 and 3 only connect through the optional hub 1, and optional 4 has no arcs at all so no route visits
+        // every airport. Covering the required ones flies through the hub twice and leaves 4 out
+        let mut distance_matrix = vec![vec![f64::INFINITY; 5]; 5];
+        for (from, to, cost) in [(0, 1, 1.0), (1, 2, 2.0), (2, 1, 1.0), (1, 3, 3.0)] {
+            distance_matrix[from][to] = cost;
+        }
+        assert!(!crate::feasibility::check_matrix(&distance_matrix).is_feasible());
+        let required = vec![true, false, true, true, false];
+        let route = crate::steiner::solve_steiner(&distance_matrix, &required, held_karp);
+        assert_eq!(check_steiner_route(&distance_matrix, &required, &route), "valid");
+        assert_eq!(route.visit_order, vec![0, 2, 3]);
+        assert_eq!(route.optional_flown, vec![1]);
+        assert!((route.cost - 7.0).abs() < 1e-9, "Expected 7 but got {}", route.cost);
+        // requiring the isolated airport makes it impossible again
+        let required = vec![true, false, true, true, true];
+        assert_eq!(crate::steiner::solve_steiner(&distance_matrix, &required, held_karp).cost, f64::INFINITY);
+        // a single required airport is a route on its own
+        let route = crate::steiner::solve_steiner(&distance_matrix, &[false, false, true, false, false], held_karp);
+        assert_eq!((route.cost, route.visit_order), (0.0, vec![2]));
+    }
+    #[test]
+    fn test_gtsp() {
+        let held_karp = |matrix: &[Vec<f64>]| crate::algorithms::held_karp_algorithm(matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()).unwrap();
+        let config = crate::gtsp::GtspConfig::default();
+        for (file_name, _distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            let n = distance_matrix.len();
+            if n > 15 {
+                continue;
+            }
+            // every location a cluster of its own is the plain TSP
+            let singles: Vec<usize> = (0..n).collect();
+            let exact = crate::gtsp::gtsp_held_karp(&distance_matrix, &singles, &config).unwrap();
+            assert!((exact.cost - held_karp(&distance_matrix).0).abs() < 1e-4, "Singleton clusters don't match Held-Karp for {}", file_name);
+            // clusters of three on the closure, the DP against every choice of one location per cluster
+            let closure = crate::metric_closure::metric_closure(&distance_matrix).distances;
+            let cluster_of: Vec<usize> = (0..n).map(|node| node / 3).collect();
+            let exact = crate::gtsp::gtsp_held_karp(&closure, &cluster_of, &config).unwrap();
+            let heuristic = crate::gtsp::gtsp_heuristic(&closure, &cluster_of, &config);
+            for route in [&exact, &heuristic] {
+                let check = is_valid_gtsp(&closure, &cluster_of, route);
+                assert_eq!(check, "valid", "The GTSP route is invalid for file {}: {}", file_name, check);
+            }
+            assert!(exact.cost <= heuristic.cost + 1e-6, "Heuristic {} beats the DP {} for file {}", heuristic.cost, exact.cost, file_name);
+            let clusters = crate::gtsp::cluster_lists(n, &cluster_of);
+            let mut best = f64::INFINITY;
+            for choice in 0..clusters.iter().map(|members| members.len()).product::<usize>() {
+                let mut rest = choice;
+                let picked: Vec<usize> = clusters.iter().map(|members| {
+                    let node = members[rest % members.len()];
+                    rest /= members.len();
+                    node
+                }).collect();
+                let sub: Vec<Vec<f64>> = picked.iter().map(|&from| picked.iter().map(|&to| closure[from][to]).collect()).collect();
+                best = best.min(held_karp(&sub).0);
+            }
+            assert!((exact.cost - best).abs() < 1e-4, "DP cost {} but the best choice costs {} for {}", exact.cost, best, file_name);
+        }
+
+        // two cities of two airports and a third of one. The cheap arcs only join the second airport of each city
+        let mut distance_matrix = vec![vec![10.0; 5]; 5];
+        for (from, to) in [(1, 3), (3, 4)] {
+            distance_matrix[from][to] = 1.0;
+        }
+        for (node, row) in distance_matrix.iter_mut().enumerate() {
+            row[node] = f64::INFINITY;
+        }
+        let cluster_of = vec![0, 0, 1, 1, 2];
+        for route in [crate::gtsp::gtsp_held_karp(&distance_matrix, &cluster_of, &config).unwrap(),
+                      crate::gtsp::gtsp_heuristic(&distance_matrix, &cluster_of, &config)] {
+            assert_eq!(is_valid_gtsp(&distance_matrix, &cluster_of, &route), "valid");
+            assert_eq!((route.path, route.chosen, route.cost), (vec![1, 3, 4], vec![1, 3, 4], 2.0));
+        }
+        assert!(matches!(crate::gtsp::gtsp_held_karp(&vec![vec![1.0; 30]; 30], &[], &config), Err(crate::gtsp::GtspError::TooManyClusters { clusters: 30, .. })));
+    }
+    fn adjacency(arcs: &[(usize, usize)]) -> Distances {
+        let mut distances: Distances = HashMap::new();
+        for &(from, to) in arcs {
+            distances.entry(from).or_default().insert(to, 1.0);
+        }
+        distances
+    }
+    #[test]
+    fn test_feasibility_test_worlds() {
+        for (file_name, distances, distance_matrix) in test_worlds("test-csv-sparse") {
+            let report = crate::feasibility::check(&distances);
+            assert!(report.is_feasible(), "{}: {}", file_name, report);
+            assert_eq!(report, crate::feasibility::check_matrix(&distance_matrix));
+            let mut nodes: Vec<usize> = report.components.concat();
+            nodes.sort();
+            assert_eq!(nodes, (0..distance_matrix.len()).collect::<Vec<usize>>(), "Components don't partition the nodes of {}", file_name);
+        }
+    }
+    #[test]
+    fn test_feasibility_obstructions() {
+        use crate::feasibility::Obstruction;
+        // 2 and 3 are both dead ends, the solvers give up before doing any work
+        let distances = adjacency(&[(0, 1), (1, 0), (0, 2), (1, 3)]);
+        let report = crate::feasibility::check(&distances);
+        assert_eq!(report.nodes, 4);
+        assert_eq!(report.obstructions[0], Obstruction::TooManySinks(vec![2, 3]));
+        assert_eq!(report.obstructions.len(), 2); // and no arc between the two dead end components
+        assert!(matches!(report.obstructions[1], Obstruction::BrokenChain { .. }));
+        assert_eq!(crate::algorithms::nearest_neighbor_sparse(&distances), (f64::INFINITY, Vec::new()));
+        let mut distance_matrix = vec![vec![f64::INFINITY; 4]; 4];
+        for (&from, inner_map) in &distances {
+            for (&to, &cost) in inner_map {
+                distance_matrix[from][to] = cost;
+            }
+        }
+        assert_eq!(crate::algorithms::held_karp_algorithm(&distance_matrix, HeldKarpMode::BestOpenPath, &HeldKarpConfig::default()),
+            Err(HeldKarpError::Infeasible(Box::new(report))));
+
+        // two separate loops, the condensation has no arc between its components
+        let report = crate::feasibility::check(&adjacency(&[(0, 1), (1, 0), (2, 3), (3, 2)]));
+        assert_eq!(report.components.len(), 2);
+        assert_eq!(report.obstructions, vec![Obstruction::BrokenChain { from: 0, to: 1 }]);
+
+        // 0 has to start, so 1 and 2 (one way in each) both need their arc out of 0
+        let report = crate::feasibility::check(&adjacency(&[(0, 1), (0, 2), (1, 3), (2, 3)]));
+        assert!(report.obstructions.contains(&Obstruction::ConflictingForcedArcs { node: 0, arcs: vec![(0, 1), (0, 2)] }), "{}", report);
+
+        // 1 and 2 can only be reached from each other once 0 starts the path
+        let report = crate::feasibility::check(&adjacency(&[(0, 3), (1, 2), (2, 1), (2, 3)]));
+        assert!(report.forced_arcs.contains(&(1, 2)) && report.forced_arcs.contains(&(2, 1)));
+        assert!(report.obstructions.contains(&Obstruction::ForcedCycle(vec![1, 2])), "{}", report);
+
+        // a plain path is fine, every arc is forced
+        let report = crate::feasibility::check(&adjacency(&[(0, 1), (1, 2), (2, 3)]));
+        assert!(report.is_feasible());
+        assert_eq!(report.forced_arcs, vec![(0, 1), (1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn test_sidecar_readers() {
+        // the sample second csvs main reads next to a world, written for 15 locations
+        let required = crate::csv_readers::read_required_csv("test-csv-sidecars/required.csv", 15).unwrap();
+        assert_eq!((0..15).filter(|&node| !required[node]).collect::<Vec<_>>(), vec![3, 7, 11]);
+        assert_eq!(crate::csv_readers::read_precedence_csv("test-csv-sidecars/precedence.csv", 15).unwrap(), vec![(2, 9), (5, 13), (1, 5)]);
+        let windows = crate::csv_readers::read_time_windows_csv("test-csv-sidecars/windows.csv", 15).unwrap();
+        assert_eq!((windows[4].earliest, windows[4].latest, windows[4].service), (0.0, 40.0, 1.0));
+        assert_eq!((windows[9].earliest, windows[9].latest, windows[9].service), (10.0, f64::INFINITY, 2.0));
+        assert_eq!((windows[13].earliest, windows[13].latest, windows[13].service), (5.0, 60.0, 0.0));
+        let mut types = crate::csv_readers::read_aircraft_types_csv("test-csv-sidecars/aircraft.csv", 3, 15).unwrap();
+        crate::csv_readers::read_aircraft_costs_csv("test-csv-sidecars/aircraft_costs.csv", &mut types, 15).unwrap();
+        assert_eq!((types[0].cost_multiplier, types[1].cost_multiplier, types[2].cost_multiplier), (1.0, 0.8, 1.3));
+        assert_eq!((types[1].prohibited_nodes.clone(), types[2].prohibited_arcs.clone()), (vec![6, 10], vec![(0, 1), (4, 2)]));
+        assert!(types[0].cost_table.is_none() && types[1].cost_table.is_none());
+        assert_eq!(types[2].cost_table.as_ref().map(|table| (table[&0][&2], table[&2][&0])), Some((3.0, 3.0)));
+        let demands = crate::csv_readers::read_demands_csv("test-csv-sidecars/demands.csv", 15).unwrap();
+        assert_eq!((demands[0], demands[2], demands[8], demands.iter().sum::<f64>()), (0.0, 7.0, 10.0, 26.0));
+        let prizes = crate::csv_readers::read_prizes_csv("test-csv-sidecars/prizes.csv", 15).unwrap();
+        assert_eq!((prizes[4], prizes[10], prizes.iter().sum::<f64>()), (8.0, 9.0, 27.0));
+        // locations without a cluster row get clusters of their own after the highest id
+        let cluster_of = crate::csv_readers::read_clusters_csv("test-csv-sidecars/clusters.csv", 15).unwrap();
+        assert_eq!(cluster_of, vec![0, 0, 1, 1, 1, 2, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+        // a smaller world than the samples are written for is an error, not a silently shorter list
+        assert!(crate::csv_readers::read_prizes_csv("test-csv-sidecars/prizes.csv", 10).is_err());
+        assert!(crate::csv_readers::read_aircraft_types_csv("test-csv-sidecars/aircraft.csv", 2, 15).is_err());
+
+        // malformed rows are reported with their line instead of skipped, blank lines are fine
+        let file_path = std::env::temp_dir().join(format!("sidecar_readers_{}.csv", std::process::id()));
+        let file_name = file_path.to_str().unwrap();
+        for (contents, line) in [("Location,Prize\n1,3\n4\n", "line 3"), ("Location,Prize\n1,3\n\nfour,8\n", "line 4"), ("Location,Prize\n1,lots\n", "line 2")] {
+            fs::write(&file_path, contents).unwrap();
+            let error = crate::csv_readers::read_prizes_csv(file_name, 15).unwrap_err().to_string();
+            assert!(error.contains(line), "{:?} should fail at {} but got {}", contents, line, error);
+        }
+        fs::write(&file_path, "Before,After\n2,9\n\n5,13\n").unwrap();
+        assert_eq!(crate::csv_readers::read_precedence_csv(file_name, 15).unwrap(), vec![(2, 9), (5, 13)]);
+        fs::remove_file(&file_path).unwrap();
+    }
+}
@@ -0,0 +1,322 @@
+// Time windows at airports (TSPTW). The csv cost of an arc is its flight time, every location may have an earliest and
+// latest arrival and a service (ground) time. Arriving early means waiting for the window to open, arriving after it
+// closes is lateness. Relaxed mode trades lateness against flight time with a penalty, strict mode rejects any lateness.
+
+use std::fmt;
+use crate::fleet::MISSING_ARC_PENALTY; // shared with the fleet, routes through arcs that don't exist lose to every real route
+
+const RELOCATE_WINDOW: usize = 25;    // local search only moves a location this many positions, keeps 100 locations fast
+const BEAM_WIDTH: usize = 200;        // partial routes the on-time beam search keeps per length
+const EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeWindow {
+    pub earliest: f64,
+    pub latest: f64, // latest arrival
+    pub service: f64,
+}
+
+impl Default for TimeWindow {
+    fn default() -> Self {
+        TimeWindow { earliest: 0.0, latest: f64::INFINITY, service: 0.0 }
+    }
+}
+
+pub struct TimeWindowConfig {
+    pub strict: bool,
+    pub lateness_penalty: f64, // relaxed mode cost of one unit of lateness, in flight time
+    pub start_time: f64,       // the aircraft can't leave before this
+}
+
+impl Default for TimeWindowConfig {
+    fn default() -> Self {
+        TimeWindowConfig {
+            strict: false,
+            lateness_penalty: 10.0,
+            start_time: 0.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ScheduledStop {
+    pub node: usize,
+    pub arrival: f64,
+    pub departure: f64, // after waiting for the window and the service time
+    pub wait: f64,
+    pub lateness: f64,
+}
+
+#[derive(Debug)]
+pub struct Schedule {
+    pub cost: f64, // flight time, infinity if an arc is missing
+    pub path: Vec<usize>,
+    pub stops: Vec<ScheduledStop>,
+    pub total_wait: f64,
+    pub total_lateness: f64,
+    pub completion_time: f64, // departure from the last stop
+}
+
+#[derive(Debug)]
+pub enum TimeWindowError {
+    NoRoute,
+    WindowsViolated { late_stops: Vec<usize>, best: Box<Schedule> }, // strict mode, the best route found was still late
+}
+
+impl fmt::Display for TimeWindowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeWindowError::NoRoute => write!(f, "no route visits every location with the arcs in the world"),
+            TimeWindowError::WindowsViolated { late_stops, best } =>
+                write!(f, "best route is {} late in total, arriving after the window at {:?}", best.total_lateness, late_stops),
+        }
+    }
+}
+
+impl std::error::Error for TimeWindowError {}
+
+struct Evaluation {
+    flight: f64, // flight time over the arcs that exist
+    missing: usize,
+    wait: f64,
+    lateness: f64,
+}
+
+fn evaluate(distance_matrix: &[Vec<f64>], windows: &[TimeWindow], path: &[usize], start_time: f64) -> Evaluation {
+    // same walk as schedule_path without building the stops, a missing arc is counted and flown in no time
+    let mut evaluation = Evaluation { flight: 0.0, missing: 0, wait: 0.0, lateness: 0.0 };
+    let mut time = start_time;
+    for (k, &node) in path.iter().enumerate() {
+        let window = &windows[node];
+        let arrival = if k == 0 {
+            time.max(window.earliest)
+        } else {
+            let leg = distance_matrix[path[k - 1]][node];
+            if leg.is_finite() {
+                evaluation.flight += leg;
+                time + leg
+            } else {
+                evaluation.missing += 1;
+                time
+            }
+        };
+        evaluation.lateness += (arrival - window.latest).max(0.0);
+        evaluation.wait += (window.earliest - arrival).max(0.0);
+        time = arrival.max(window.earliest) + window.service;
+    }
+    evaluation
+}
+
+fn objective_key(evaluation: &Evaluation, config: &TimeWindowConfig) -> (f64, f64) {
+    let missing = evaluation.missing as f64 * MISSING_ARC_PENALTY;
+    if config.strict {
+        (missing + evaluation.lateness, evaluation.flight)
+    } else {
+        (missing + evaluation.flight + config.lateness_penalty * evaluation.lateness, evaluation.wait)
+    }
+}
+
+fn better(candidate: (f64, f64), current: (f64, f64)) -> bool {
+    candidate.0 < current.0 - EPSILON || (candidate.0 <= current.0 + EPSILON && candidate.1 < current.1 - EPSILON)
+}
+
+pub fn schedule_path(distance_matrix: &[Vec<f64>], windows: &[TimeWindow], path: &[usize], start_time: f64) -> Schedule {
+    // arrival and departure at every stop flying the path as early as possible
+    let mut stops = Vec::with_capacity(path.len());
+    let mut cost = 0.0;
+    let mut time = start_time;
+    for (k, &node) in path.iter().enumerate() {
+        let window = &windows[node];
+        let arrival = if k == 0 {
+            time.max(window.earliest)
+        } else {
+            let leg = distance_matrix[path[k - 1]][node];
+            cost += leg;
+            time + leg
+        };
+        let wait = (window.earliest - arrival).max(0.0);
+        let departure = arrival + wait + window.service;
+        stops.push(ScheduledStop { node, arrival, departure, wait, lateness: (arrival - window.latest).max(0.0) });
+        time = departure;
+    }
+    Schedule {
+        cost: if path.is_empty() { f64::INFINITY } else { cost },
+        path: path.to_vec(),
+        total_wait: stops.iter().map(|stop| stop.wait).sum(),
+        total_lateness: stops.iter().map(|stop| stop.lateness).sum(),
+        completion_time: stops.last().map_or(start_time, |stop| stop.departure),
+        stops,
+    }
+}
+
+fn construct(distance_matrix: &[Vec<f64>], windows: &[TimeWindow], start: usize, start_time: f64) -> Vec<usize> {
+    // time oriented nearest neighbor: next is the location whose service can start soonest among those reached before
+    // their window closes, or the least late one if none can be. Dead ends append the rest by closing time
+    let n = distance_matrix.len();
+    let mut visited = vec![false; n];
+    let mut path = vec![start];
+    visited[start] = true;
+    let mut time = start_time.max(windows[start].earliest) + windows[start].service;
+    while path.len() < n {
+        let current = *path.last().unwrap();
+        let next = (0..n)
+            .filter(|&node| !visited[node] && distance_matrix[current][node].is_finite())
+            .map(|node| {
+                let arrival = time + distance_matrix[current][node];
+                ((arrival - windows[node].latest).max(0.0), arrival.max(windows[node].earliest), node)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        let Some((_, service_start, node)) = next else {
+            let mut rest: Vec<usize> = (0..n).filter(|&node| !visited[node]).collect();
+            rest.sort_by(|&a, &b| windows[a].latest.total_cmp(&windows[b].latest));
+            path.extend(rest);
+            break;
+        };
+        visited[node] = true;
+        path.push(node);
+        time = service_start + windows[node].service;
+    }
+    path
+}
+
+struct Partial {
+    path: Vec<usize>,
+    visited: Vec<bool>,
+    time: f64, // departure from the last location
+    flight: f64,
+}
+
+fn on_time_search(distance_matrix: &[Vec<f64>], windows: &[TimeWindow], start_time: f64) -> Option<Vec<usize>> {
+    // beam search over routes that are never late, the BEAM_WIDTH partial routes that are done soonest go on. A partial
+    // route is dropped once some unvisited location can't be reached before its window closes even over its cheapest arc in
+    let n = distance_matrix.len();
+    let cheapest_in: Vec<f64> = (0..n).map(|to| (0..n).map(|from| distance_matrix[from][to]).fold(f64::INFINITY, f64::min)).collect();
+    let hopeless = |visited: &[bool], time: f64| (0..n).any(|node| !visited[node] && windows[node].latest < time + cheapest_in[node]);
+    let mut beam: Vec<Partial> = (0..n)
+        .filter(|&start| start_time <= windows[start].latest)
+        .map(|start| {
+            let mut visited = vec![false; n];
+            visited[start] = true;
+            Partial { path: vec![start], visited, time: start_time.max(windows[start].earliest) + windows[start].service, flight: 0.0 }
+        })
+        .filter(|partial| !hopeless(&partial.visited, partial.time))
+        .collect();
+    for _ in 1..n {
+        let mut children: Vec<(f64, f64, usize, usize)> = Vec::new(); // time, flight, parent, next
+        for (parent, partial) in beam.iter().enumerate() {
+            let current = *partial.path.last().unwrap();
+            for node in (0..n).filter(|&node| !partial.visited[node]) {
+                let arrival = partial.time + distance_matrix[current][node];
+                if arrival <= windows[node].latest {
+                    children.push((arrival.max(windows[node].earliest) + windows[node].service, partial.flight + distance_matrix[current][node], parent, node));
+                }
+            }
+        }
+        children.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        let mut next_beam = Vec::with_capacity(BEAM_WIDTH);
+        for (time, flight, parent, node) in children {
+            let mut visited = beam[parent].visited.clone();
+            visited[node] = true;
+            if hopeless(&visited, time) {
+                continue;
+            }
+            let mut path = beam[parent].path.clone();
+            path.push(node);
+            next_beam.push(Partial { path, visited, time, flight });
+            if next_beam.len() == BEAM_WIDTH {
+                break;
+            }
+        }
+        beam = next_beam;
+    }
+    beam.into_iter().min_by(|a, b| a.flight.total_cmp(&b.flight)).map(|partial| partial.path)
+}
+
+fn insertion(distance_matrix: &[Vec<f64>], windows: &[TimeWindow], config: &TimeWindowConfig) -> Vec<usize> {
+    // locations by closing time, each inserted where the partial route's objective grows least
+    let mut order: Vec<usize> = (0..distance_matrix.len()).collect();
+    order.sort_by(|&a, &b| windows[a].latest.total_cmp(&windows[b].latest).then(windows[a].earliest.total_cmp(&windows[b].earliest)));
+    let mut path: Vec<usize> = Vec::with_capacity(order.len());
+    for node in order {
+        let mut best: Option<((f64, f64), usize)> = None;
+        for position in 0..=path.len() {
+            path.insert(position, node);
+            let key = objective_key(&evaluate(distance_matrix, windows, &path, config.start_time), config);
+            path.remove(position);
+            if best.is_none_or(|(best_key, _)| better(key, best_key)) {
+                best = Some((key, position));
+            }
+        }
+        path.insert(best.unwrap().1, node);
+    }
+    path
+}
+
+fn improve(distance_matrix: &[Vec<f64>], windows: &[TimeWindow], path: &mut Vec<usize>, config: &TimeWindowConfig) {
+    // first improvement relocate and swap, moves stay within RELOCATE_WINDOW positions
+    let key_of = |path: &[usize]| objective_key(&evaluate(distance_matrix, windows, path, config.start_time), config);
+    let n = path.len();
+    let mut current = key_of(path);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..n {
+            for j in i.saturating_sub(RELOCATE_WINDOW)..(i + RELOCATE_WINDOW + 1).min(n) {
+                if i == j {
+                    continue;
+                }
+                let mut candidate = path.clone();
+                let node = candidate.remove(i);
+                candidate.insert(j, node);
+                let key = key_of(&candidate);
+                if better(key, current) {
+                    *path = candidate;
+                    current = key;
+                    improved = true;
+                    continue;
+                }
+                if j > i {
+                    let mut candidate = path.clone();
+                    candidate.swap(i, j);
+                    let key = key_of(&candidate);
+                    if better(key, current) {
+                        *path = candidate;
+                        current = key;
+                        improved = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn time_window_route(distance_matrix: &[Vec<f64>], windows: &[TimeWindow], config: &TimeWindowConfig) -> Result<Schedule, TimeWindowError> {
+    // nearest construction from every start, the insertion construction and the on-time search, local search on the best, then the schedule. Relaxed mode always returns the
+    // schedule (infinite cost if it needs a missing arc), strict mode errors unless every arrival is inside its window
+    let n = distance_matrix.len();
+    if n == 0 || windows.len() < n {
+        return Err(TimeWindowError::NoRoute);
+    }
+    let key_of = |path: &[usize]| objective_key(&evaluate(distance_matrix, windows, path, config.start_time), config);
+    let mut path = (0..n)
+        .map(|start| construct(distance_matrix, windows, start, config.start_time))
+        .chain(std::iter::once(insertion(distance_matrix, windows, config)))
+        .chain(on_time_search(distance_matrix, windows, config.start_time))
+        .min_by(|a, b| {
+            let (key_a, key_b) = (key_of(a), key_of(b));
+            key_a.0.total_cmp(&key_b.0).then(key_a.1.total_cmp(&key_b.1))
+        })
+        .unwrap();
+    improve(distance_matrix, windows, &mut path, config);
+    let schedule = schedule_path(distance_matrix, windows, &path, config.start_time);
+    if config.strict {
+        if schedule.cost.is_infinite() {
+            return Err(TimeWindowError::NoRoute);
+        }
+        if schedule.total_lateness > 0.0 {
+            let late_stops = schedule.stops.iter().filter(|stop| stop.lateness > 0.0).map(|stop| stop.node).collect();
+            return Err(TimeWindowError::WindowsViolated { late_stops, best: Box::new(schedule) });
+        }
+    }
+    Ok(schedule)
+}
@@ -0,0 +1,97 @@
+// Rewrites directed instances into symmetric ones so symmetric-only solvers and bounds can run on our sparse worlds.
+// Jonker-Volgenant: every location i gets a ghost i + n. The edge (i, ghost i) is free, the edge (ghost i, j) costs
+// cost(i, j) + big_m, and edges inside either half do not exist. Any tour has to alternate halves, and a tour that uses
+// all n free edges pays big_m exactly n times, so with big_m above any directed tour cost the optimal symmetric tour
+// is the optimal directed tour read i -> j wherever ghost i sits next to j.
+
+pub struct SymmetricInstance {
+    pub matrix: Vec<Vec<f64>>, // 2 * cities locations, infinity where there is no edge
+    pub offset: f64,           // symmetric tour cost minus offset is the directed cost
+    pub cities: usize,         // locations in the directed instance the matrix was built from, including the dummy
+    pub open_path: bool,       // a dummy location with free arcs was added so closed tours map back to open paths
+}
+
+pub fn asymmetric_to_symmetric(distance_matrix: &[Vec<f64>], open_path: bool) -> SymmetricInstance {
+    // open_path adds a dummy location with free arcs to and from everyone, a closed tour through it is an open path
+    let mut directed: Vec<Vec<f64>> = distance_matrix.to_vec();
+    if open_path {
+        for row in directed.iter_mut() {
+            row.push(0.0);
+        }
+        let mut dummy_row = vec![0.0; directed.len() + 1];
+        dummy_row[directed.len()] = f64::INFINITY;
+        directed.push(dummy_row);
+    }
+    let n = directed.len();
+    // larger than any directed tour, every tour is paid with one of each location's outgoing arcs
+    let big_m = 1.0 + directed.iter().enumerate().map(|(i, row)| row.iter().enumerate()
+        .filter(|&(j, cost)| i != j && cost.is_finite())
+        .fold(0.0, |largest: f64, (_, &cost)| largest.max(cost))).sum::<f64>();
+
+    let mut matrix = vec![vec![f64::INFINITY; 2 * n]; 2 * n];
+    for i in 0..n {
+        matrix[i][i + n] = 0.0;
+        matrix[i + n][i] = 0.0;
+        for j in 0..n {
+            if i != j && directed[i][j].is_finite() {
+                matrix[i + n][j] = directed[i][j] + big_m;
+                matrix[j][i + n] = directed[i][j] + big_m;
+            }
+        }
+    }
+    SymmetricInstance { matrix, offset: n as f64 * big_m, cities: n, open_path }
+}
+
+impl SymmetricInstance {
+    pub fn directed_tour(&self, symmetric_tour: &[usize]) -> Option<Vec<usize>> {
+        // maps a closed tour over the 2n locations (the start may be repeated at the end) back to the directed order.
+        // For an open path instance the dummy is cut out, otherwise the result is the closed tour without the repeat.
+        // None if the tour skips a free edge, which only happens when it is worse than every tour that doesn't
+        let n = self.cities;
+        let tour = match symmetric_tour {
+            [first, .., last] if symmetric_tour.len() == 2 * n + 1 && first == last => &symmetric_tour[..2 * n],
+            _ => symmetric_tour,
+        };
+        if tour.len() != 2 * n || n == 0 {
+            return None;
+        }
+        // walk the tour in the direction where every location is followed by its ghost
+        let start = tour.iter().position(|&node| node < n)?;
+        let forward = tour[(start + 1) % (2 * n)] == tour[start] + n;
+        let ordered: Vec<usize> = if forward {
+            (0..2 * n).map(|k| tour[(start + k) % (2 * n)]).collect()
+        } else {
+            (0..2 * n).map(|k| tour[(start + 2 * n - k) % (2 * n)]).collect()
+        };
+        let mut directed = Vec::with_capacity(n);
+        let mut seen = vec![false; n];
+        for pair in ordered.chunks(2) {
+            if pair[0] >= n || pair[1] != pair[0] + n || seen[pair[0]] {
+                return None;
+            }
+            seen[pair[0]] = true;
+            directed.push(pair[0]);
+        }
+        if self.open_path {
+            let dummy = n - 1;
+            let position = directed.iter().position(|&node| node == dummy)?;
+            directed.rotate_left(position);
+            directed.remove(0);
+        }
+        Some(directed)
+    }
+
+    pub fn symmetric_tour(&self, directed: &[usize]) -> Vec<usize> {
+        // the other direction, a directed tour (or open path for an open path instance) as a closed symmetric tour
+        let n = self.cities;
+        let mut cities = directed.to_vec();
+        if self.open_path {
+            cities.insert(0, n - 1);
+        }
+        cities.iter().flat_map(|&node| [node, node + n]).collect()
+    }
+
+    pub fn directed_cost(&self, symmetric_cost: f64) -> f64 {
+        symmetric_cost - self.offset
+    }
+}